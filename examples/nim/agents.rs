@@ -1,4 +1,5 @@
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 use game_logic::core::Agent;
 use game_logic::tournament::AgentFactory;
@@ -33,11 +34,23 @@ impl Agent for NimPerfectAgent {
 
 pub struct NimRandomAgent {
     max_takes: u32,
+    rng: StdRng,
 }
 
 impl NimRandomAgent {
     pub fn new(max_takes: u32) -> Self {
-        NimRandomAgent { max_takes }
+        NimRandomAgent {
+            max_takes,
+            rng: StdRng::from_rng(&mut rand::rng()),
+        }
+    }
+
+    /// Creates an agent that makes the same moves every time for the same seed.
+    pub fn with_seed(max_takes: u32, seed: u64) -> Self {
+        NimRandomAgent {
+            max_takes,
+            rng: StdRng::seed_from_u64(seed),
+        }
     }
 }
 
@@ -45,19 +58,21 @@ impl Agent for NimRandomAgent {
     type Game = NimGameLogic;
 
     fn calculate_next_move(&mut self, new_state: NimState) -> NimMove {
-        let mut rng = rand::rng();
         NimMove {
-            amount: rng.random_range(1..=self.max_takes.min(new_state.pile_size)),
+            amount: self.rng.random_range(1..=self.max_takes.min(new_state.pile_size)),
         }
     }
 
     fn digest_state(&mut self, _new_state: NimState) {}
 }
 
+// Only the integration tests host tournaments with the factories
+#[allow(dead_code)]
 pub struct PerfectFactory {
     mod_base: u32,
 }
 
+#[allow(dead_code)]
 impl PerfectFactory {
     pub fn new(mod_base: u32) -> Self {
         PerfectFactory { mod_base }
@@ -74,10 +89,13 @@ impl AgentFactory for PerfectFactory {
     }
 }
 
+// Only the integration tests host tournaments with the factories
+#[allow(dead_code)]
 pub struct RandomFactory {
     max_takes: u32,
 }
 
+#[allow(dead_code)]
 impl RandomFactory {
     pub fn new(max_takes: u32) -> Self {
         RandomFactory { max_takes }
@@ -88,8 +106,10 @@ impl AgentFactory for RandomFactory {
     type Agent = Box<dyn Agent<Game = NimGameLogic> + Send>;
 
     fn create_agent(&self) -> Self::Agent {
-        Box::new(NimRandomAgent {
-            max_takes: self.max_takes,
-        })
+        Box::new(NimRandomAgent::new(self.max_takes))
+    }
+
+    fn create_agent_seeded(&self, seed: u64) -> Self::Agent {
+        Box::new(NimRandomAgent::with_seed(self.max_takes, seed))
     }
 }
@@ -1,11 +1,16 @@
 use std::{
     cmp::Ordering,
     collections::{HashMap, HashSet},
+    fmt,
+    num::ParseIntError,
+    str::FromStr,
 };
 
-use game_logic::core::{FinalScores, GameError, GameLogic, Id, LegalMoves, MoveResult};
+use game_logic::core::{Determinize, GameError, GameLogic, Id, LegalMoves, MoveResult, Outcome};
+use rand::rngs::StdRng;
 
 #[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NimPlayerId(pub u32);
 
 impl Id for NimPlayerId {}
@@ -16,11 +21,29 @@ pub struct NimGameLogic {
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NimMove {
     pub amount: u32,
 }
 
-#[derive(Clone, Debug)]
+impl fmt::Display for NimMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "take {}", self.amount)
+    }
+}
+
+/// Parses "take 3" as well as "3".
+impl FromStr for NimMove {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let amount = s.trim().trim_start_matches("take").trim().parse()?;
+        Ok(NimMove { amount })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NimState {
     pub pile_size: u32,
     pub players: Vec<NimPlayerId>,
@@ -38,6 +61,12 @@ impl NimState {
     }
 }
 
+impl fmt::Display for NimState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Matches left: {}", self.pile_size)
+    }
+}
+
 impl GameLogic for NimGameLogic {
     type PID = NimPlayerId;
     type Move = NimMove;
@@ -49,7 +78,7 @@ impl GameLogic for NimGameLogic {
         (
             NimState {
                 pile_size: self.initial_pile_size,
-                players: players.iter().copied().collect(),
+                players: players.to_vec(),
             },
             HashSet::from([players[0]]),
         )
@@ -90,7 +119,7 @@ impl GameLogic for NimGameLogic {
                     Ordering::Equal => {
                         // Player wins by taking the last match
                         state.pile_size = 0;
-                        Ok(MoveResult::GameOver(vec![(player, 1)].into_iter().collect()))
+                        Ok(MoveResult::Finished(Outcome::win(player, &state.players)))
                     }
                     Ordering::Greater => {
                         // Update state in-place
@@ -128,3 +157,15 @@ impl LegalMoves for NimGameLogic {
             .collect()
     }
 }
+
+impl Determinize for NimGameLogic {
+    fn determinize(
+        &self,
+        state: &Self::MaskedState,
+        player: Self::PID,
+        _rng: &mut StdRng,
+    ) -> (Self::State, HashSet<Self::PID>) {
+        // Nothing is hidden, and the player asked for a move is the one to move
+        (state.clone(), HashSet::from([player]))
+    }
+}
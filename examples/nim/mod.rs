@@ -1,5 +1,5 @@
 pub mod game;
 pub mod agents;
 
-pub use game::{NimGameLogic, NimMove, NimPlayerId, NimState};
-pub use agents::{NimPerfectAgent, NimRandomAgent, PerfectFactory, RandomFactory};
+pub use game::{NimGameLogic, NimPlayerId};
+pub use agents::{NimPerfectAgent, NimRandomAgent};
@@ -2,11 +2,13 @@ mod nim;
 
 use indexmap::IndexMap;
 use std::io::{self, Write};
+use std::sync::Arc;
 
-use game_logic::simulate_game;
+use game_logic::agents::HumanAgent;
 use game_logic::core::Agent;
-use nim::game::{NimGameLogic, NimPlayerId};
-use nim::agents::{NimPerfectAgent, NimRandomAgent};
+use game_logic::simulate_game;
+use game_logic::simulation::{simulate_game_with, IllegalMovePolicy, SimulationConfig};
+use nim::{NimGameLogic, NimPerfectAgent, NimPlayerId, NimRandomAgent};
 
 fn main() {
     println!("=== Nim Game Example ===\n");
@@ -62,9 +64,43 @@ fn get_input(prompt: &str, default: u32) -> u32 {
     input.trim().parse().unwrap_or(default)
 }
 
-fn play_human_vs_ai(_game: &NimGameLogic, _ai: Box<dyn Agent<Game = NimGameLogic>>) {
-    println!("\nHuman vs AI mode not yet implemented!");
-    println!("This would require interactive gameplay support.");
+fn play_human_vs_ai(game: &NimGameLogic, ai: Box<dyn Agent<Game = NimGameLogic>>) {
+    let rules = Arc::new(NimGameLogic {
+        initial_pile_size: game.initial_pile_size,
+        max_takes: game.max_takes,
+    });
+    let human = HumanAgent::stdio().with_legal_moves(rules);
+
+    let mut agents: IndexMap<NimPlayerId, Box<dyn Agent<Game = NimGameLogic>>> = [
+        (
+            NimPlayerId(1),
+            Box::new(human) as Box<dyn Agent<Game = NimGameLogic>>,
+        ),
+        (NimPlayerId(2), ai),
+    ]
+    .into();
+
+    // Let the human try again after a typo instead of ending the game
+    let config = SimulationConfig {
+        max_turns: Some(1000),
+        illegal_move_policy: IllegalMovePolicy::retry(10),
+        ..SimulationConfig::default()
+    };
+
+    println!("\nYou move first. Type a number from the list, or \"take N\".\n");
+
+    match simulate_game_with(game, &mut agents, &config).result {
+        Ok(result) => {
+            if result.get(&NimPlayerId(1)).is_some_and(|&score| score > 0) {
+                println!("You win!");
+            } else {
+                println!("The AI wins!");
+            }
+        }
+        Err(e) => {
+            println!("Game error: {}", e);
+        }
+    }
 }
 
 fn simulate_ai_vs_ai(
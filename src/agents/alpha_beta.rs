@@ -0,0 +1,416 @@
+use std::{collections::HashMap, hash::Hash, sync::Arc, time::Instant};
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::core::{Agent, Determinize, GameLogic, Id, LegalMoves, Outcome};
+
+use super::search::{reward, Position, SearchBudget};
+
+/// The score of a won game. Games won sooner score a little more, and lost games score the
+/// negation. Evaluations should stay well within it.
+pub const WIN_SCORE: i32 = 1_000_000;
+
+/// Larger than any score, so that it can be negated.
+const INFINITY: i32 = i32::MAX - 1;
+
+/// Scores positions for an [`AlphaBetaAgent`] once it has searched as deep as it can.
+///
+/// Closures taking the game, the state and the player to move implement it, with the default
+/// move ordering.
+pub trait Evaluator<G: GameLogic> {
+    /// Estimates how good `state` is for `player`, who is about to move: positive is good, 0 is
+    /// even. The game is zero-sum, so the opponent's estimate is the negation.
+    fn evaluate(&self, game: &G, state: &G::State, player: G::PID) -> i32;
+
+    /// Returns how promising `player_move` looks for `player`, who is about to move. Moves are
+    /// searched from the highest priority down, and searching good moves first lets alpha-beta
+    /// skip more of the others. Defaults to 0, keeping the order of `LegalMoves::legal_moves`.
+    fn move_priority(
+        &self,
+        _game: &G,
+        _state: &G::State,
+        _player: G::PID,
+        _player_move: &G::Move,
+    ) -> i32 {
+        0
+    }
+}
+
+impl<G, F> Evaluator<G> for F
+where
+    G: GameLogic,
+    F: Fn(&G, &G::State, G::PID) -> i32,
+{
+    fn evaluate(&self, game: &G, state: &G::State, player: G::PID) -> i32 {
+        self(game, state, player)
+    }
+}
+
+/// Settings of an [`AlphaBetaAgent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AlphaBetaConfig {
+    /// How long to search for every move. `Iterations` is the deepest search, in moves.
+    /// With `Time`, searches deepen until the time is up, and the last complete one counts.
+    pub budget: SearchBudget,
+    /// The most positions the transposition table remembers. It is emptied once full.
+    pub table_size: usize,
+}
+
+impl Default for AlphaBetaConfig {
+    fn default() -> Self {
+        AlphaBetaConfig {
+            budget: SearchBudget::Iterations(6),
+            table_size: 1 << 20,
+        }
+    }
+}
+
+/// What an [`AlphaBetaAgent`] found in its last search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchInfo {
+    /// The depth of the last complete search, in moves.
+    pub depth: usize,
+    /// The score of the chosen move for the agent. Above `WIN_SCORE / 2` means a forced win.
+    pub score: i32,
+    /// The number of positions visited, over every depth.
+    pub nodes: u64,
+}
+
+#[derive(Clone, Copy)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+struct Entry {
+    depth: usize,
+    /// The score, with wins and losses counted from this position rather than the root.
+    score: i32,
+    bound: Bound,
+    /// The index of the best move in `LegalMoves::legal_moves`.
+    best: usize,
+    /// Whether a position below this one was evaluated rather than searched to the end.
+    cut_off: bool,
+}
+
+type Table<G> = HashMap<(<G as GameLogic>::State, <G as GameLogic>::PID), Entry>;
+
+/// An agent for two-player zero-sum games with perfect information, searching the game tree
+/// with alpha-beta pruning.
+///
+/// The search deepens one move at a time, trying the best move of the previous depth first,
+/// and remembers the positions it scored in a transposition table that lasts for the whole
+/// game. Positions that aren't over at the deepest level are scored by the [`Evaluator`]; won
+/// and lost games are scored by the game, so small games like Nim are solved with an evaluator
+/// that always returns 0.
+///
+/// The state is rebuilt from the agent's view with `Determinize::determinize`, which should
+/// return the state itself. Players moving twice in a row are supported; simultaneous moves are
+/// searched as if the other player knew the agent's move.
+///
+/// The agent learns its ID from `Agent::on_game_start`, which the simulation functions call.
+pub struct AlphaBetaAgent<G: GameLogic, E> {
+    game: Arc<G>,
+    evaluator: E,
+    config: AlphaBetaConfig,
+    table: Table<G>,
+    rng: StdRng,
+    pid: Option<G::PID>,
+    players: Vec<G::PID>,
+    last_search: Option<SearchInfo>,
+}
+
+impl<G: GameLogic, E> AlphaBetaAgent<G, E> {
+    /// Creates an agent searching `game`, scoring unfinished positions with `evaluator`.
+    /// `config.budget` sets the depth: `Iterations(depth)` searches that many moves ahead, at
+    /// least one, and `Time` deepens until the time is up. The transposition table remembers
+    /// at most `config.table_size` positions.
+    pub fn new(game: Arc<G>, evaluator: E, config: AlphaBetaConfig) -> Self {
+        AlphaBetaAgent {
+            game,
+            evaluator,
+            config,
+            table: HashMap::new(),
+            // Perfect-information games don't sample anything, but the trait asks for randomness
+            rng: StdRng::seed_from_u64(0),
+            pid: None,
+            players: Vec::new(),
+            last_search: None,
+        }
+    }
+
+    /// Returns what the last search found, if the agent has moved yet.
+    pub fn last_search(&self) -> Option<SearchInfo> {
+        self.last_search
+    }
+}
+
+impl<G, E> Agent for AlphaBetaAgent<G, E>
+where
+    G: Determinize + LegalMoves,
+    G::State: Clone + Hash + Eq,
+    G::Move: Clone,
+    E: Evaluator<G>,
+{
+    type Game = G;
+
+    fn digest_state(&mut self, _new_state: G::MaskedState) {}
+
+    /// # Panics
+    /// Panics if the agent wasn't told its ID with `on_game_start`, or if it has no legal moves.
+    fn calculate_next_move(&mut self, new_state: G::MaskedState) -> G::Move {
+        let pid = self
+            .pid
+            .expect("AlphaBetaAgent needs on_game_start to learn its ID before moving");
+        let root =
+            Position::determinize(&*self.game, &self.players, &new_state, pid, &mut self.rng);
+        let mut moves = root.legal_moves();
+        assert!(
+            !moves.is_empty(),
+            "AlphaBetaAgent has no legal moves to choose from"
+        );
+
+        let start = Instant::now();
+        let (max_depth, time_limit) = match self.config.budget {
+            SearchBudget::Iterations(depth) => (depth.max(1), None),
+            SearchBudget::Time(limit) => (usize::MAX, Some(limit)),
+        };
+        let mut searcher = Searcher {
+            game: &*self.game,
+            evaluator: &self.evaluator,
+            table: &mut self.table,
+            table_size: self.config.table_size,
+            deadline: None,
+            nodes: 0,
+            aborted: false,
+            cut_off: false,
+        };
+        let key = (root.state().clone(), pid);
+        let mut info = SearchInfo {
+            depth: 0,
+            score: 0,
+            nodes: 0,
+        };
+        let mut best = 0;
+
+        for depth in 1..=max_depth {
+            searcher.cut_off = false;
+            let score = searcher.negamax(&root, depth, 0, -INFINITY, INFINITY, pid);
+            if searcher.aborted {
+                break;
+            }
+            best = searcher.table.get(&key).map_or(best, |entry| entry.best);
+            info.depth = depth;
+            info.score = score;
+            // The first depth always completes, so there is a move to play
+            searcher.deadline = time_limit.map(|limit| start + limit);
+            if !searcher.cut_off || score.abs() > WIN_SCORE / 2 {
+                // The whole game was searched, or its result is known
+                break;
+            }
+        }
+
+        info.nodes = searcher.nodes;
+        self.last_search = Some(info);
+        moves.swap_remove(best)
+    }
+
+    fn on_game_start(&mut self, pid: G::PID, players: &[G::PID]) {
+        self.pid = Some(pid);
+        self.players = players.to_vec();
+        self.table.clear();
+    }
+}
+
+struct Searcher<'s, G: GameLogic, E> {
+    game: &'s G,
+    evaluator: &'s E,
+    table: &'s mut Table<G>,
+    table_size: usize,
+    deadline: Option<Instant>,
+    nodes: u64,
+    /// Set once the deadline passes. The search unwinds and its result is ignored.
+    aborted: bool,
+    /// Set when a position was evaluated rather than searched to the end of the game.
+    cut_off: bool,
+}
+
+impl<G, E> Searcher<'_, G, E>
+where
+    G: LegalMoves,
+    G::State: Clone + Hash + Eq,
+    G::Move: Clone,
+    E: Evaluator<G>,
+{
+    /// Returns the score of `position` for `perspective`, searching `depth` more moves.
+    /// `ply` is the number of moves made since the root.
+    fn negamax(
+        &mut self,
+        position: &Position<'_, G>,
+        depth: usize,
+        ply: usize,
+        mut alpha: i32,
+        beta: i32,
+        perspective: G::PID,
+    ) -> i32 {
+        self.nodes += 1;
+        if self.nodes % 1024 == 0
+            && self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.aborted = true;
+        }
+        if self.aborted {
+            return 0;
+        }
+
+        if let Some(outcome) = position.outcome() {
+            return terminal_score(outcome, perspective, ply);
+        }
+        let Some(mover) = position.next_player() else {
+            return 0;
+        };
+        if mover != perspective {
+            return -self.negamax(position, depth, ply, -beta, -alpha, mover);
+        }
+        if depth == 0 {
+            self.cut_off = true;
+            return self.evaluator.evaluate(self.game, position.state(), mover);
+        }
+
+        // Only whole turns are remembered, since moves chosen mid-turn aren't in the state
+        let key = position
+            .at_turn_start()
+            .then(|| (position.state().clone(), mover));
+        let mut table_best = None;
+        if let Some(entry) = key.as_ref().and_then(|key| self.table.get(key)) {
+            table_best = Some(entry.best);
+            let score = from_table(entry.score, ply);
+            let usable = match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => score >= beta,
+                Bound::Upper => score <= alpha,
+            };
+            if entry.depth >= depth && usable {
+                self.cut_off |= entry.cut_off;
+                return score;
+            }
+        }
+
+        let moves = position.legal_moves();
+        if moves.is_empty() {
+            // The game is stuck, which counts as a draw
+            return 0;
+        }
+        let order = self.order(position, mover, &moves, table_best);
+
+        // Track whether this position's own subtree is cut off, for its table entry
+        let outer_cut_off = std::mem::replace(&mut self.cut_off, false);
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best = order[0];
+        for index in order {
+            let mut child = position.clone();
+            child.play(moves[index].clone());
+            let score = self.negamax(&child, depth - 1, ply + 1, alpha, beta, mover);
+            if self.aborted {
+                return 0;
+            }
+            if score > best_score {
+                best_score = score;
+                best = index;
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        if let Some(key) = key {
+            if self.table.len() >= self.table_size {
+                self.table.clear();
+            }
+            let bound = if best_score <= original_alpha {
+                Bound::Upper
+            } else if best_score >= beta {
+                Bound::Lower
+            } else {
+                Bound::Exact
+            };
+            self.table.insert(
+                key,
+                Entry {
+                    depth,
+                    score: to_table(best_score, ply),
+                    bound,
+                    best,
+                    cut_off: self.cut_off,
+                },
+            );
+        }
+        self.cut_off |= outer_cut_off;
+        best_score
+    }
+
+    /// Returns the indices of `moves` in the order to search them: the best move found
+    /// before first, then by the evaluator's priority.
+    fn order(
+        &self,
+        position: &Position<'_, G>,
+        mover: G::PID,
+        moves: &[G::Move],
+        table_best: Option<usize>,
+    ) -> Vec<usize> {
+        let priorities: Vec<i32> = moves
+            .iter()
+            .map(|player_move| {
+                self.evaluator
+                    .move_priority(self.game, position.state(), mover, player_move)
+            })
+            .collect();
+        let mut order: Vec<usize> = (0..moves.len()).collect();
+        order.sort_by_key(|&index| std::cmp::Reverse(priorities[index]));
+        if let Some(best) = table_best.filter(|&best| best < moves.len()) {
+            order.retain(|&index| index != best);
+            order.insert(0, best);
+        }
+        order
+    }
+}
+
+/// Scores a finished game for `player`, preferring quick wins and slow losses.
+fn terminal_score<PID: Id>(outcome: &Outcome<PID>, player: PID, ply: usize) -> i32 {
+    // 1 for a win, 0 for a draw, -1 for a loss
+    let result = 2.0 * reward(outcome, player) - 1.0;
+    let magnitude = WIN_SCORE - ply.min(WIN_SCORE as usize / 2) as i32;
+    (result * magnitude as f64).round() as i32
+}
+
+/// Converts a score found `ply` moves below the root to one counted from its own position, so
+/// that wins and losses found through a transposition keep their distance.
+fn to_table(score: i32, ply: usize) -> i32 {
+    let ply = ply.min(WIN_SCORE as usize / 2) as i32;
+    if score > WIN_SCORE / 2 {
+        score + ply
+    } else if score < -WIN_SCORE / 2 {
+        score - ply
+    } else {
+        score
+    }
+}
+
+/// Converts a score from the transposition table back to one counted from the root.
+fn from_table(score: i32, ply: usize) -> i32 {
+    let ply = ply.min(WIN_SCORE as usize / 2) as i32;
+    if score > WIN_SCORE / 2 {
+        score - ply
+    } else if score < -WIN_SCORE / 2 {
+        score + ply
+    } else {
+        score
+    }
+}
@@ -0,0 +1,231 @@
+use std::{
+    fmt::{self, Debug, Display},
+    io::{self, BufRead, Write},
+    str::FromStr,
+    sync::Arc,
+};
+
+use crate::core::{Agent, FinalScores, GameError, GameLogic, LegalMoves};
+use crate::simulation::SimulationError;
+
+type LegalMovesFn<G> =
+    fn(&G, &<G as GameLogic>::MaskedState, <G as GameLogic>::PID) -> Vec<<G as GameLogic>::Move>;
+
+/// An agent asking a person for its moves, for any game whose moves can be parsed from text and
+/// whose masked states can be displayed.
+///
+/// The first state the player sees is shown in full, and after that only the lines that changed
+/// (`- ` for removed lines, `+ ` for added ones). When the agent knows the game's legal moves,
+/// see [`HumanAgent::with_legal_moves`], they are listed as numbered choices: typing a number
+/// picks from the list, and anything else is parsed with `FromStr`. Input that doesn't parse is
+/// asked for again. Moves the game rejects are reported, and with
+/// `IllegalMovePolicy::Retry` the player is asked for another one.
+///
+/// Any reader and writer can be used, e.g. byte slices and a `Vec<u8>` in tests, or
+/// [`HumanAgent::stdio`] for a terminal.
+pub struct HumanAgent<G: GameLogic, R, W> {
+    input: R,
+    output: W,
+    legal_moves: Option<(Arc<G>, LegalMovesFn<G>)>,
+    pid: Option<G::PID>,
+    players: Vec<G::PID>,
+    /// The last state shown, as displayed.
+    last_state: Option<String>,
+}
+
+impl<G: GameLogic, R, W> HumanAgent<G, R, W> {
+    /// Creates an agent that shows the game's states on `output` and reads the player's moves,
+    /// one per line, from `input`. Call [`HumanAgent::with_legal_moves`] to offer numbered
+    /// choices as well.
+    pub fn new(input: R, output: W) -> Self {
+        HumanAgent {
+            input,
+            output,
+            legal_moves: None,
+            pid: None,
+            players: Vec::new(),
+            last_state: None,
+        }
+    }
+
+    /// Lists the legal moves of `game` as numbered choices whenever a move is asked for.
+    pub fn with_legal_moves(mut self, game: Arc<G>) -> Self
+    where
+        G: LegalMoves,
+        G::Move: Clone,
+    {
+        self.legal_moves = Some((game, |game, state, player| game.legal_moves(state, player)));
+        self
+    }
+
+    /// Returns the writer the agent talks to the player through.
+    pub fn output(&self) -> &W {
+        &self.output
+    }
+}
+
+impl<G: GameLogic> HumanAgent<G, io::StdinLock<'static>, io::Stdout> {
+    /// Creates an agent for a player at the terminal.
+    pub fn stdio() -> Self {
+        HumanAgent::new(io::stdin().lock(), io::stdout())
+    }
+}
+
+impl<G, R, W> HumanAgent<G, R, W>
+where
+    G: GameLogic,
+    G::MaskedState: Display,
+    R: BufRead,
+    W: Write,
+{
+    fn write(&mut self, text: fmt::Arguments<'_>) {
+        self.output
+            .write_fmt(text)
+            .and_then(|()| self.output.flush())
+            .expect("HumanAgent couldn't write to its output");
+    }
+
+    /// Shows `state` in full the first time, then the lines that changed since the last one.
+    fn show(&mut self, state: &G::MaskedState) {
+        let text = state.to_string();
+        let shown = match &self.last_state {
+            None => text.clone(),
+            Some(last) => diff_lines(last, &text),
+        };
+        if !shown.is_empty() {
+            self.write(format_args!("{}\n", shown.trim_end()));
+        }
+        self.last_state = Some(text);
+    }
+}
+
+impl<G, R, W> Agent for HumanAgent<G, R, W>
+where
+    G: GameLogic,
+    G::PID: Debug,
+    G::Move: FromStr + Display + Clone,
+    <G::Move as FromStr>::Err: Display,
+    G::MaskedState: Display,
+    R: BufRead,
+    W: Write,
+{
+    type Game = G;
+
+    fn digest_state(&mut self, new_state: G::MaskedState) {
+        self.show(&new_state);
+    }
+
+    /// # Panics
+    /// Panics if the input ends or fails before a move was read, or if the output fails.
+    fn calculate_next_move(&mut self, new_state: G::MaskedState) -> G::Move {
+        self.show(&new_state);
+        let moves = match (&self.legal_moves, self.pid) {
+            (Some((game, legal_moves)), Some(pid)) => legal_moves(game, &new_state, pid),
+            _ => Vec::new(),
+        };
+        if !moves.is_empty() {
+            self.write(format_args!("Your moves:\n"));
+            for (index, player_move) in moves.iter().enumerate() {
+                self.write(format_args!("  {}) {}\n", index + 1, player_move));
+            }
+        }
+
+        loop {
+            self.write(format_args!("Your move: "));
+            let mut line = String::new();
+            let read = self
+                .input
+                .read_line(&mut line)
+                .expect("HumanAgent couldn't read from its input");
+            assert!(
+                read > 0,
+                "HumanAgent's input ended before a move was chosen"
+            );
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let choice = line
+                .parse::<usize>()
+                .ok()
+                .and_then(|number| number.checked_sub(1))
+                .and_then(|index| moves.get(index));
+            if let Some(player_move) = choice {
+                return player_move.clone();
+            }
+            match line.parse::<G::Move>() {
+                Ok(player_move) => return player_move,
+                Err(error) => self.write(format_args!(
+                    "Couldn't read {:?} as a move: {}\n",
+                    line, error
+                )),
+            }
+        }
+    }
+
+    fn on_game_start(&mut self, pid: G::PID, players: &[G::PID]) {
+        self.pid = Some(pid);
+        self.players = players.to_vec();
+        self.last_state = None;
+        self.write(format_args!("You are {:?}, playing {:?}\n", pid, players));
+    }
+
+    fn on_game_over(&mut self, scores: &FinalScores<G::PID>) {
+        self.write(format_args!("Game over\n"));
+        for player in self.players.clone() {
+            let you = if Some(player) == self.pid {
+                " (you)"
+            } else {
+                ""
+            };
+            match scores.get(&player) {
+                Some(score) => self.write(format_args!("  {:?}{}: {}\n", player, you, score)),
+                None => self.write(format_args!("  {:?}{}: no score\n", player, you)),
+            }
+        }
+    }
+
+    fn on_game_aborted(&mut self, error: &SimulationError<G::PID>) {
+        self.write(format_args!("Game aborted: {}\n", error));
+    }
+
+    fn on_move_rejected(&mut self, error: &GameError<G::PID>) {
+        self.write(format_args!("That move was rejected: {}\n", error));
+    }
+}
+
+/// Returns the lines removed from `old` prefixed with `- ` and the lines added in `new` prefixed
+/// with `+ `, in order, keeping the longest run of common lines unchanged.
+fn diff_lines(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // common[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && common[i + 1][j] >= common[i][j + 1]) {
+            diff.push_str(&format!("- {}\n", old[i]));
+            i += 1;
+        } else {
+            diff.push_str(&format!("+ {}\n", new[j]));
+            j += 1;
+        }
+    }
+    diff
+}
@@ -0,0 +1,210 @@
+use std::{sync::Arc, time::Instant};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::core::{Agent, Determinize, GameLogic, LegalMoves};
+
+use super::mcts::{rollout, MctsConfig};
+use super::search::{reward, Position};
+
+/// An agent for games with hidden information, searching with Information Set Monte Carlo Tree
+/// Search (single-observer ISMCTS).
+///
+/// Unlike [`MctsAgent`](super::MctsAgent), which searches a single guess of the hidden state,
+/// every playout starts from a new state sampled with `Determinize::determinize`. All of them
+/// share one tree of moves, so the statistics of a move are gathered over every hidden state
+/// the agent considers possible, rather than over the one it happened to guess. Moves are told
+/// apart with `PartialEq`, and a move only competes in the playouts where it was legal.
+///
+/// Every player is assumed to play for their own best finishing position. The other players'
+/// moves are searched as if they could see the sampled state, which is the usual simplification
+/// of single-observer ISMCTS.
+///
+/// The agent learns its ID from `Agent::on_game_start`, which the simulation functions call.
+pub struct IsmctsAgent<G: GameLogic> {
+    game: Arc<G>,
+    config: MctsConfig<G>,
+    rng: StdRng,
+    pid: Option<G::PID>,
+    players: Vec<G::PID>,
+}
+
+impl<G: GameLogic> IsmctsAgent<G> {
+    /// Creates an agent searching `game` as `config` says, where `config.budget` counts playouts
+    /// and each playout samples its own hidden state. With `config.seed` set, the sampling and
+    /// the playouts are reproducible; otherwise the agent is seeded from the thread's randomness.
+    /// In a seeded tournament, set the seed from `AgentFactory::create_agent_seeded`.
+    pub fn new(game: Arc<G>, config: MctsConfig<G>) -> Self {
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_rng(&mut rand::rng()),
+        };
+        IsmctsAgent {
+            game,
+            config,
+            rng,
+            pid: None,
+            players: Vec::new(),
+        }
+    }
+}
+
+impl<G> Agent for IsmctsAgent<G>
+where
+    G: Determinize + LegalMoves,
+    G::State: Clone,
+    G::Move: Clone + PartialEq,
+{
+    type Game = G;
+
+    fn digest_state(&mut self, _new_state: G::MaskedState) {}
+
+    /// # Panics
+    /// Panics if the agent wasn't told its ID with `on_game_start`, or if it has no legal moves.
+    fn calculate_next_move(&mut self, new_state: G::MaskedState) -> G::Move {
+        let pid = self
+            .pid
+            .expect("IsmctsAgent needs on_game_start to learn its ID before moving");
+        let mut moves = self.game.legal_moves(&new_state, pid);
+        assert!(
+            !moves.is_empty(),
+            "IsmctsAgent has no legal moves to choose from"
+        );
+        if moves.len() == 1 {
+            return moves.swap_remove(0);
+        }
+
+        let mut tree: Vec<Node<G>> = vec![Node::new(None, None)];
+        let start = Instant::now();
+        let mut iterations = 0;
+        while !self.config.budget.is_spent(start, iterations) {
+            let position =
+                Position::determinize(&*self.game, &self.players, &new_state, pid, &mut self.rng);
+            playout(&mut tree, position, &self.config, &mut self.rng);
+            iterations += 1;
+        }
+
+        let best = tree[0]
+            .children
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by_key(|&(index, child)| (tree[child].visits, std::cmp::Reverse(index)))
+            .and_then(|(_, child)| tree[child].player_move.clone());
+        best.unwrap_or_else(|| moves.swap_remove(0))
+    }
+
+    fn on_game_start(&mut self, pid: G::PID, players: &[G::PID]) {
+        self.pid = Some(pid);
+        self.players = players.to_vec();
+    }
+}
+
+struct Node<G: GameLogic> {
+    /// The player who chose the move leading here. `None` for the root.
+    player: Option<G::PID>,
+    player_move: Option<G::Move>,
+    visits: u32,
+    /// The number of playouts in which the move was legal when its parent was reached.
+    availability: u32,
+    /// The sum of the rewards of `player` over every playout through this node.
+    reward: f64,
+    children: Vec<usize>,
+}
+
+impl<G: GameLogic> Node<G> {
+    fn new(player: Option<G::PID>, player_move: Option<G::Move>) -> Self {
+        Node {
+            player,
+            player_move,
+            visits: 0,
+            availability: 1,
+            reward: 0.0,
+            children: Vec::new(),
+        }
+    }
+}
+
+/// Runs one iteration of ISMCTS on a freshly sampled `position`.
+fn playout<G>(
+    tree: &mut Vec<Node<G>>,
+    mut position: Position<'_, G>,
+    config: &MctsConfig<G>,
+    rng: &mut StdRng,
+) where
+    G: LegalMoves,
+    G::State: Clone,
+    G::Move: Clone + PartialEq,
+{
+    let mut node = 0;
+    let mut path = vec![0];
+
+    // Follow the best available moves down to a move that wasn't tried yet, and try it
+    while let Some(player) = position.next_player() {
+        let mut untried = Vec::new();
+        let mut available = Vec::new();
+        for player_move in position.legal_moves() {
+            let known = tree[node]
+                .children
+                .iter()
+                .copied()
+                .find(|&child| tree[child].player_move.as_ref() == Some(&player_move));
+            match known {
+                Some(child) => available.push(child),
+                None => untried.push(player_move),
+            }
+        }
+
+        for &child in &available {
+            tree[child].availability += 1;
+        }
+
+        if !untried.is_empty() {
+            let player_move = untried.swap_remove(rng.random_range(0..untried.len()));
+            position.play(player_move.clone());
+            let child = tree.len();
+            tree.push(Node::new(Some(player), Some(player_move)));
+            tree[node].children.push(child);
+            path.push(child);
+            break;
+        }
+        if available.is_empty() {
+            // No legal moves, the game is stuck
+            break;
+        }
+        let child = select(tree, &available, config.exploration);
+        let player_move = tree[child]
+            .player_move
+            .clone()
+            .expect("only the root has no move");
+        position.play(player_move);
+        node = child;
+        path.push(child);
+    }
+
+    rollout(&mut position, config, rng);
+
+    // Games that didn't finish count as a draw for everyone
+    let outcome = position.outcome();
+    for &index in &path {
+        let node = &mut tree[index];
+        node.visits += 1;
+        if let Some(player) = node.player {
+            node.reward += outcome.map_or(0.5, |outcome| reward(outcome, player));
+        }
+    }
+}
+
+/// Returns the available child with the best UCT value, counting how often each move was
+/// available instead of how often its parent was visited.
+fn select<G: GameLogic>(tree: &[Node<G>], available: &[usize], exploration: f64) -> usize {
+    let uct = |child: &Node<G>| {
+        let visits = child.visits as f64;
+        child.reward / visits + exploration * ((child.availability as f64).ln() / visits).sqrt()
+    };
+    available
+        .iter()
+        .copied()
+        .max_by(|&a, &b| uct(&tree[a]).total_cmp(&uct(&tree[b])))
+        .expect("there is at least one available move")
+}
@@ -0,0 +1,335 @@
+use std::{fmt, sync::Arc, time::Instant};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::core::{Agent, Determinize, GameLogic, LegalMoves};
+
+use super::search::{reward, Position, SearchBudget};
+
+/// Picks a move for a playout, given the player's view of the state, their ID and their legal
+/// moves, which are never empty.
+pub type RolloutFn<G> = Arc<
+    dyn Fn(
+            &<G as GameLogic>::MaskedState,
+            <G as GameLogic>::PID,
+            Vec<<G as GameLogic>::Move>,
+            &mut StdRng,
+        ) -> <G as GameLogic>::Move
+        + Send
+        + Sync,
+>;
+
+/// How moves are picked when a search plays a game out to the end.
+#[derive(Default)]
+pub enum RolloutPolicy<G: GameLogic> {
+    /// Every legal move is equally likely.
+    #[default]
+    Random,
+    /// The moves are picked by a function, e.g. a cheap heuristic that plays more like a real
+    /// player. Playouts get slower, but more informative.
+    Custom(RolloutFn<G>),
+}
+
+impl<G: GameLogic> Clone for RolloutPolicy<G> {
+    fn clone(&self) -> Self {
+        match self {
+            RolloutPolicy::Random => RolloutPolicy::Random,
+            RolloutPolicy::Custom(rollout) => RolloutPolicy::Custom(Arc::clone(rollout)),
+        }
+    }
+}
+
+impl<G: GameLogic> fmt::Debug for RolloutPolicy<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RolloutPolicy::Random => f.write_str("Random"),
+            RolloutPolicy::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// Settings of an [`MctsAgent`].
+pub struct MctsConfig<G: GameLogic> {
+    /// How long to search for every move. `Iterations` counts playouts.
+    pub budget: SearchBudget,
+    /// The exploration constant of UCT. Higher values try more moves, lower values look deeper
+    /// into the best ones. Rewards are between 0 and 1, for which √2 is the textbook choice.
+    pub exploration: f64,
+    /// How moves are picked in playouts.
+    pub rollout: RolloutPolicy<G>,
+    /// The most moves a playout makes before it stops and counts as a draw for everyone.
+    /// Set it for games that can go on forever.
+    pub max_rollout_depth: Option<usize>,
+    /// Makes the agent's moves reproducible. Unseeded agents use fresh randomness.
+    pub seed: Option<u64>,
+}
+
+impl<G: GameLogic> Default for MctsConfig<G> {
+    fn default() -> Self {
+        MctsConfig {
+            budget: SearchBudget::Iterations(1000),
+            exploration: std::f64::consts::SQRT_2,
+            rollout: RolloutPolicy::Random,
+            max_rollout_depth: None,
+            seed: None,
+        }
+    }
+}
+
+impl<G: GameLogic> Clone for MctsConfig<G> {
+    fn clone(&self) -> Self {
+        MctsConfig {
+            budget: self.budget,
+            exploration: self.exploration,
+            rollout: self.rollout.clone(),
+            max_rollout_depth: self.max_rollout_depth,
+            seed: self.seed,
+        }
+    }
+}
+
+impl<G: GameLogic> fmt::Debug for MctsConfig<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MctsConfig")
+            .field("budget", &self.budget)
+            .field("exploration", &self.exploration)
+            .field("rollout", &self.rollout)
+            .field("max_rollout_depth", &self.max_rollout_depth)
+            .field("seed", &self.seed)
+            .finish()
+    }
+}
+
+/// An agent that picks its moves with Monte Carlo Tree Search (UCT), for any game that can list
+/// its legal moves and rebuild its state from a player's view.
+///
+/// Every move, the agent rebuilds the state with `Determinize::determinize`, then repeatedly
+/// plays the game out to the end, steering towards moves that did well so far while still
+/// trying the others. The move it tried the most is played. Every player is assumed to play
+/// for their own best finishing position, so it works for any number of players.
+///
+/// Simultaneous moves are searched as if the agent chose first and the other players chose
+/// knowing its move, which makes it cautious. Hidden information is searched as if the state
+/// rebuilt from the view were the truth.
+///
+/// The agent learns its ID from `Agent::on_game_start`, which the simulation functions call.
+pub struct MctsAgent<G: GameLogic> {
+    game: Arc<G>,
+    config: MctsConfig<G>,
+    rng: StdRng,
+    pid: Option<G::PID>,
+    players: Vec<G::PID>,
+}
+
+impl<G: GameLogic> MctsAgent<G> {
+    /// Creates an agent searching `game` as `config` says. With `config.seed` set, the agent's
+    /// random number generator is seeded from it, so the same game gets the same moves;
+    /// otherwise it is seeded from the thread's randomness. In a seeded tournament, set the seed
+    /// from `AgentFactory::create_agent_seeded`.
+    pub fn new(game: Arc<G>, config: MctsConfig<G>) -> Self {
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_rng(&mut rand::rng()),
+        };
+        MctsAgent {
+            game,
+            config,
+            rng,
+            pid: None,
+            players: Vec::new(),
+        }
+    }
+}
+
+impl<G> Agent for MctsAgent<G>
+where
+    G: Determinize + LegalMoves,
+    G::State: Clone,
+    G::Move: Clone,
+{
+    type Game = G;
+
+    fn digest_state(&mut self, _new_state: G::MaskedState) {}
+
+    /// # Panics
+    /// Panics if the agent wasn't told its ID with `on_game_start`, or if it has no legal moves.
+    fn calculate_next_move(&mut self, new_state: G::MaskedState) -> G::Move {
+        let pid = self
+            .pid
+            .expect("MctsAgent needs on_game_start to learn its ID before moving");
+        let root =
+            Position::determinize(&*self.game, &self.players, &new_state, pid, &mut self.rng);
+
+        let visits = search(root, &self.config, &mut self.rng);
+        let (best, _) = visits
+            .into_iter()
+            .enumerate()
+            .max_by_key(|&(index, (_, visits))| (visits, std::cmp::Reverse(index)))
+            .map(|(_, choice)| choice)
+            .expect("MctsAgent has no legal moves to choose from");
+        best
+    }
+
+    fn on_game_start(&mut self, pid: G::PID, players: &[G::PID]) {
+        self.pid = Some(pid);
+        self.players = players.to_vec();
+    }
+}
+
+struct Node<G: GameLogic> {
+    /// The player who chose the move leading here. `None` for the root.
+    player: Option<G::PID>,
+    visits: u32,
+    /// The sum of the rewards of `player` over every playout through this node.
+    reward: f64,
+    children: Vec<(G::Move, usize)>,
+    untried: Vec<G::Move>,
+}
+
+impl<G: GameLogic> Node<G> {
+    fn new(player: Option<G::PID>, untried: Vec<G::Move>) -> Self {
+        Node {
+            player,
+            visits: 0,
+            reward: 0.0,
+            children: Vec::new(),
+            untried,
+        }
+    }
+}
+
+/// Runs UCT from `root` within the budget, returning every legal move of the player to move at
+/// `root` with the number of playouts that went through it, in the order of `legal_moves`.
+pub(crate) fn search<G>(
+    root: Position<'_, G>,
+    config: &MctsConfig<G>,
+    rng: &mut StdRng,
+) -> Vec<(G::Move, u32)>
+where
+    G: LegalMoves,
+    G::State: Clone,
+    G::Move: Clone,
+{
+    let moves = root.legal_moves();
+    if moves.len() < 2 {
+        // Nothing to think about
+        return moves
+            .into_iter()
+            .map(|player_move| (player_move, 0))
+            .collect();
+    }
+
+    let mut tree: Vec<Node<G>> = vec![Node::new(None, moves.clone())];
+    // Untried moves are picked from the end, so try them in the order they were listed
+    tree[0].untried.reverse();
+    let start = Instant::now();
+    let mut iterations = 0;
+    while !config.budget.is_spent(start, iterations) {
+        playout(&mut tree, root.clone(), config, rng);
+        iterations += 1;
+    }
+
+    let root_children = &tree[0].children;
+    moves
+        .into_iter()
+        .enumerate()
+        .map(|(index, player_move)| {
+            // Moves are tried in order, so the children are in the same order
+            let visits = root_children
+                .get(index)
+                .map_or(0, |&(_, child)| tree[child].visits);
+            (player_move, visits)
+        })
+        .collect()
+}
+
+/// Runs one iteration of UCT: selection, expansion, a random playout and backpropagation.
+fn playout<G>(
+    tree: &mut Vec<Node<G>>,
+    mut position: Position<'_, G>,
+    config: &MctsConfig<G>,
+    rng: &mut StdRng,
+) where
+    G: LegalMoves,
+    G::State: Clone,
+    G::Move: Clone,
+{
+    let mut node = 0;
+    let mut path = vec![0];
+
+    // Follow the best moves down to a node with untried moves, and try one
+    while let Some(player) = position.next_player() {
+        if let Some(player_move) = tree[node].untried.pop() {
+            position.play(player_move.clone());
+            let child = tree.len();
+            tree.push(Node::new(Some(player), position.legal_moves()));
+            tree[node].children.push((player_move, child));
+            path.push(child);
+            break;
+        }
+        let Some(&(ref player_move, child)) = select(tree, node, config.exploration) else {
+            // No legal moves, the game is stuck
+            break;
+        };
+        position.play(player_move.clone());
+        node = child;
+        path.push(child);
+    }
+
+    rollout(&mut position, config, rng);
+
+    // Games that didn't finish count as a draw for everyone
+    let outcome = position.outcome();
+    for &index in &path {
+        let node = &mut tree[index];
+        node.visits += 1;
+        if let Some(player) = node.player {
+            node.reward += outcome.map_or(0.5, |outcome| reward(outcome, player));
+        }
+    }
+}
+
+/// Plays the game out from `position` with the rollout policy, up to the rollout depth.
+pub(crate) fn rollout<G>(position: &mut Position<'_, G>, config: &MctsConfig<G>, rng: &mut StdRng)
+where
+    G: LegalMoves,
+    G::State: Clone,
+    G::Move: Clone,
+{
+    let mut depth = 0;
+    while let Some(player) = position.next_player() {
+        if config.max_rollout_depth.is_some_and(|max| depth >= max) {
+            break;
+        }
+        let mut moves = position.legal_moves();
+        if moves.is_empty() {
+            break;
+        }
+        let player_move = match &config.rollout {
+            RolloutPolicy::Random => moves.swap_remove(rng.random_range(0..moves.len())),
+            RolloutPolicy::Custom(rollout) => {
+                let view = position.game().mask_state(position.state(), player);
+                rollout(&view, player, moves, rng)
+            }
+        };
+        position.play(player_move);
+        depth += 1;
+    }
+}
+
+/// Returns the child of `node` with the best UCT value for the player choosing there.
+fn select<G: GameLogic>(
+    tree: &[Node<G>],
+    node: usize,
+    exploration: f64,
+) -> Option<&(G::Move, usize)> {
+    let parent_visits = (tree[node].visits as f64).ln();
+    let uct = |child: &Node<G>| {
+        let visits = child.visits as f64;
+        child.reward / visits + exploration * (parent_visits / visits).sqrt()
+    };
+    tree[node]
+        .children
+        .iter()
+        .max_by(|(_, a), (_, b)| uct(&tree[*a]).total_cmp(&uct(&tree[*b])))
+}
@@ -0,0 +1,21 @@
+pub mod alpha_beta;
+pub mod human;
+pub mod ismcts;
+pub mod mcts;
+pub mod pimc;
+#[cfg(feature = "remote")]
+pub mod remote;
+#[cfg(all(feature = "sandbox", unix))]
+pub mod sandbox;
+mod search;
+
+pub use alpha_beta::{AlphaBetaAgent, AlphaBetaConfig, Evaluator, SearchInfo, WIN_SCORE};
+pub use human::HumanAgent;
+pub use ismcts::IsmctsAgent;
+pub use mcts::{MctsAgent, MctsConfig, RolloutFn, RolloutPolicy};
+pub use pimc::PimcAgent;
+#[cfg(feature = "remote")]
+pub use remote::{RemoteAgent, RemoteConfig, RemoteError};
+#[cfg(all(feature = "sandbox", unix))]
+pub use sandbox::{ResourceLimits, SandboxConfig, SandboxFactory};
+pub use search::SearchBudget;
@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::core::{Agent, Determinize, GameLogic, LegalMoves};
+
+use super::mcts::{search, MctsConfig};
+use super::search::Position;
+
+/// An agent for games with hidden information, using Perfect Information Monte Carlo (PIMC).
+///
+/// Every move, the agent samples several states consistent with its view using
+/// `Determinize::determinize`, searches each of them as if nothing were hidden with the same
+/// search as [`MctsAgent`](super::MctsAgent), and plays the move that was searched the most
+/// over all of them. The budget of `config` applies to each sample.
+///
+/// PIMC is simple and often strong, but it assumes it will learn the hidden information after
+/// moving, so it never plays to gather or hide information. [`IsmctsAgent`](super::IsmctsAgent)
+/// avoids some of that.
+///
+/// The sampled states must show the agent the same legal moves, in the same order, as its view.
+/// The agent learns its ID from `Agent::on_game_start`, which the simulation functions call.
+pub struct PimcAgent<G: GameLogic> {
+    game: Arc<G>,
+    determinizations: usize,
+    config: MctsConfig<G>,
+    rng: StdRng,
+    pid: Option<G::PID>,
+    players: Vec<G::PID>,
+}
+
+impl<G: GameLogic> PimcAgent<G> {
+    /// Creates an agent sampling `determinizations` states for every move, at least one.
+    pub fn new(game: Arc<G>, determinizations: usize, config: MctsConfig<G>) -> Self {
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_rng(&mut rand::rng()),
+        };
+        PimcAgent {
+            game,
+            determinizations: determinizations.max(1),
+            config,
+            rng,
+            pid: None,
+            players: Vec::new(),
+        }
+    }
+}
+
+impl<G> Agent for PimcAgent<G>
+where
+    G: Determinize + LegalMoves,
+    G::State: Clone,
+    G::Move: Clone,
+{
+    type Game = G;
+
+    fn digest_state(&mut self, _new_state: G::MaskedState) {}
+
+    /// # Panics
+    /// Panics if the agent wasn't told its ID with `on_game_start`, or if it has no legal moves.
+    fn calculate_next_move(&mut self, new_state: G::MaskedState) -> G::Move {
+        let pid = self
+            .pid
+            .expect("PimcAgent needs on_game_start to learn its ID before moving");
+        let mut moves = self.game.legal_moves(&new_state, pid);
+        assert!(
+            !moves.is_empty(),
+            "PimcAgent has no legal moves to choose from"
+        );
+        if moves.len() == 1 {
+            return moves.swap_remove(0);
+        }
+
+        let mut totals = vec![0u64; moves.len()];
+        for _ in 0..self.determinizations {
+            let root =
+                Position::determinize(&*self.game, &self.players, &new_state, pid, &mut self.rng);
+            let visits = search(root, &self.config, &mut self.rng);
+            assert_eq!(
+                visits.len(),
+                moves.len(),
+                "a sampled state shows the agent different legal moves than its view"
+            );
+            for (total, (_, visits)) in totals.iter_mut().zip(visits) {
+                *total += u64::from(visits);
+            }
+        }
+
+        let best = (0..moves.len())
+            .max_by_key(|&index| (totals[index], std::cmp::Reverse(index)))
+            .expect("there is at least one legal move");
+        moves.swap_remove(best)
+    }
+
+    fn on_game_start(&mut self, pid: G::PID, players: &[G::PID]) {
+        self.pid = Some(pid);
+        self.players = players.to_vec();
+    }
+}
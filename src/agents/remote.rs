@@ -0,0 +1,510 @@
+use std::{
+    fmt,
+    io::{self, BufRead, BufReader, Read, Write},
+    marker::PhantomData,
+    net::{Shutdown, TcpStream, ToSocketAddrs},
+    process::{Child, Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossbeam::channel::{self, Receiver, RecvTimeoutError, Sender};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::core::{Agent, FinalScores, GameError, GameLogic};
+use crate::simulation::SimulationError;
+
+/// The version of the protocol spoken by [`RemoteAgent`].
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// The protocol versions a [`RemoteAgent`] can speak, offered to bots in the handshake.
+pub const SUPPORTED_VERSIONS: &[u32] = &[PROTOCOL_VERSION];
+
+/// Settings of a [`RemoteAgent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemoteConfig {
+    /// How long the bot has to answer the handshake.
+    pub handshake_timeout: Duration,
+    /// How long the bot has to answer a move request.
+    pub move_timeout: Duration,
+    /// How long the bot has to read a message once its connection or input is full.
+    pub write_timeout: Duration,
+}
+
+impl Default for RemoteConfig {
+    fn default() -> Self {
+        RemoteConfig {
+            handshake_timeout: Duration::from_secs(10),
+            move_timeout: Duration::from_secs(10),
+            write_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// An error talking to a remote bot.
+#[derive(Debug)]
+pub enum RemoteError {
+    /// Reading from or writing to the bot failed, or it couldn't be started.
+    Io(io::Error),
+    /// The bot closed its connection or output.
+    Closed,
+    /// The bot didn't answer in time, or didn't read what it was sent.
+    TimedOut(Duration),
+    /// The bot sent something that isn't a valid message, or not the one expected.
+    Protocol(String),
+    /// The bot asked for a protocol version that isn't supported.
+    UnsupportedVersion(u32),
+    /// The bot reported an error of its own.
+    Bot(String),
+}
+
+impl fmt::Display for RemoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemoteError::Io(e) => write!(f, "I/O error talking to the bot: {}", e),
+            RemoteError::Closed => write!(f, "The bot closed the connection"),
+            RemoteError::TimedOut(timeout) => {
+                write!(f, "The bot didn't answer within {:?}", timeout)
+            }
+            RemoteError::Protocol(message) => write!(f, "Protocol error: {}", message),
+            RemoteError::UnsupportedVersion(version) => write!(
+                f,
+                "The bot asked for protocol version {}, but only {:?} are supported",
+                version, SUPPORTED_VERSIONS
+            ),
+            RemoteError::Bot(message) => write!(f, "The bot reported an error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for RemoteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RemoteError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for RemoteError {
+    fn from(e: io::Error) -> Self {
+        RemoteError::Io(e)
+    }
+}
+
+/// A message sent to the bot, as one line of JSON tagged with `"type"`.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Request<'a, PID, S> {
+    Hello { versions: &'a [u32] },
+    GameStart { player: &'a PID, players: &'a [PID] },
+    DigestState { state: &'a S },
+    NextMove { state: &'a S },
+    MoveRejected { reason: String },
+    GameOver { scores: Vec<Score<'a, PID>> },
+    GameAborted { reason: String },
+}
+
+#[derive(Serialize)]
+struct Score<'a, PID> {
+    player: &'a PID,
+    score: i32,
+}
+
+/// A message received from the bot.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Reply<M> {
+    Hello {
+        version: u32,
+        #[serde(default)]
+        name: Option<String>,
+    },
+    Move {
+        #[serde(rename = "move")]
+        player_move: M,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// An agent played by a bot in another process or on another machine, e.g. one written in
+/// another language, speaking a protocol of JSON lines over TCP or a child process's
+/// stdin and stdout. Only built with the `remote` feature.
+///
+/// Every message is a JSON object on its own line, tagged with `"type"`. The bot is sent:
+/// - `{"type": "hello", "versions": [1]}` once connected, the protocol versions offered. It
+///   answers `{"type": "hello", "version": 1, "name": "..."}` with the version it picked; the
+///   name is optional.
+/// - `{"type": "game_start", "player": ..., "players": [...]}` before the first turn.
+/// - `{"type": "digest_state", "state": ...}` when a turn starts without it.
+/// - `{"type": "next_move", "state": ...}` when it has to move. It answers
+///   `{"type": "move", "move": ...}`.
+/// - `{"type": "move_rejected", "reason": "..."}` when the game rejects its move.
+/// - `{"type": "game_over", "scores": [{"player": ..., "score": ...}, ...]}` at the end.
+/// - `{"type": "game_aborted", "reason": "..."}` at the end instead, if the game ended with an
+///   error.
+///
+/// Players, states and moves are encoded with their `Serialize` and `Deserialize` impls. The
+/// bot may answer any request with `{"type": "error", "message": "..."}` to give up.
+///
+/// `Agent` methods can't return errors, so the agent panics when the bot breaks the protocol,
+/// closes the connection or runs out of time, which the simulation reports as
+/// `SimulationError::AgentPanicked`. Under a `TimeControl`, a bot that runs out of the
+/// simulation's time is handled by its `TimeoutPolicy` instead. A bot started with
+/// [`RemoteAgent::spawn`] is killed when the agent is dropped.
+///
+/// A reply that arrives after its request timed out is skipped, so it isn't taken for the
+/// answer to the next request.
+pub struct RemoteAgent<G: GameLogic> {
+    lines: Receiver<io::Result<String>>,
+    /// `None` once the bot's input was closed.
+    writes: Option<Sender<Vec<u8>>>,
+    written: Receiver<io::Result<()>>,
+    /// Set once a write timed out, while the writing thread may still be stuck on it.
+    write_stuck: bool,
+    /// The replies still owed for requests that timed out.
+    unanswered: usize,
+    child: Option<Child>,
+    /// Shut down on drop, which ends the reading thread.
+    stream: Option<TcpStream>,
+    config: RemoteConfig,
+    name: Option<String>,
+    version: u32,
+    /// Extra details for failure messages, e.g. what the bot wrote to stderr.
+    diagnostics: Option<Box<dyn Fn() -> String + Send>>,
+    /// Takes what the bot logged so far, for `Agent::take_log`.
+    log: Option<Box<dyn FnMut() -> String + Send>>,
+    game: PhantomData<fn() -> G>,
+}
+
+impl<G> RemoteAgent<G>
+where
+    G: GameLogic,
+    G::PID: Serialize,
+    G::Move: DeserializeOwned,
+    G::MaskedState: Serialize,
+{
+    /// Connects to a bot listening on `address`, and shakes hands with it.
+    pub fn connect(address: impl ToSocketAddrs, config: RemoteConfig) -> Result<Self, RemoteError> {
+        let stream = TcpStream::connect(address)?;
+        stream.set_nodelay(true)?;
+        // Lets the writing thread give up too, rather than staying stuck on a full connection
+        stream.set_write_timeout(Some(config.write_timeout))?;
+        let reader = stream.try_clone()?;
+        let writer = stream.try_clone()?;
+        let mut agent = Self::with_streams(reader, writer, None, config);
+        agent.stream = Some(stream);
+        agent.handshake()?;
+        Ok(agent)
+    }
+
+    /// Starts `command` as a bot talking through its stdin and stdout, and shakes hands with it.
+    /// Its stderr is left as configured on `command`.
+    pub fn spawn(command: &mut Command, config: RemoteConfig) -> Result<Self, RemoteError> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("the bot's stdin is piped");
+        let stdout = child.stdout.take().expect("the bot's stdout is piped");
+        Self::from_streams(stdout, stdin, Some(child), config)
+    }
+
+    /// Talks to a bot through any pair of streams, and shakes hands with it.
+    ///
+    /// `child` is killed when the agent is dropped, or if the handshake fails.
+    pub fn from_streams(
+        reader: impl Read + Send + 'static,
+        writer: impl Write + Send + 'static,
+        child: Option<Child>,
+        config: RemoteConfig,
+    ) -> Result<Self, RemoteError> {
+        let mut agent = Self::with_streams(reader, writer, child, config);
+        agent.handshake()?;
+        Ok(agent)
+    }
+
+    fn with_streams(
+        reader: impl Read + Send + 'static,
+        writer: impl Write + Send + 'static,
+        child: Option<Child>,
+        config: RemoteConfig,
+    ) -> Self {
+        // Reading happens on its own thread, so that waiting for a line can time out
+        let (sender, lines) = channel::unbounded();
+        thread::spawn(move || {
+            for line in BufReader::new(reader).lines() {
+                let failed = line.is_err();
+                if sender.send(line).is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        // So is writing, so that a bot that stops reading can't block the simulation
+        let (writes, to_write) = channel::unbounded::<Vec<u8>>();
+        let (wrote, written) = channel::unbounded();
+        thread::spawn(move || {
+            let mut writer = writer;
+            for line in to_write {
+                let result = writer.write_all(&line).and_then(|()| writer.flush());
+                let failed = result.is_err();
+                if wrote.send(result).is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        RemoteAgent {
+            lines,
+            writes: Some(writes),
+            written,
+            write_stuck: false,
+            unanswered: 0,
+            child,
+            stream: None,
+            config,
+            name: None,
+            version: PROTOCOL_VERSION,
+            diagnostics: None,
+            log: None,
+            game: PhantomData,
+        }
+    }
+
+    fn handshake(&mut self) -> Result<(), RemoteError> {
+        self.send(&Request::Hello {
+            versions: SUPPORTED_VERSIONS,
+        })?;
+        match self.receive(self.config.handshake_timeout)? {
+            Reply::Hello { version, name } => {
+                if !SUPPORTED_VERSIONS.contains(&version) {
+                    return Err(RemoteError::UnsupportedVersion(version));
+                }
+                self.version = version;
+                self.name = name;
+                Ok(())
+            }
+            Reply::Move { .. } => Err(RemoteError::Protocol(
+                "expected a hello, got a move".to_string(),
+            )),
+            Reply::Error { message } => Err(RemoteError::Bot(message)),
+        }
+    }
+
+    /// Returns the name the bot gave in the handshake, if any.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the protocol version agreed on in the handshake.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Asks the bot for a move.
+    pub fn request_move(&mut self, state: &G::MaskedState) -> Result<G::Move, RemoteError> {
+        self.send(&Request::NextMove { state })?;
+        self.receive_move(self.config.move_timeout)
+    }
+
+    /// Waits up to `timeout` for the answer to a move request.
+    fn receive_move(&mut self, timeout: Duration) -> Result<G::Move, RemoteError> {
+        match self.receive(timeout)? {
+            Reply::Move { player_move } => Ok(player_move),
+            Reply::Hello { .. } => Err(RemoteError::Protocol(
+                "expected a move, got a hello".to_string(),
+            )),
+            Reply::Error { message } => Err(RemoteError::Bot(message)),
+        }
+    }
+
+    fn send(&mut self, request: &Request<'_, G::PID, G::MaskedState>) -> Result<(), RemoteError> {
+        let mut line = serde_json::to_vec(request)
+            .map_err(|e| RemoteError::Protocol(format!("couldn't encode a request: {}", e)))?;
+        line.push(b'\n');
+
+        let timeout = self.config.write_timeout;
+        if self.write_stuck {
+            return Err(RemoteError::TimedOut(timeout));
+        }
+        let writes = self.writes.as_ref().ok_or(RemoteError::Closed)?;
+        writes.send(line).map_err(|_| RemoteError::Closed)?;
+        match self.written.recv_timeout(timeout) {
+            Ok(result) => Ok(result?),
+            Err(RecvTimeoutError::Timeout) => {
+                self.write_stuck = true;
+                Err(RemoteError::TimedOut(timeout))
+            }
+            Err(RecvTimeoutError::Disconnected) => Err(RemoteError::Closed),
+        }
+    }
+
+    fn receive(&mut self, timeout: Duration) -> Result<Reply<G::Move>, RemoteError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let line = match self.lines.recv_deadline(deadline) {
+                Ok(line) => line?,
+                Err(RecvTimeoutError::Timeout) => {
+                    self.unanswered += 1;
+                    return Err(RemoteError::TimedOut(timeout));
+                }
+                Err(RecvTimeoutError::Disconnected) => return Err(RemoteError::Closed),
+            };
+            if self.unanswered > 0 {
+                // A late reply to a request that timed out
+                self.unanswered -= 1;
+                continue;
+            }
+            return serde_json::from_str(&line)
+                .map_err(|e| RemoteError::Protocol(format!("couldn't decode {:?}: {}", line, e)));
+        }
+    }
+
+    /// Sends a notification, panicking if the bot can't be reached.
+    fn notify(&mut self, request: &Request<'_, G::PID, G::MaskedState>) {
+        if let Err(e) = self.send(request) {
+            self.fail(e);
+        }
+    }
+
+    /// Adds the output of `diagnostics` to the messages the agent panics with.
+    #[cfg(feature = "sandbox")]
+    pub(crate) fn set_diagnostics(&mut self, diagnostics: impl Fn() -> String + Send + 'static) {
+        self.diagnostics = Some(Box::new(diagnostics));
+    }
+
+    /// Makes `Agent::take_log` return the output of `log`, unless it's empty.
+    #[cfg(feature = "sandbox")]
+    pub(crate) fn set_log(&mut self, log: impl FnMut() -> String + Send + 'static) {
+        self.log = Some(Box::new(log));
+    }
+
+    /// Panics with `error`, how the bot's process exited if it did, and the diagnostics.
+    fn fail(&mut self, error: RemoteError) -> ! {
+        let mut message = error.to_string();
+        if let (RemoteError::Closed | RemoteError::Io(_), Some(child)) = (&error, &mut self.child) {
+            // The bot's output closes a moment before it can be waited for
+            for _ in 0..20 {
+                if let Ok(Some(status)) = child.try_wait() {
+                    message.push_str(&format!(" (the bot exited with {})", status));
+                    break;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+        if let Some(details) = self.diagnostics.as_ref().map(|diagnostics| diagnostics()) {
+            if !details.is_empty() {
+                message.push('\n');
+                message.push_str(&details);
+            }
+        }
+        panic!("{}", message);
+    }
+}
+
+impl<G> Agent for RemoteAgent<G>
+where
+    G: GameLogic,
+    G::PID: Serialize + fmt::Debug,
+    G::Move: DeserializeOwned,
+    G::MaskedState: Serialize,
+{
+    type Game = G;
+
+    fn digest_state(&mut self, new_state: G::MaskedState) {
+        self.notify(&Request::DigestState { state: &new_state });
+    }
+
+    /// # Panics
+    /// Panics with the [`RemoteError`] if no valid move arrives in time.
+    fn calculate_next_move(&mut self, new_state: G::MaskedState) -> G::Move {
+        match self.request_move(&new_state) {
+            Ok(player_move) => player_move,
+            Err(e) => self.fail(e),
+        }
+    }
+
+    /// Waits for the bot until `limit` or `RemoteConfig::move_timeout` passes, whichever comes
+    /// first. Returns `None` if it's `limit`, so the simulation applies its `TimeoutPolicy`; the
+    /// late reply is skipped once it arrives.
+    ///
+    /// # Panics
+    /// Panics with the [`RemoteError`] if the bot fails otherwise, or runs out of `move_timeout`.
+    fn calculate_next_move_within(
+        &mut self,
+        new_state: G::MaskedState,
+        limit: Duration,
+    ) -> Option<G::Move> {
+        self.notify(&Request::NextMove { state: &new_state });
+        let move_timeout = self.config.move_timeout;
+        match self.receive_move(limit.min(move_timeout)) {
+            Ok(player_move) => Some(player_move),
+            Err(RemoteError::TimedOut(_)) if limit <= move_timeout => None,
+            Err(e) => self.fail(e),
+        }
+    }
+
+    fn on_game_start(&mut self, pid: G::PID, players: &[G::PID]) {
+        self.notify(&Request::GameStart {
+            player: &pid,
+            players,
+        });
+    }
+
+    fn on_game_over(&mut self, scores: &FinalScores<G::PID>) {
+        let scores = scores
+            .iter()
+            .map(|(player, &score)| Score { player, score })
+            .collect();
+        self.notify(&Request::GameOver { scores });
+    }
+
+    fn on_game_aborted(&mut self, error: &SimulationError<G::PID>) {
+        self.notify(&Request::GameAborted {
+            reason: error.to_string(),
+        });
+    }
+
+    fn on_move_rejected(&mut self, error: &GameError<G::PID>) {
+        self.notify(&Request::MoveRejected {
+            reason: error.to_string(),
+        });
+    }
+
+    /// The game is over by then, so a bot started by the agent is stopped first, making sure
+    /// everything it wrote is in the log. Its input is closed, and it's killed unless it exits
+    /// shortly after.
+    fn take_log(&mut self) -> Option<String> {
+        if let (Some(_), Some(child)) = (&self.log, &mut self.child) {
+            // Ends the writing thread once it's done, which closes the bot's input
+            self.writes = None;
+            for _ in 0..20 {
+                if let Ok(Some(_)) = child.try_wait() {
+                    break;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        self.log
+            .as_mut()
+            .map(|log| log())
+            .filter(|log| !log.is_empty())
+    }
+}
+
+impl<G: GameLogic> Drop for RemoteAgent<G> {
+    fn drop(&mut self) {
+        if let Some(stream) = &self.stream {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+        if let Some(child) = &mut self.child {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use std::{
+    ffi::OsString,
+    io::{self, Read},
+    marker::PhantomData,
+    os::unix::process::CommandExt,
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use crossbeam::channel;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::core::GameLogic;
+use crate::tournament::AgentFactory;
+
+use super::remote::{RemoteAgent, RemoteConfig};
+
+/// Limits on the resources of a sandboxed bot, applied with `setrlimit` before it starts.
+/// They hold for the bot and anything it starts. Core dumps are always disabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ResourceLimits {
+    /// The most address space the bot may map, in bytes (`RLIMIT_AS`). Allocations beyond it
+    /// fail, which usually ends the bot.
+    pub memory_bytes: Option<u64>,
+    /// The most CPU time the bot may use over the whole game, rounded up to whole seconds
+    /// (`RLIMIT_CPU`). The bot is killed once it's used up.
+    pub cpu_time: Option<Duration>,
+    /// The largest file the bot may write, in bytes (`RLIMIT_FSIZE`).
+    pub file_size_bytes: Option<u64>,
+    /// The most files the bot may have open at once (`RLIMIT_NOFILE`).
+    pub open_files: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// Applies the limits to the current process. Only calls `setrlimit`, so that it's safe
+    /// to call between `fork` and `exec`.
+    fn apply(&self) -> io::Result<()> {
+        fn limit(value: u64) -> libc::rlimit {
+            libc::rlimit {
+                rlim_cur: value as libc::rlim_t,
+                rlim_max: value as libc::rlim_t,
+            }
+        }
+        fn check(result: libc::c_int) -> io::Result<()> {
+            match result {
+                0 => Ok(()),
+                _ => Err(io::Error::last_os_error()),
+            }
+        }
+
+        // SAFETY: setrlimit only reads the limit it's given
+        unsafe {
+            check(libc::setrlimit(libc::RLIMIT_CORE, &limit(0)))?;
+            if let Some(bytes) = self.memory_bytes {
+                check(libc::setrlimit(libc::RLIMIT_AS, &limit(bytes)))?;
+            }
+            if let Some(time) = self.cpu_time {
+                let seconds = time.as_secs() + u64::from(time.subsec_nanos() > 0);
+                check(libc::setrlimit(libc::RLIMIT_CPU, &limit(seconds.max(1))))?;
+            }
+            if let Some(bytes) = self.file_size_bytes {
+                check(libc::setrlimit(libc::RLIMIT_FSIZE, &limit(bytes)))?;
+            }
+            if let Some(files) = self.open_files {
+                check(libc::setrlimit(libc::RLIMIT_NOFILE, &limit(files)))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Settings of a [`SandboxFactory`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SandboxConfig {
+    /// The limits every bot process runs under.
+    pub limits: ResourceLimits,
+    /// The timeouts of the protocol.
+    pub remote: RemoteConfig,
+    /// How many more times a bot is started for a game when it fails to start or to shake
+    /// hands, e.g. because it crashed.
+    pub restarts: usize,
+    /// The most bytes of each bot's stderr that are kept until its game's log is taken, the
+    /// latest ones.
+    pub stderr_limit: usize,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        SandboxConfig {
+            limits: ResourceLimits::default(),
+            remote: RemoteConfig::default(),
+            restarts: 2,
+            stderr_limit: 64 * 1024,
+        }
+    }
+}
+
+/// The tail of what a bot wrote to stderr, shared with the thread reading it.
+type StderrLog = Arc<Mutex<Vec<u8>>>;
+
+/// An [`AgentFactory`] starting a bot as its own process for every game, under resource
+/// limits, so that untrusted submissions can play in a tournament. The bots speak the protocol
+/// of [`RemoteAgent`] over their stdin and stdout. Only built with the `sandbox` feature, on
+/// Unix.
+///
+/// A fresh process plays every game and is killed once the game is over, so a bot that
+/// crashed or broke its limits in one game starts clean in the next. Bots that fail to start
+/// or to shake hands are started again up to [`SandboxConfig::restarts`] times before the
+/// factory gives up by panicking, which the tournament reports as
+/// `SimulationError::AgentPanicked` for that game.
+///
+/// What each bot writes to stderr is kept as its agent's log, see `Agent::take_log`, so the
+/// tournament adds it to the report of the bot's game under the bot's player. The end of it is
+/// also added to the message of a failing agent.
+///
+/// Resource limits keep a bot from exhausting the machine, but they don't isolate it: it can
+/// still read files and use the network with the rights of the tournament's user. Run
+/// untrusted code as an unprivileged user, or in a container.
+pub struct SandboxFactory<G> {
+    program: PathBuf,
+    args: Vec<OsString>,
+    current_dir: Option<PathBuf>,
+    config: SandboxConfig,
+    game: PhantomData<fn() -> G>,
+}
+
+impl<G> SandboxFactory<G> {
+    /// Creates a factory running `program`, found in `PATH` unless it's a path.
+    pub fn new(program: impl Into<PathBuf>, config: SandboxConfig) -> Self {
+        SandboxFactory {
+            program: program.into(),
+            args: Vec::new(),
+            current_dir: None,
+            config,
+            game: PhantomData,
+        }
+    }
+
+    /// Adds arguments to pass to the program.
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Runs the program in `dir`, rather than in the current directory.
+    pub fn current_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.current_dir = Some(dir.into());
+        self
+    }
+
+    fn command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(dir) = &self.current_dir {
+            command.current_dir(dir);
+        }
+        let limits = self.config.limits;
+        // SAFETY: the closure only calls setrlimit, which is async-signal-safe
+        unsafe {
+            command.pre_exec(move || limits.apply());
+        }
+        command
+    }
+}
+
+impl<G> SandboxFactory<G>
+where
+    G: GameLogic,
+    G::PID: Serialize,
+    G::Move: DeserializeOwned,
+    G::MaskedState: Serialize,
+{
+    /// Starts the bot once, keeping its stderr.
+    fn launch(&self) -> Result<RemoteAgent<G>, String> {
+        let mut child = self.command().spawn().map_err(|e| e.to_string())?;
+        let stdin = child.stdin.take().expect("the bot's stdin is piped");
+        let stdout = child.stdout.take().expect("the bot's stdout is piped");
+        let stderr = child.stderr.take().expect("the bot's stderr is piped");
+
+        let log = StderrLog::default();
+        let (done, captured) = channel::bounded(1);
+        let limit = self.config.stderr_limit;
+        let capturing = Arc::clone(&log);
+        thread::spawn(move || {
+            capture(stderr, &capturing, limit);
+            let _ = done.send(());
+        });
+
+        match RemoteAgent::from_streams(stdout, stdin, Some(child), self.config.remote) {
+            Ok(mut agent) => {
+                let diagnosed = Arc::clone(&log);
+                agent.set_diagnostics(move || with_stderr(String::new(), &diagnosed));
+                agent.set_log(move || {
+                    // The bot was stopped, so its stderr ends soon
+                    let _ = captured.recv_timeout(Duration::from_secs(1));
+                    let taken = std::mem::take(&mut *log.lock().unwrap());
+                    String::from_utf8_lossy(&taken).into_owned()
+                });
+                Ok(agent)
+            }
+            Err(e) => {
+                // The bot was killed, so its stderr ends soon, unless something it started
+                // keeps it open
+                let _ = captured.recv_timeout(Duration::from_secs(1));
+                Err(with_stderr(e.to_string(), &log))
+            }
+        }
+    }
+}
+
+impl<G> AgentFactory for SandboxFactory<G>
+where
+    G: GameLogic,
+    G::PID: Serialize,
+    G::Move: DeserializeOwned,
+    G::MaskedState: Serialize,
+{
+    type Agent = RemoteAgent<G>;
+
+    /// # Panics
+    /// Panics if the bot couldn't be started after every restart.
+    fn create_agent(&self) -> RemoteAgent<G> {
+        let mut failures = Vec::new();
+        for _ in 0..=self.config.restarts {
+            match self.launch() {
+                Ok(agent) => return agent,
+                Err(failure) => failures.push(failure),
+            }
+        }
+        panic!(
+            "the bot {:?} failed to start {} times: {}",
+            self.program,
+            failures.len(),
+            failures.join("; ")
+        );
+    }
+}
+
+/// Reads `stderr` to the end into `log`, keeping its last `limit` bytes.
+fn capture(mut stderr: impl Read, log: &Mutex<Vec<u8>>, limit: usize) {
+    let mut buffer = [0; 4096];
+    while let Ok(read @ 1..) = stderr.read(&mut buffer) {
+        let mut log = log.lock().unwrap();
+        log.extend_from_slice(&buffer[..read]);
+        let excess = log.len().saturating_sub(limit);
+        log.drain(..excess);
+    }
+}
+
+/// Appends the end of the bot's stderr to `message`, if it wrote anything.
+fn with_stderr(mut message: String, log: &Mutex<Vec<u8>>) -> String {
+    const TAIL: usize = 2048;
+    let log = log.lock().unwrap();
+    let tail = String::from_utf8_lossy(&log[log.len().saturating_sub(TAIL)..]);
+    let tail = tail.trim_end();
+    if !tail.is_empty() {
+        if !message.is_empty() {
+            message.push('\n');
+        }
+        message.push_str("stderr of the bot:\n");
+        message.push_str(tail);
+    }
+    message
+}
@@ -0,0 +1,244 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use rand::rngs::StdRng;
+
+use crate::core::{Determinize, GameError, GameLogic, Id, LegalMoves, MoveResult, Outcome};
+
+/// How long a search agent thinks about each move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SearchBudget {
+    /// A fixed amount of work: playouts for Monte Carlo agents, plies for tree searches.
+    /// Searches with the same budget and seed make the same moves on any machine.
+    Iterations(usize),
+    /// Searching stops once the time is up, after at least one iteration.
+    Time(Duration),
+}
+
+impl SearchBudget {
+    /// Returns true once a search that started at `start` and did `done` iterations should stop.
+    pub(crate) fn is_spent(&self, start: Instant, done: usize) -> bool {
+        match *self {
+            SearchBudget::Iterations(iterations) => done >= iterations.max(1),
+            SearchBudget::Time(limit) => done > 0 && start.elapsed() >= limit,
+        }
+    }
+}
+
+/// A game being played out inside a search, on a state of the searcher's own.
+///
+/// Players choose one at a time, in seat order, even in simultaneous-move games. The moves are
+/// applied once every active player has chosen, so searches can treat every game as turn-based.
+pub(crate) struct Position<'a, G: GameLogic> {
+    game: &'a G,
+    players: &'a [G::PID],
+    state: G::State,
+    to_move: Vec<G::PID>,
+    moves: HashMap<G::PID, G::Move>,
+    eliminated: Vec<HashSet<G::PID>>,
+    outcome: Option<Outcome<G::PID>>,
+}
+
+impl<'a, G: GameLogic> Position<'a, G> {
+    /// Starts a search from `state`, with `active` players to move. `players` are in seat order.
+    pub(crate) fn new(
+        game: &'a G,
+        players: &'a [G::PID],
+        state: G::State,
+        active: HashSet<G::PID>,
+    ) -> Self {
+        let mut position = Position {
+            game,
+            players,
+            state,
+            to_move: Vec::new(),
+            moves: HashMap::new(),
+            eliminated: Vec::new(),
+            outcome: None,
+        };
+        position.set_active(active);
+        position
+    }
+
+    /// Starts a search from a state rebuilt from `player`'s view, with `player` choosing first.
+    ///
+    /// # Panics
+    /// Panics if `player` isn't active in the rebuilt state.
+    pub(crate) fn determinize(
+        game: &'a G,
+        players: &'a [G::PID],
+        view: &G::MaskedState,
+        player: G::PID,
+        rng: &mut StdRng,
+    ) -> Self
+    where
+        G: Determinize,
+    {
+        let (state, active) = game.determinize(view, player, rng);
+        let mut position = Position::new(game, players, state, active);
+        position.choose_first(player);
+        assert!(
+            position.next_player() == Some(player),
+            "a search agent was asked for a move while not active"
+        );
+        position
+    }
+
+    /// Returns the player who chooses next, or `None` once the game is over.
+    pub(crate) fn next_player(&self) -> Option<G::PID> {
+        match self.outcome {
+            Some(_) => None,
+            None => self.to_move.first().copied(),
+        }
+    }
+
+    /// Returns the outcome, once the game is over.
+    pub(crate) fn outcome(&self) -> Option<&Outcome<G::PID>> {
+        self.outcome.as_ref()
+    }
+
+    /// Returns the game being played.
+    pub(crate) fn game(&self) -> &'a G {
+        self.game
+    }
+
+    /// Returns the full state. Moves chosen this turn aren't applied to it yet.
+    pub(crate) fn state(&self) -> &G::State {
+        &self.state
+    }
+
+    /// Returns true if no moves were chosen yet this turn.
+    pub(crate) fn at_turn_start(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// Lets `player` choose before the other active players this turn. The moves of a turn are
+    /// applied together, so the order only matters to the search.
+    pub(crate) fn choose_first(&mut self, player: G::PID) {
+        if let Some(index) = self.to_move.iter().position(|&pid| pid == player) {
+            self.to_move[..=index].rotate_right(1);
+        }
+    }
+
+    /// Returns the legal moves of the player who chooses next, seen from their point of view.
+    pub(crate) fn legal_moves(&self) -> Vec<G::Move>
+    where
+        G: LegalMoves,
+        G::Move: Clone,
+    {
+        match self.next_player() {
+            Some(player) => {
+                let view = self.game.mask_state(&self.state, player);
+                self.game.legal_moves(&view, player)
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Makes the next player's choice, applying the turn once every active player has chosen.
+    /// A move the game rejects ends the game, with the player it blames forfeiting.
+    ///
+    /// # Panics
+    /// Panics if the game is already over.
+    pub(crate) fn play(&mut self, player_move: G::Move) {
+        let player = self.to_move.remove(0);
+        self.moves.insert(player, player_move);
+        if !self.to_move.is_empty() {
+            return;
+        }
+
+        let moves = std::mem::take(&mut self.moves);
+        match self.game.apply_moves(&mut self.state, moves) {
+            Ok(MoveResult::Continue(active)) => self.set_active(active),
+            Ok(MoveResult::Eliminated { eliminated, active }) => {
+                self.eliminated.push(eliminated);
+                self.set_active(active);
+            }
+            Ok(MoveResult::GameOver(scores)) => {
+                let remaining = self.remaining_players();
+                self.end_with(Outcome::from_scores(scores, &remaining));
+            }
+            Ok(MoveResult::Finished(outcome)) => self.end_with(outcome),
+            Err(error) => self.reject(error),
+        }
+    }
+
+    fn reject(&mut self, error: GameError<G::PID>) {
+        let remaining = self.remaining_players();
+        let outcome = match error.culprit() {
+            Some(culprit) => Outcome::forfeit(culprit, 0, &remaining),
+            None => Outcome::draw(&remaining),
+        };
+        self.end_with(outcome);
+    }
+
+    fn end_with(&mut self, outcome: Outcome<G::PID>) {
+        self.to_move.clear();
+        self.outcome = Some(outcome.rank_eliminated(&self.eliminated));
+    }
+
+    fn set_active(&mut self, active: HashSet<G::PID>) {
+        // Seat order keeps searches reproducible, whatever the order of the set
+        self.to_move = self
+            .players
+            .iter()
+            .copied()
+            .filter(|pid| active.contains(pid))
+            .collect();
+        for &pid in &active {
+            if !self.players.contains(&pid) {
+                self.to_move.push(pid);
+            }
+        }
+        if self.to_move.is_empty() {
+            // Nobody can move, so the game can't go on
+            let remaining = self.remaining_players();
+            self.end_with(Outcome::draw(&remaining));
+        }
+    }
+
+    fn remaining_players(&self) -> Vec<G::PID> {
+        self.players
+            .iter()
+            .copied()
+            .filter(|pid| !self.eliminated.iter().any(|group| group.contains(pid)))
+            .collect()
+    }
+}
+
+impl<G> Clone for Position<'_, G>
+where
+    G: GameLogic,
+    G::State: Clone,
+    G::Move: Clone,
+{
+    fn clone(&self) -> Self {
+        Position {
+            game: self.game,
+            players: self.players,
+            state: self.state.clone(),
+            to_move: self.to_move.clone(),
+            moves: self.moves.clone(),
+            eliminated: self.eliminated.clone(),
+            outcome: self.outcome.clone(),
+        }
+    }
+}
+
+/// Returns how well `player` did, from 0 for finishing last alone to 1 for winning alone.
+/// Ties split the difference, so a two-player draw is worth 0.5. Unranked players get 0.
+pub(crate) fn reward<PID: Id>(outcome: &Outcome<PID>, player: PID) -> f64 {
+    let Some(rank) = outcome.rank_of(player) else {
+        return 0.0;
+    };
+    let others = outcome.rankings.iter().map(Vec::len).sum::<usize>() - 1;
+    if others == 0 {
+        return 1.0;
+    }
+    let below: usize = outcome.rankings[rank..].iter().map(Vec::len).sum();
+    let tied = outcome.rankings[rank - 1].len() - 1;
+    (below as f64 + tied as f64 / 2.0) / others as f64
+}
@@ -1,5 +1,9 @@
+pub mod outcome;
+pub mod seed;
 pub mod traits;
 pub mod types;
 
-pub use traits::{Agent, GameLogic, LegalMoves};
+pub use outcome::{Outcome, Termination};
+pub use seed::derive_seed;
+pub use traits::{Agent, AgentFuture, AsyncAgent, Determinize, GameLogic, LegalMoves};
 pub use types::{FinalScores, GameError, Id, MoveResult};
@@ -0,0 +1,178 @@
+use std::collections::HashSet;
+
+use super::types::{FinalScores, Id};
+
+/// Why a game ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Termination<PID: Id> {
+    /// The game reached its natural end.
+    Completed,
+    /// A player forfeited, e.g. by resigning, running out of time or making an illegal move.
+    Forfeit(PID),
+    /// A reason specific to the game, e.g. "threefold repetition".
+    Other(String),
+}
+
+/// How a game ended for every player: their finishing positions, their scores and why it ended.
+///
+/// Games report an outcome with `MoveResult::Finished`. Games that only report scores with
+/// `MoveResult::GameOver` get one built from the scores, so every finished game has an outcome.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outcome<PID: Id> {
+    /// The players grouped by finishing position, best first. Players in the same group tied.
+    pub rankings: Vec<Vec<PID>>,
+    /// Every player's score.
+    pub scores: FinalScores<PID>,
+    /// Why the game ended.
+    pub termination: Termination<PID>,
+}
+
+impl<PID: Id> Outcome<PID> {
+    /// Creates a completed outcome from finishing positions, best first. Every player scores the
+    /// number of players ranked below them, so a two-player game scores 1 for a win and 0 for a
+    /// draw or a loss. Use `with_scores` for other scoring.
+    pub fn from_rankings(rankings: Vec<Vec<PID>>) -> Self {
+        let mut below: usize = rankings.iter().map(Vec::len).sum();
+        let mut scores = FinalScores::new();
+        for group in &rankings {
+            below -= group.len();
+            scores.extend(group.iter().map(|&pid| (pid, below as i32)));
+        }
+        Outcome {
+            rankings,
+            scores,
+            termination: Termination::Completed,
+        }
+    }
+
+    /// Creates a completed outcome in which `winner` beats all the other `players`, who tie.
+    pub fn win(winner: PID, players: &[PID]) -> Self {
+        let others: Vec<PID> = players
+            .iter()
+            .copied()
+            .filter(|&pid| pid != winner)
+            .collect();
+        let rankings = if others.is_empty() {
+            vec![vec![winner]]
+        } else {
+            vec![vec![winner], others]
+        };
+        Self::from_rankings(rankings)
+    }
+
+    /// Creates a completed outcome in which all `players` tie, scoring 0.
+    pub fn draw(players: &[PID]) -> Self {
+        Self::from_rankings(vec![players.to_vec()])
+    }
+
+    /// Creates a completed outcome from scores, ranking `players` from the highest score to the
+    /// lowest. Players missing from `scores` score 0. Groups keep the order of `players`.
+    pub fn from_scores(mut scores: FinalScores<PID>, players: &[PID]) -> Self {
+        for &pid in players {
+            scores.entry(pid).or_insert(0);
+        }
+        let mut levels: Vec<i32> = players.iter().map(|pid| scores[pid]).collect();
+        levels.sort_unstable_by(|a, b| b.cmp(a));
+        levels.dedup();
+        let rankings = levels
+            .into_iter()
+            .map(|level| {
+                players
+                    .iter()
+                    .copied()
+                    .filter(|pid| scores[pid] == level)
+                    .collect()
+            })
+            .collect();
+        Outcome {
+            rankings,
+            scores,
+            termination: Termination::Completed,
+        }
+    }
+
+    /// Creates the outcome of a game `player` forfeited: they rank last scoring `penalty`, and
+    /// the other `players` tie for first, scoring 0.
+    pub fn forfeit(player: PID, penalty: i32, players: &[PID]) -> Self {
+        let others: Vec<PID> = players
+            .iter()
+            .copied()
+            .filter(|&pid| pid != player)
+            .collect();
+        let mut scores: FinalScores<PID> = others.iter().map(|&pid| (pid, 0)).collect();
+        scores.insert(player, penalty);
+        let rankings = if others.is_empty() {
+            vec![vec![player]]
+        } else {
+            vec![others, vec![player]]
+        };
+        Outcome {
+            rankings,
+            scores,
+            termination: Termination::Forfeit(player),
+        }
+    }
+
+    /// Replaces the scores, keeping the rankings.
+    pub fn with_scores(mut self, scores: FinalScores<PID>) -> Self {
+        self.scores = scores;
+        self
+    }
+
+    /// Replaces the reason the game ended.
+    pub fn with_termination(mut self, termination: Termination<PID>) -> Self {
+        self.termination = termination;
+        self
+    }
+
+    /// Returns the players who finished first. More than one means they tied.
+    pub fn winners(&self) -> &[PID] {
+        self.rankings.first().map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns true if every player tied.
+    pub fn is_draw(&self) -> bool {
+        self.rankings.len() == 1 && self.rankings[0].len() > 1
+    }
+
+    /// Returns a player's finishing position, starting from 1 for the winners. Tied players
+    /// share a position, and the next position is one lower.
+    pub fn rank_of(&self, player: PID) -> Option<usize> {
+        self.rankings
+            .iter()
+            .position(|group| group.contains(&player))
+            .map(|index| index + 1)
+    }
+
+    /// Returns a player's score, counting players missing from the scores as 0.
+    pub fn score_of(&self, player: PID) -> i32 {
+        self.scores.get(&player).copied().unwrap_or(0)
+    }
+
+    /// Adds players who were eliminated during the game and aren't ranked yet below everyone
+    /// else, the last ones eliminated highest. `eliminated` is in the order players left the game.
+    pub(crate) fn rank_eliminated(mut self, eliminated: &[HashSet<PID>]) -> Self {
+        for group in eliminated.iter().rev() {
+            let unranked: Vec<PID> = group
+                .iter()
+                .copied()
+                .filter(|&pid| self.rank_of(pid).is_none())
+                .collect();
+            if !unranked.is_empty() {
+                for &pid in &unranked {
+                    self.scores.entry(pid).or_insert(0);
+                }
+                self.rankings.push(unranked);
+            }
+        }
+        self
+    }
+}
+
+impl<PID: Id> From<Outcome<PID>> for FinalScores<PID> {
+    fn from(outcome: Outcome<PID>) -> Self {
+        outcome.scores
+    }
+}
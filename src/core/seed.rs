@@ -0,0 +1,11 @@
+/// Derives an independent seed for a sub-stream of randomness from a parent seed.
+///
+/// Used to give every game of a tournament, and every agent of a game, its own seed from a
+/// single master seed. The derivation is SplitMix64, so it's stable across platforms and
+/// releases, and nearby inputs give unrelated outputs.
+pub fn derive_seed(seed: u64, stream: u64) -> u64 {
+    let mut z = seed.wrapping_add(stream.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
@@ -1,6 +1,15 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    pin::Pin,
+    time::Duration,
+};
 
-use super::types::{GameError, Id, MoveResult};
+use rand::rngs::StdRng;
+
+use crate::simulation::SimulationError;
+
+use super::types::{FinalScores, GameError, Id, MoveResult};
 
 /// Represents the logic of a turn-based game.
 pub trait GameLogic {
@@ -22,6 +31,17 @@ pub trait GameLogic {
     /// A tuple containing the initial game state and a set of player IDs that can make moves.
     fn init(&self, players: Vec<Self::PID>) -> (Self::State, HashSet<Self::PID>);
 
+    /// Initializes the game state like `init`, for games with a random setup (e.g. shuffling a deck).
+    /// The setup must be fully determined by `seed`, so that seeded simulations can be reproduced.
+    /// Defaults to `init`, ignoring the seed.
+    fn init_seeded(
+        &self,
+        players: Vec<Self::PID>,
+        _seed: u64,
+    ) -> (Self::State, HashSet<Self::PID>) {
+        self.init(players)
+    }
+
     /// Applies the given moves to the game state in-place and returns the result.
     ///
     /// # Arguments
@@ -70,6 +90,45 @@ pub trait Agent {
         &mut self,
         new_state: <Self::Game as GameLogic>::MaskedState,
     ) -> <Self::Game as GameLogic>::Move;
+
+    /// Calculates the next move like `calculate_next_move`, giving up once `limit` has passed.
+    /// Called instead of `calculate_next_move` when the game has a time control. Returns `None`
+    /// if the agent ran out of time, which the simulation treats as a timeout.
+    ///
+    /// Defaults to `calculate_next_move`, which can't be interrupted. Wrap an agent in a
+    /// [`DeadlineAgent`](crate::simulation::DeadlineAgent) to enforce the limit.
+    fn calculate_next_move_within(
+        &mut self,
+        new_state: <Self::Game as GameLogic>::MaskedState,
+        _limit: Duration,
+    ) -> Option<<Self::Game as GameLogic>::Move> {
+        Some(self.calculate_next_move(new_state))
+    }
+
+    /// Called once before the first turn, with the agent's own ID and every player in seat order.
+    fn on_game_start(
+        &mut self,
+        _pid: <Self::Game as GameLogic>::PID,
+        _players: &[<Self::Game as GameLogic>::PID],
+    ) {
+    }
+
+    /// Called once the game has ended with final scores, e.g. to learn from the outcome.
+    fn on_game_over(&mut self, _scores: &FinalScores<<Self::Game as GameLogic>::PID>) {}
+
+    /// Called instead of `on_game_over` when the game ends with an error, e.g. a timeout, a
+    /// panic or the turn limit, so the agent can clean up. Not called on an agent whose own
+    /// panic ended the game, since it is abandoned.
+    fn on_game_aborted(&mut self, _error: &SimulationError<<Self::Game as GameLogic>::PID>) {}
+
+    /// Called when the game logic rejects the agent's last move.
+    fn on_move_rejected(&mut self, _error: &GameError<<Self::Game as GameLogic>::PID>) {}
+
+    /// Returns what the agent logged during the game and forgets it, e.g. what a bot wrote to
+    /// stderr. Tournaments call it once a game is over and keep the log in the game's report.
+    fn take_log(&mut self) -> Option<String> {
+        None
+    }
 }
 
 /// Blanket impl so that Box<dyn Agent<Game = G>> can be used wherever Agent is expected.
@@ -83,6 +142,34 @@ impl<G: GameLogic> Agent for Box<dyn Agent<Game = G>> {
     fn calculate_next_move(&mut self, new_state: G::MaskedState) -> G::Move {
         (**self).calculate_next_move(new_state)
     }
+
+    fn calculate_next_move_within(
+        &mut self,
+        new_state: G::MaskedState,
+        limit: Duration,
+    ) -> Option<G::Move> {
+        (**self).calculate_next_move_within(new_state, limit)
+    }
+
+    fn on_game_start(&mut self, pid: G::PID, players: &[G::PID]) {
+        (**self).on_game_start(pid, players);
+    }
+
+    fn on_game_over(&mut self, scores: &FinalScores<G::PID>) {
+        (**self).on_game_over(scores);
+    }
+
+    fn on_game_aborted(&mut self, error: &SimulationError<G::PID>) {
+        (**self).on_game_aborted(error);
+    }
+
+    fn on_move_rejected(&mut self, error: &GameError<G::PID>) {
+        (**self).on_move_rejected(error);
+    }
+
+    fn take_log(&mut self) -> Option<String> {
+        (**self).take_log()
+    }
 }
 
 /// Blanket impl for Send variant.
@@ -96,6 +183,152 @@ impl<G: GameLogic> Agent for Box<dyn Agent<Game = G> + Send> {
     fn calculate_next_move(&mut self, new_state: G::MaskedState) -> G::Move {
         (**self).calculate_next_move(new_state)
     }
+
+    fn calculate_next_move_within(
+        &mut self,
+        new_state: G::MaskedState,
+        limit: Duration,
+    ) -> Option<G::Move> {
+        (**self).calculate_next_move_within(new_state, limit)
+    }
+
+    fn on_game_start(&mut self, pid: G::PID, players: &[G::PID]) {
+        (**self).on_game_start(pid, players);
+    }
+
+    fn on_game_over(&mut self, scores: &FinalScores<G::PID>) {
+        (**self).on_game_over(scores);
+    }
+
+    fn on_game_aborted(&mut self, error: &SimulationError<G::PID>) {
+        (**self).on_game_aborted(error);
+    }
+
+    fn on_move_rejected(&mut self, error: &GameError<G::PID>) {
+        (**self).on_move_rejected(error);
+    }
+
+    fn take_log(&mut self) -> Option<String> {
+        (**self).take_log()
+    }
+}
+
+/// The future returned by `AsyncAgent` methods.
+pub type AgentFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// An agent whose moves take a while to arrive, e.g. from a remote process or a web service.
+///
+/// The counterpart of [`Agent`] for async code: waiting for a move doesn't block a thread, and
+/// the moves of simultaneously active players are requested concurrently. The futures are boxed
+/// so that `Box<dyn AsyncAgent<Game = G>>` works like `Box<dyn Agent<Game = G>>`.
+pub trait AsyncAgent {
+    /// The game the agent is playing.
+    type Game: GameLogic;
+
+    /// Updates the agent's internal state based on the new game state.
+    /// This is called with a new game state when it's not the agent's turn.
+    fn digest_state(
+        &mut self,
+        new_state: <Self::Game as GameLogic>::MaskedState,
+    ) -> AgentFuture<'_, ()>;
+
+    /// Calculates the next move for the agent based on the new game state.
+    /// This is called when it's the agent's turn to make a move.
+    fn calculate_next_move(
+        &mut self,
+        new_state: <Self::Game as GameLogic>::MaskedState,
+    ) -> AgentFuture<'_, <Self::Game as GameLogic>::Move>;
+
+    /// Called once before the first turn, with the agent's own ID and every player in seat order.
+    fn on_game_start(
+        &mut self,
+        _pid: <Self::Game as GameLogic>::PID,
+        _players: &[<Self::Game as GameLogic>::PID],
+    ) {
+    }
+
+    /// Called once the game has ended with final scores, e.g. to learn from the outcome.
+    fn on_game_over(&mut self, _scores: &FinalScores<<Self::Game as GameLogic>::PID>) {}
+
+    /// Called instead of `on_game_over` when the game ends with an error, e.g. a timeout, a
+    /// panic or the turn limit, so the agent can clean up. Not called on an agent whose own
+    /// panic ended the game, since it is abandoned.
+    fn on_game_aborted(&mut self, _error: &SimulationError<<Self::Game as GameLogic>::PID>) {}
+
+    /// Called when the game logic rejects the agent's last move.
+    fn on_move_rejected(&mut self, _error: &GameError<<Self::Game as GameLogic>::PID>) {}
+
+    /// Returns what the agent logged during the game and forgets it, e.g. what a bot wrote to
+    /// stderr. Tournaments call it once a game is over and keep the log in the game's report.
+    fn take_log(&mut self) -> Option<String> {
+        None
+    }
+}
+
+/// Blanket impl so that Box<dyn AsyncAgent<Game = G>> can be used wherever AsyncAgent is expected.
+impl<G: GameLogic> AsyncAgent for Box<dyn AsyncAgent<Game = G>> {
+    type Game = G;
+
+    fn digest_state(&mut self, new_state: G::MaskedState) -> AgentFuture<'_, ()> {
+        (**self).digest_state(new_state)
+    }
+
+    fn calculate_next_move(&mut self, new_state: G::MaskedState) -> AgentFuture<'_, G::Move> {
+        (**self).calculate_next_move(new_state)
+    }
+
+    fn on_game_start(&mut self, pid: G::PID, players: &[G::PID]) {
+        (**self).on_game_start(pid, players);
+    }
+
+    fn on_game_over(&mut self, scores: &FinalScores<G::PID>) {
+        (**self).on_game_over(scores);
+    }
+
+    fn on_game_aborted(&mut self, error: &SimulationError<G::PID>) {
+        (**self).on_game_aborted(error);
+    }
+
+    fn on_move_rejected(&mut self, error: &GameError<G::PID>) {
+        (**self).on_move_rejected(error);
+    }
+
+    fn take_log(&mut self) -> Option<String> {
+        (**self).take_log()
+    }
+}
+
+/// Blanket impl for Send variant.
+impl<G: GameLogic> AsyncAgent for Box<dyn AsyncAgent<Game = G> + Send> {
+    type Game = G;
+
+    fn digest_state(&mut self, new_state: G::MaskedState) -> AgentFuture<'_, ()> {
+        (**self).digest_state(new_state)
+    }
+
+    fn calculate_next_move(&mut self, new_state: G::MaskedState) -> AgentFuture<'_, G::Move> {
+        (**self).calculate_next_move(new_state)
+    }
+
+    fn on_game_start(&mut self, pid: G::PID, players: &[G::PID]) {
+        (**self).on_game_start(pid, players);
+    }
+
+    fn on_game_over(&mut self, scores: &FinalScores<G::PID>) {
+        (**self).on_game_over(scores);
+    }
+
+    fn on_game_aborted(&mut self, error: &SimulationError<G::PID>) {
+        (**self).on_game_aborted(error);
+    }
+
+    fn on_move_rejected(&mut self, error: &GameError<G::PID>) {
+        (**self).on_move_rejected(error);
+    }
+
+    fn take_log(&mut self) -> Option<String> {
+        (**self).take_log()
+    }
 }
 
 /// Extension trait for games that can enumerate legal moves from a player's perspective.
@@ -115,3 +348,25 @@ pub trait LegalMoves: GameLogic {
     where
         Self::Move: Clone;
 }
+
+/// Extension trait for games whose full state can be rebuilt from a player's view, so that
+/// search agents can look ahead with `apply_moves` on a state of their own.
+///
+/// For perfect-information games this is a copy of the view. Games with hidden information
+/// sample the parts the player can't see (e.g. the other players' cards), so every call may
+/// return a different state, as long as it is consistent with the view.
+pub trait Determinize: GameLogic {
+    /// Returns a full state consistent with what `player` sees, and the players who move next
+    /// in it, like `init`. Called when `player` is asked for a move, so `player` is active.
+    ///
+    /// # Arguments
+    /// * `state` - The masked game state visible to the player.
+    /// * `player` - The player ID whose view it is.
+    /// * `rng` - The randomness to sample hidden information with.
+    fn determinize(
+        &self,
+        state: &Self::MaskedState,
+        player: Self::PID,
+        rng: &mut StdRng,
+    ) -> (Self::State, HashSet<Self::PID>);
+}
@@ -5,6 +5,8 @@ use std::{
     hash::Hash,
 };
 
+use super::outcome::Outcome;
+
 /// Represents a unique identifier for a player in the game.
 pub trait Id: Hash + Eq + Copy {}
 
@@ -13,6 +15,7 @@ pub type FinalScores<PID> = HashMap<PID, i32>;
 
 /// Errors that can occur during game operations.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GameError<PID: Id> {
     /// An invalid move was attempted.
     InvalidMove {
@@ -33,6 +36,17 @@ pub enum GameError<PID: Id> {
     },
 }
 
+impl<PID: Id> GameError<PID> {
+    /// Returns the player responsible for the error, if it can be attributed to one.
+    pub fn culprit(&self) -> Option<PID> {
+        match self {
+            GameError::InvalidMove { player, .. } => Some(*player),
+            GameError::WrongPlayer { got, .. } => Some(*got),
+            GameError::IllegalState(_) | GameError::MissingMoves { .. } => None,
+        }
+    }
+}
+
 impl<PID: Id + fmt::Debug> fmt::Display for GameError<PID> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -56,9 +70,21 @@ impl<PID: Id + fmt::Debug> Error for GameError<PID> {}
 
 /// Represents the result of applying moves to a game state.
 /// It can either continue with a new set of active players, or indicate the game is over with final scores.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MoveResult<PID: Id> {
     /// The game continues with the specified set of active players.
     Continue(HashSet<PID>),
-    /// The game is over with the final scores.
+    /// The game continues with `active` players, without the `eliminated` ones. Eliminated players
+    /// never move again, and rank below the players still in the game when it ends, unless the
+    /// final `Outcome` ranks them itself.
+    Eliminated {
+        eliminated: HashSet<PID>,
+        active: HashSet<PID>,
+    },
+    /// The game is over with the final scores. Players missing from the scores score 0, and
+    /// players are ranked by their scores.
     GameOver(FinalScores<PID>),
+    /// The game is over with a full outcome: rankings, scores and the reason it ended.
+    Finished(Outcome<PID>),
 }
@@ -1,4 +1,25 @@
 // Re-export commonly used types and traits for convenience
-pub use crate::core::{Agent, FinalScores, GameError, GameLogic, Id, LegalMoves, MoveResult};
-pub use crate::simulation::{simulate_game, SimulationError};
-pub use crate::tournament::{host_tournament, AgentFactory, IdGenerator, MatchMaker, MatchMakerOutput, TournamentResult};
+pub use crate::agents::{
+    AlphaBetaAgent, AlphaBetaConfig, Evaluator, HumanAgent, IsmctsAgent, MctsAgent, MctsConfig,
+    PimcAgent, RolloutPolicy, SearchBudget,
+};
+#[cfg(feature = "remote")]
+pub use crate::agents::{RemoteAgent, RemoteConfig};
+#[cfg(all(feature = "sandbox", unix))]
+pub use crate::agents::{ResourceLimits, SandboxConfig, SandboxFactory};
+pub use crate::core::{
+    derive_seed, Agent, AsyncAgent, Determinize, FinalScores, GameError, GameLogic, Id, LegalMoves,
+    MoveResult, Outcome, Termination,
+};
+pub use crate::simulation::{
+    block_on, simulate_game, simulate_game_async, simulate_game_async_with, simulate_game_parallel,
+    simulate_game_recorded, simulate_game_with, simulate_game_with_observers, AsyncAdapter,
+    BlockingAdapter, DeadlineAgent, GameRecord, GameRecorder, GameSession, IllegalMovePolicy,
+    Observer, Replay, SimulationConfig, SimulationError, TimeControl, TimeoutPolicy,
+};
+pub use crate::tournament::{
+    host_tournament, host_tournament_async, host_tournament_with, host_tournament_with_observers,
+    AgentFactory, Bracket, Elo, GameReport, Glicko2, IdGenerator, MatchMaker, MatchMakerOutput,
+    Matchup, Rated, RatingSystem, RoundRobin, Swiss, TieBreaker, TournamentConfig,
+    TournamentObserver, TournamentReport, TournamentResult,
+};
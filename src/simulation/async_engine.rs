@@ -0,0 +1,478 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    future::{poll_fn, Future},
+    panic::{catch_unwind, AssertUnwindSafe},
+    pin::{pin, Pin},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+    time::{Duration, Instant},
+};
+use indexmap::IndexMap;
+
+use crate::core::{Agent, AgentFuture, AsyncAgent, FinalScores, GameError, GameLogic, Outcome};
+
+use super::clock::Clocks;
+use super::engine::{
+    aborted_agent, catch_agent, catch_game, panic_message, resolve_turn, submit_move_timed,
+    submit_responses, AgentResponse, GameResult, GameSummary, OutcomeResult, Reply,
+    SimulationConfig, SimulationError, TurnStep,
+};
+use super::observer::Observer;
+use super::session::GameSession;
+
+/// Runs a future to completion on the current thread, parking it while the future is pending.
+///
+/// A minimal executor for driving async simulations and tournaments from sync code and tests.
+/// Futures that rely on a specific runtime (e.g. its timers or IO) need that runtime instead.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    let mut future = pin!(future);
+
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+/// Polls all the futures concurrently, returning their outputs in order once all are done.
+pub(crate) async fn join_all<F: Future + Unpin>(futures: Vec<F>) -> Vec<F::Output> {
+    let mut futures: Vec<Option<F>> = futures.into_iter().map(Some).collect();
+    let mut outputs: Vec<Option<F::Output>> = futures.iter().map(|_| None).collect();
+
+    poll_fn(|context| {
+        for (slot, output) in futures.iter_mut().zip(outputs.iter_mut()) {
+            if let Some(future) = slot {
+                if let Poll::Ready(value) = Pin::new(future).poll(context) {
+                    *output = Some(value);
+                    *slot = None;
+                }
+            }
+        }
+        if futures.iter().all(Option::is_none) {
+            Poll::Ready(
+                outputs
+                    .iter_mut()
+                    .map(|output| output.take().unwrap())
+                    .collect(),
+            )
+        } else {
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+/// Creates a future and runs it, turning a panic while creating or polling it into an error message.
+pub(crate) async fn catch_unwind_async<F: Future + Unpin>(
+    create: impl FnOnce() -> F,
+) -> Result<F::Output, String> {
+    let mut future = catch_unwind(AssertUnwindSafe(create)).map_err(panic_message)?;
+    poll_fn(|context| {
+        match catch_unwind(AssertUnwindSafe(|| Pin::new(&mut future).poll(context))) {
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(payload) => Poll::Ready(Err(panic_message(payload))),
+        }
+    })
+    .await
+}
+
+/// Waits for `future` until `limit` passes, returning `None` if it does and dropping the
+/// future. Without a limit, waits for as long as the future takes.
+async fn within<F: Future + Unpin>(
+    mut future: F,
+    limit: Option<Duration>,
+) -> Option<F::Output> {
+    let Some(limit) = limit else {
+        return Some(future.await);
+    };
+    let timer = Timer::start(limit);
+    poll_fn(|context| {
+        if let Poll::Ready(output) = Pin::new(&mut future).poll(context) {
+            return Poll::Ready(Some(output));
+        }
+        timer.poll_expired(context).map(|()| None)
+    })
+    .await
+}
+
+/// Wakes the task polling it once a limit passes, from a thread of its own, so that it works
+/// on any executor. The thread ends when the timer is dropped.
+struct Timer {
+    state: Arc<TimerState>,
+    thread: Thread,
+}
+
+#[derive(Default)]
+struct TimerState {
+    expired: AtomicBool,
+    /// Set once the timer is dropped, so that its thread stops waiting.
+    cancelled: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+}
+
+impl Timer {
+    fn start(limit: Duration) -> Self {
+        let state = Arc::new(TimerState::default());
+        let deadline = Instant::now() + limit;
+        let waiting = Arc::clone(&state);
+        let handle = thread::spawn(move || {
+            while !waiting.cancelled.load(Ordering::Acquire) {
+                let now = Instant::now();
+                if now >= deadline {
+                    waiting.expired.store(true, Ordering::Release);
+                    if let Some(waker) = waiting.waker.lock().unwrap().take() {
+                        waker.wake();
+                    }
+                    return;
+                }
+                thread::park_timeout(deadline - now);
+            }
+        });
+        Timer {
+            state,
+            thread: handle.thread().clone(),
+        }
+    }
+
+    fn poll_expired(&self, context: &mut Context<'_>) -> Poll<()> {
+        // Stored before checking, so that the thread either sees the waker or was seen expiring
+        *self.state.waker.lock().unwrap() = Some(context.waker().clone());
+        if self.state.expired.load(Ordering::Acquire) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        self.state.cancelled.store(true, Ordering::Release);
+        self.thread.unpark();
+    }
+}
+
+/// Makes an `Agent` usable as an `AsyncAgent`. The agent's methods run to completion when the
+/// future is first polled, so a slow agent still blocks the thread polling it.
+pub struct AsyncAdapter<A>(pub A);
+
+impl<A: Agent> AsyncAgent for AsyncAdapter<A> {
+    type Game = A::Game;
+
+    fn digest_state(
+        &mut self,
+        new_state: <A::Game as GameLogic>::MaskedState,
+    ) -> AgentFuture<'_, ()> {
+        Box::pin(async move { self.0.digest_state(new_state) })
+    }
+
+    fn calculate_next_move(
+        &mut self,
+        new_state: <A::Game as GameLogic>::MaskedState,
+    ) -> AgentFuture<'_, <A::Game as GameLogic>::Move> {
+        Box::pin(async move { self.0.calculate_next_move(new_state) })
+    }
+
+    fn on_game_start(
+        &mut self,
+        pid: <A::Game as GameLogic>::PID,
+        players: &[<A::Game as GameLogic>::PID],
+    ) {
+        self.0.on_game_start(pid, players);
+    }
+
+    fn on_game_over(&mut self, scores: &FinalScores<<A::Game as GameLogic>::PID>) {
+        self.0.on_game_over(scores);
+    }
+
+    fn on_game_aborted(&mut self, error: &SimulationError<<A::Game as GameLogic>::PID>) {
+        self.0.on_game_aborted(error);
+    }
+
+    fn on_move_rejected(&mut self, error: &GameError<<A::Game as GameLogic>::PID>) {
+        self.0.on_move_rejected(error);
+    }
+
+    fn take_log(&mut self) -> Option<String> {
+        self.0.take_log()
+    }
+}
+
+/// Makes an `AsyncAgent` usable as an `Agent`, waiting for every future with [`block_on`].
+pub struct BlockingAdapter<A>(pub A);
+
+impl<A: AsyncAgent> Agent for BlockingAdapter<A> {
+    type Game = A::Game;
+
+    fn digest_state(&mut self, new_state: <A::Game as GameLogic>::MaskedState) {
+        block_on(self.0.digest_state(new_state))
+    }
+
+    fn calculate_next_move(
+        &mut self,
+        new_state: <A::Game as GameLogic>::MaskedState,
+    ) -> <A::Game as GameLogic>::Move {
+        block_on(self.0.calculate_next_move(new_state))
+    }
+
+    fn on_game_start(
+        &mut self,
+        pid: <A::Game as GameLogic>::PID,
+        players: &[<A::Game as GameLogic>::PID],
+    ) {
+        self.0.on_game_start(pid, players);
+    }
+
+    fn on_game_over(&mut self, scores: &FinalScores<<A::Game as GameLogic>::PID>) {
+        self.0.on_game_over(scores);
+    }
+
+    fn on_game_aborted(&mut self, error: &SimulationError<<A::Game as GameLogic>::PID>) {
+        self.0.on_game_aborted(error);
+    }
+
+    fn on_move_rejected(&mut self, error: &GameError<<A::Game as GameLogic>::PID>) {
+        self.0.on_move_rejected(error);
+    }
+
+    fn take_log(&mut self) -> Option<String> {
+        self.0.take_log()
+    }
+}
+
+/// Simulates a game like [`simulate_game`](super::simulate_game), with async agents. Seeded
+/// games are played with [`simulate_game_async_with`].
+///
+/// # Returns
+/// A `Result` containing either:
+/// - `Ok(FinalScores)` - The game ended normally with final scores
+/// - `Err(SimulationError)` - The game exceeded max turns or encountered an error
+pub async fn simulate_game_async<G, A>(
+    game: &G,
+    agents: &mut IndexMap<G::PID, A>,
+    max_turns: Option<usize>,
+) -> GameResult<G::PID>
+where
+    G: GameLogic,
+    A: AsyncAgent<Game = G>,
+    G::PID: fmt::Debug,
+{
+    let config = SimulationConfig {
+        max_turns,
+        ..SimulationConfig::default()
+    };
+    simulate_game_async_with(game, agents, &config).await.result
+}
+
+/// Simulates a game like [`simulate_game_with`](super::simulate_game_with), with async agents.
+///
+/// Every turn, the moves of all active players are requested at the same time, and the other
+/// agents digest the state meanwhile. Time controls measure each agent from the start of the
+/// turn until its move arrives, and a move still pending once the agent's time is up is dropped,
+/// so the `TimeoutPolicy` applies even to agents that never answer. The resulting game is the same as with sync agents making the
+/// same moves: agents are handled in seat order once all of them are done.
+pub async fn simulate_game_async_with<G, A>(
+    game: &G,
+    agents: &mut IndexMap<G::PID, A>,
+    config: &SimulationConfig<G>,
+) -> GameSummary<G::PID>
+where
+    G: GameLogic,
+    A: AsyncAgent<Game = G>,
+    G::PID: fmt::Debug,
+{
+    simulate_async(game, agents, config, &mut ()).await
+}
+
+pub(crate) async fn simulate_async<G, A, O>(
+    game: &G,
+    agents: &mut IndexMap<G::PID, A>,
+    config: &SimulationConfig<G>,
+    observer: &mut O,
+) -> GameSummary<G::PID>
+where
+    G: GameLogic,
+    A: AsyncAgent<Game = G>,
+    O: Observer<G> + ?Sized,
+{
+    let start = Instant::now();
+    let mut turns = 0;
+    let result = run_game_async(game, agents, config, observer, &mut turns).await;
+
+    match &result {
+        Ok(outcome) => {
+            // Panics are ignored, since the game is already decided
+            for (&pid, agent) in agents.iter_mut() {
+                let _ = catch_agent(pid, || agent.on_game_over(&outcome.scores));
+            }
+            observer.on_game_over(&outcome.scores);
+        }
+        Err(error) => {
+            // Panics are ignored, since the game is already over
+            for (&pid, agent) in agents.iter_mut() {
+                if aborted_agent(error, pid) {
+                    let _ = catch_agent(pid, || agent.on_game_aborted(error));
+                }
+            }
+            observer.on_error(error);
+        }
+    }
+
+    GameSummary::new(result, turns, start.elapsed())
+}
+
+async fn run_game_async<G, A, O>(
+    game: &G,
+    agents: &mut IndexMap<G::PID, A>,
+    config: &SimulationConfig<G>,
+    observer: &mut O,
+    turn_count: &mut usize,
+) -> OutcomeResult<G::PID>
+where
+    G: GameLogic,
+    A: AsyncAgent<Game = G>,
+    O: Observer<G> + ?Sized,
+{
+    let players: Vec<G::PID> = agents.keys().copied().collect();
+    let mut session = catch_game(|| match config.seed {
+        Some(seed) => GameSession::new_seeded(game, players, seed),
+        None => GameSession::new(game, players),
+    })?;
+    for (&pid, agent) in agents.iter_mut() {
+        catch_agent(pid, || agent.on_game_start(pid, session.players()))?;
+    }
+    observer.on_game_start(
+        session.players(),
+        config.seed,
+        session.state(),
+        session.active_players(),
+    );
+    let mut clocks = config.time_control.map(Clocks::new);
+
+    loop {
+        // Check turn limit
+        if let Some(max) = config.max_turns {
+            if *turn_count >= max {
+                return Err(SimulationError::MaxTurnsExceeded(max));
+            }
+        }
+        *turn_count += 1;
+        observer.on_turn_start(*turn_count, session.state(), session.active_players());
+
+        // Ask active players for moves and notify inactive players, all at once
+        let mut requests: Vec<AgentFuture<'_, AgentResponse<G::Move>>> = Vec::new();
+        for (&pid, agent) in agents.iter_mut() {
+            let view = catch_game(|| session.masked_view(pid))?;
+            let active = session.is_active(pid);
+            let limit = clocks.as_ref().map(|clocks| clocks.limit(pid));
+            requests.push(Box::pin(async move {
+                let start = Instant::now();
+                let response = if active {
+                    catch_unwind_async(move || {
+                        Box::pin(within(agent.calculate_next_move(view), limit))
+                    })
+                    .await
+                    .map(Reply::Move)
+                } else {
+                    catch_unwind_async(move || agent.digest_state(view))
+                        .await
+                        .map(|()| Reply::Digested)
+                };
+                (response, start.elapsed())
+            }));
+        }
+        let responses = join_all(requests).await;
+
+        if let Some(outcome) = submit_responses(&mut clocks, config, &mut session, responses)? {
+            return Ok(outcome);
+        }
+
+        // Apply moves and check result
+        if let Some(outcome) = apply_turn_async(
+            game,
+            agents,
+            config,
+            &mut session,
+            &mut clocks,
+            observer,
+            *turn_count,
+        )
+        .await?
+        {
+            return Ok(outcome);
+        }
+    }
+}
+
+/// Applies the submitted moves of a turn like `apply_turn`, with async agents.
+async fn apply_turn_async<G, A, O>(
+    game: &G,
+    agents: &mut IndexMap<G::PID, A>,
+    config: &SimulationConfig<G>,
+    session: &mut GameSession<'_, G>,
+    clocks: &mut Option<Clocks<G::PID>>,
+    observer: &mut O,
+    turn: usize,
+) -> Result<Option<Outcome<G::PID>>, SimulationError<G::PID>>
+where
+    G: GameLogic,
+    A: AsyncAgent<Game = G>,
+    O: Observer<G> + ?Sized,
+{
+    let mut rejected = HashMap::new();
+    loop {
+        let step = resolve_turn(
+            game,
+            config,
+            session,
+            observer,
+            turn,
+            &mut rejected,
+            |pid, error| {
+                if let Some(agent) = agents.get_mut(&pid) {
+                    // A panic here doesn't change what happens next, so it is ignored
+                    let _ = catch_agent(pid, || agent.on_move_rejected(error));
+                }
+            },
+        )?;
+        let pid = match step {
+            TurnStep::Over(outcome) => return Ok(outcome),
+            TurnStep::Retry(pid) => pid,
+        };
+
+        let agent = &mut agents[&pid];
+        let view = catch_game(|| session.masked_view(pid))?;
+        let limit = clocks.as_ref().map(|clocks| clocks.limit(pid));
+        let start = Instant::now();
+        let player_move =
+            catch_unwind_async(move || Box::pin(within(agent.calculate_next_move(view), limit)))
+                .await
+                .map_err(|message| SimulationError::AgentPanicked {
+                    player: pid,
+                    message,
+                })?;
+        let elapsed = start.elapsed();
+        if let Some(outcome) =
+            submit_move_timed(clocks, config, session, pid, elapsed, player_move)?
+        {
+            return Ok(Some(outcome));
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::{collections::HashMap, fmt, sync::Arc, time::Duration};
+
+use crate::core::{GameLogic, Id};
+
+/// How much time agents get to think about their moves.
+///
+/// Only `Agent::calculate_next_move` is timed; `Agent::digest_state` is free. Agents are asked
+/// with `Agent::calculate_next_move_within` and the time left for the move. Most agents can't be
+/// interrupted, so their time is measured after they return and one that never returns still
+/// blocks the game. Wrap such agents in a [`DeadlineAgent`](super::DeadlineAgent), which is
+/// abandoned once its time is up. Async agents are always cut off: their move future is dropped
+/// once their time is up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TimeControl {
+    /// Every move must be made within the given time.
+    PerMove(Duration),
+    /// Chess-style clock: every player starts with `initial` time, the time spent on a move is
+    /// taken off the clock, and `increment` is added back after every move made in time.
+    Clock {
+        initial: Duration,
+        increment: Duration,
+    },
+    /// Every player has the given time for all of their moves in the game.
+    PerGame(Duration),
+}
+
+/// Picks a move for a player who ran out of time, given their view of the state and their ID.
+pub type DefaultMoveFn<G> = Arc<
+    dyn Fn(&<G as GameLogic>::MaskedState, <G as GameLogic>::PID) -> <G as GameLogic>::Move
+        + Send
+        + Sync,
+>;
+
+/// What happens when an agent runs out of time.
+#[derive(Default)]
+pub enum TimeoutPolicy<G: GameLogic> {
+    /// The agent's move is replaced by the one this function picks, given the agent's view of
+    /// the state and its player ID. The game continues.
+    DefaultMove(DefaultMoveFn<G>),
+    /// The game ends immediately. The agent scores `penalty` and everyone else scores 0.
+    Forfeit { penalty: i32 },
+    /// The game ends with `SimulationError::TimedOut`.
+    #[default]
+    Error,
+}
+
+impl<G: GameLogic> TimeoutPolicy<G> {
+    /// Creates a `DefaultMove` policy from a function.
+    pub fn default_move(
+        choose: impl Fn(&G::MaskedState, G::PID) -> G::Move + Send + Sync + 'static,
+    ) -> Self {
+        TimeoutPolicy::DefaultMove(Arc::new(choose))
+    }
+}
+
+impl<G: GameLogic> Clone for TimeoutPolicy<G> {
+    fn clone(&self) -> Self {
+        match self {
+            TimeoutPolicy::DefaultMove(choose) => TimeoutPolicy::DefaultMove(Arc::clone(choose)),
+            TimeoutPolicy::Forfeit { penalty } => TimeoutPolicy::Forfeit { penalty: *penalty },
+            TimeoutPolicy::Error => TimeoutPolicy::Error,
+        }
+    }
+}
+
+impl<G: GameLogic> fmt::Debug for TimeoutPolicy<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeoutPolicy::DefaultMove(_) => f.write_str("DefaultMove(..)"),
+            TimeoutPolicy::Forfeit { penalty } => {
+                f.debug_struct("Forfeit").field("penalty", penalty).finish()
+            }
+            TimeoutPolicy::Error => f.write_str("Error"),
+        }
+    }
+}
+
+/// Tracks the remaining time of every player under a `TimeControl`.
+pub(crate) struct Clocks<PID: Id> {
+    control: TimeControl,
+    remaining: HashMap<PID, Duration>,
+}
+
+impl<PID: Id> Clocks<PID> {
+    pub(crate) fn new(control: TimeControl) -> Self {
+        Clocks {
+            control,
+            remaining: HashMap::new(),
+        }
+    }
+
+    /// Returns how long `player` may take for their next move.
+    pub(crate) fn limit(&self, player: PID) -> Duration {
+        match self.control {
+            TimeControl::PerMove(limit) => limit,
+            TimeControl::Clock { initial, .. } | TimeControl::PerGame(initial) => {
+                self.remaining.get(&player).copied().unwrap_or(initial)
+            }
+        }
+    }
+
+    /// Records that `player` spent `elapsed` on a move. Returns false if the player ran out of time.
+    pub(crate) fn charge(&mut self, player: PID, elapsed: Duration) -> bool {
+        let (initial, increment) = match self.control {
+            TimeControl::PerMove(limit) => return elapsed <= limit,
+            TimeControl::Clock { initial, increment } => (initial, increment),
+            TimeControl::PerGame(budget) => (budget, Duration::ZERO),
+        };
+
+        let remaining = self.remaining.entry(player).or_insert(initial);
+        match remaining.checked_sub(elapsed) {
+            Some(left) => {
+                *remaining = left + increment;
+                true
+            }
+            None => {
+                *remaining = Duration::ZERO;
+                false
+            }
+        }
+    }
+}
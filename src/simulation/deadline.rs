@@ -0,0 +1,219 @@
+use std::{
+    panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
+    thread,
+    time::Duration,
+};
+
+use crossbeam::channel::{self, Receiver, RecvTimeoutError, Sender};
+
+use crate::core::{Agent, FinalScores, GameError, GameLogic};
+
+use super::engine::SimulationError;
+
+/// A call to make on the agent's thread.
+enum Request<G: GameLogic> {
+    Digest(G::MaskedState),
+    Calculate(G::MaskedState),
+    Start(G::PID, Vec<G::PID>),
+    GameOver(FinalScores<G::PID>),
+    Aborted(SimulationError<G::PID>),
+    Rejected(GameError<G::PID>),
+    TakeLog,
+}
+
+/// What the agent returned for a request.
+enum Answer<M> {
+    Done,
+    Move(M),
+    Log(Option<String>),
+}
+
+/// The answer to a request, or the panic of the agent.
+type Reply<M> = thread::Result<Answer<M>>;
+
+/// An agent running on its own thread, so that the simulation can stop waiting for its move
+/// once its time is up.
+///
+/// Every call is passed on to the wrapped agent and waited for. When a move is asked for with
+/// `Agent::calculate_next_move_within` and the limit passes first, the agent is abandoned: the
+/// simulation applies its `TimeoutPolicy`, and every later move of the agent times out right
+/// away. The thread ends once the abandoned agent returns, if ever.
+///
+/// Panics of the wrapped agent are raised again on the calling thread, so the simulation
+/// reports them as usual.
+pub struct DeadlineAgent<G: GameLogic> {
+    /// `None` once the agent has been abandoned.
+    requests: Option<Sender<Request<G>>>,
+    replies: Receiver<Reply<G::Move>>,
+}
+
+impl<G> DeadlineAgent<G>
+where
+    G: GameLogic + 'static,
+    G::PID: Send,
+    G::MaskedState: Send,
+    G::Move: Send,
+{
+    /// Starts a thread running `agent`.
+    pub fn new<A>(agent: A) -> Self
+    where
+        A: Agent<Game = G> + Send + 'static,
+    {
+        let (requests, received) = channel::unbounded::<Request<G>>();
+        let (replied, replies) = channel::unbounded();
+        thread::spawn(move || {
+            let mut agent = agent;
+            for request in received {
+                let reply = catch_unwind(AssertUnwindSafe(|| match request {
+                    Request::Digest(state) => {
+                        agent.digest_state(state);
+                        Answer::Done
+                    }
+                    Request::Calculate(state) => Answer::Move(agent.calculate_next_move(state)),
+                    Request::Start(pid, players) => {
+                        agent.on_game_start(pid, &players);
+                        Answer::Done
+                    }
+                    Request::GameOver(scores) => {
+                        agent.on_game_over(&scores);
+                        Answer::Done
+                    }
+                    Request::Aborted(error) => {
+                        agent.on_game_aborted(&error);
+                        Answer::Done
+                    }
+                    Request::Rejected(error) => {
+                        agent.on_move_rejected(&error);
+                        Answer::Done
+                    }
+                    Request::TakeLog => Answer::Log(agent.take_log()),
+                }));
+                let panicked = reply.is_err();
+                if replied.send(reply).is_err() || panicked {
+                    break;
+                }
+            }
+        });
+        DeadlineAgent {
+            requests: Some(requests),
+            replies,
+        }
+    }
+
+    /// Returns whether the agent ran out of time and was abandoned.
+    pub fn is_abandoned(&self) -> bool {
+        self.requests.is_none()
+    }
+
+    /// Sends `request` to the agent's thread. Returns `false` if the agent was abandoned.
+    fn send(&self, request: Request<G>) -> bool {
+        match &self.requests {
+            Some(requests) => {
+                requests
+                    .send(request)
+                    .expect("the agent's thread runs until it panics");
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Waits for the agent's reply, raising its panic on this thread.
+    fn wait(&self) -> Answer<G::Move> {
+        match self
+            .replies
+            .recv()
+            .expect("the agent's thread replies before it ends")
+        {
+            Ok(reply) => reply,
+            Err(payload) => resume_unwind(payload),
+        }
+    }
+}
+
+impl<G> Agent for DeadlineAgent<G>
+where
+    G: GameLogic + 'static,
+    G::PID: Send,
+    G::MaskedState: Send,
+    G::Move: Send,
+{
+    type Game = G;
+
+    fn digest_state(&mut self, new_state: G::MaskedState) {
+        if self.send(Request::Digest(new_state)) {
+            self.wait();
+        }
+    }
+
+    /// # Panics
+    /// Panics if the agent was abandoned.
+    fn calculate_next_move(&mut self, new_state: G::MaskedState) -> G::Move {
+        assert!(
+            self.send(Request::Calculate(new_state)),
+            "the agent was abandoned after running out of time"
+        );
+        match self.wait() {
+            Answer::Move(player_move) => player_move,
+            _ => unreachable!("the agent replies to a move request with a move"),
+        }
+    }
+
+    fn calculate_next_move_within(
+        &mut self,
+        new_state: G::MaskedState,
+        limit: Duration,
+    ) -> Option<G::Move> {
+        if !self.send(Request::Calculate(new_state)) {
+            return None;
+        }
+        match self.replies.recv_timeout(limit) {
+            Ok(Ok(Answer::Move(player_move))) => Some(player_move),
+            Ok(Ok(_)) => unreachable!("the agent replies to a move request with a move"),
+            Ok(Err(payload)) => resume_unwind(payload),
+            Err(RecvTimeoutError::Timeout) => {
+                // Dropping the sender ends the thread once the agent returns
+                self.requests = None;
+                None
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                unreachable!("the agent's thread replies before it ends")
+            }
+        }
+    }
+
+    fn on_game_start(&mut self, pid: G::PID, players: &[G::PID]) {
+        if self.send(Request::Start(pid, players.to_vec())) {
+            self.wait();
+        }
+    }
+
+    fn on_game_over(&mut self, scores: &FinalScores<G::PID>) {
+        if self.send(Request::GameOver(scores.clone())) {
+            self.wait();
+        }
+    }
+
+    fn on_game_aborted(&mut self, error: &SimulationError<G::PID>) {
+        if self.send(Request::Aborted(error.clone())) {
+            self.wait();
+        }
+    }
+
+    fn on_move_rejected(&mut self, error: &GameError<G::PID>) {
+        if self.send(Request::Rejected(error.clone())) {
+            self.wait();
+        }
+    }
+
+    /// Returns `None` once the agent was abandoned.
+    fn take_log(&mut self) -> Option<String> {
+        if !self.send(Request::TakeLog) {
+            return None;
+        }
+        match self.wait() {
+            Answer::Log(log) => log,
+            _ => unreachable!("the agent replies to a log request with its log"),
+        }
+    }
+}
@@ -1,15 +1,51 @@
-use std::fmt;
+use std::{
+    any::Any,
+    collections::HashMap,
+    fmt,
+    panic::{catch_unwind, AssertUnwindSafe},
+    time::{Duration, Instant},
+};
 use indexmap::IndexMap;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
-use crate::core::{Agent, FinalScores, GameError, GameLogic, Id, MoveResult};
+use crate::core::{derive_seed, Agent, FinalScores, GameError, GameLogic, Id, Outcome};
+
+use super::clock::{Clocks, TimeControl, TimeoutPolicy};
+use super::illegal_move::IllegalMovePolicy;
+use super::observer::Observer;
+use super::replay::{GameRecordOf, GameRecorder};
+use super::session::GameSession;
 
 /// Errors that can occur during game simulation.
-#[derive(Debug)]
-pub enum SimulationError<PID: Id + fmt::Debug> {
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SimulationError<PID: Id> {
     /// The maximum number of turns was exceeded without the game ending.
     MaxTurnsExceeded(usize),
     /// A game error occurred during simulation.
     GameError(GameError<PID>),
+    /// An agent panicked while being created, notified or asked for a move.
+    AgentPanicked { player: PID, message: String },
+    /// The game logic panicked.
+    GamePanicked(String),
+    /// A tournament observer panicked while watching the game.
+    ObserverPanicked(String),
+    /// An agent ran out of time under `TimeoutPolicy::Error`.
+    TimedOut { player: PID, elapsed: Duration },
+}
+
+impl<PID: Id> SimulationError<PID> {
+    /// Returns the player responsible for the failure, if it can be attributed to one.
+    pub fn culprit(&self) -> Option<PID> {
+        match self {
+            SimulationError::MaxTurnsExceeded(_)
+            | SimulationError::GamePanicked(_)
+            | SimulationError::ObserverPanicked(_) => None,
+            SimulationError::GameError(e) => e.culprit(),
+            SimulationError::AgentPanicked { player, .. }
+            | SimulationError::TimedOut { player, .. } => Some(*player),
+        }
+    }
 }
 
 impl<PID: Id + fmt::Debug> fmt::Display for SimulationError<PID> {
@@ -19,12 +55,109 @@ impl<PID: Id + fmt::Debug> fmt::Display for SimulationError<PID> {
                 write!(f, "Game exceeded maximum of {} turns without ending", max)
             }
             SimulationError::GameError(e) => write!(f, "Game error: {}", e),
+            SimulationError::AgentPanicked { player, message } => {
+                write!(f, "Agent of player {:?} panicked: {}", player, message)
+            }
+            SimulationError::GamePanicked(message) => write!(f, "Game logic panicked: {}", message),
+            SimulationError::ObserverPanicked(message) => {
+                write!(f, "Tournament observer panicked: {}", message)
+            }
+            SimulationError::TimedOut { player, elapsed } => {
+                write!(f, "Player {:?} ran out of time after {:?}", player, elapsed)
+            }
         }
     }
 }
 
 impl<PID: Id + fmt::Debug> std::error::Error for SimulationError<PID> {}
 
+/// The outcome of a simulated game: its final scores, or the error that ended it.
+pub type GameResult<PID> = Result<FinalScores<PID>, SimulationError<PID>>;
+
+/// How a game ended: its full outcome, or the error that ended it.
+pub(crate) type OutcomeResult<PID> = Result<Outcome<PID>, SimulationError<PID>>;
+
+/// Options controlling how a game is simulated.
+pub struct SimulationConfig<G: GameLogic> {
+    /// Maximum number of turns before the simulation terminates with an error.
+    pub max_turns: Option<usize>,
+    /// How much time agents get for their moves. `None` means unlimited.
+    pub time_control: Option<TimeControl>,
+    /// What happens when an agent runs out of time.
+    pub timeout_policy: TimeoutPolicy<G>,
+    /// What happens when the game logic rejects an agent's move.
+    pub illegal_move_policy: IllegalMovePolicy<G>,
+    /// Seed for the game setup, passed to `GameLogic::init_seeded`. `None` uses `GameLogic::init`.
+    /// Agents are created by the caller, who is responsible for seeding them.
+    pub seed: Option<u64>,
+}
+
+impl<G: GameLogic> Default for SimulationConfig<G> {
+    fn default() -> Self {
+        SimulationConfig {
+            max_turns: None,
+            time_control: None,
+            timeout_policy: TimeoutPolicy::default(),
+            illegal_move_policy: IllegalMovePolicy::default(),
+            seed: None,
+        }
+    }
+}
+
+impl<G: GameLogic> Clone for SimulationConfig<G> {
+    fn clone(&self) -> Self {
+        SimulationConfig {
+            max_turns: self.max_turns,
+            time_control: self.time_control,
+            timeout_policy: self.timeout_policy.clone(),
+            illegal_move_policy: self.illegal_move_policy.clone(),
+            seed: self.seed,
+        }
+    }
+}
+
+impl<G: GameLogic> fmt::Debug for SimulationConfig<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SimulationConfig")
+            .field("max_turns", &self.max_turns)
+            .field("time_control", &self.time_control)
+            .field("timeout_policy", &self.timeout_policy)
+            .field("illegal_move_policy", &self.illegal_move_policy)
+            .field("seed", &self.seed)
+            .finish()
+    }
+}
+
+/// Everything known about a simulated game once it has ended.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameSummary<PID: Id> {
+    /// The final scores, or the error that ended the game.
+    pub result: GameResult<PID>,
+    /// The rankings, scores and termination reason, if the game ended with scores.
+    /// The scores are the same as in `result`, with every player present.
+    pub outcome: Option<Outcome<PID>>,
+    /// The number of turns that were started, including the one that ended the game.
+    pub turns: usize,
+    /// The wall-clock time the game took.
+    pub duration: Duration,
+}
+
+impl<PID: Id> GameSummary<PID> {
+    pub(crate) fn new(outcome: OutcomeResult<PID>, turns: usize, duration: Duration) -> Self {
+        let (result, outcome) = match outcome {
+            Ok(outcome) => (Ok(outcome.scores.clone()), Some(outcome)),
+            Err(error) => (Err(error), None),
+        };
+        GameSummary {
+            result,
+            outcome,
+            turns,
+            duration,
+        }
+    }
+}
+
 /// Simulates a game using the provided game logic and agents.
 ///
 /// # Arguments
@@ -37,6 +170,9 @@ impl<PID: Id + fmt::Debug> std::error::Error for SimulationError<PID> {}
 /// - `Ok(FinalScores)` - The game ended normally with final scores
 /// - `Err(SimulationError)` - The game exceeded max turns or encountered an error
 ///
+/// The game is set up with `GameLogic::init`. To seed it, set `SimulationConfig::seed` and use
+/// [`simulate_game_with`].
+///
 /// # Examples
 /// ```ignore
 /// let game = MyGame::new();
@@ -56,45 +192,554 @@ where
     A: Agent<Game = G>,
     G::PID: fmt::Debug,
 {
-    let (mut current_state, mut current_players) = game.init(agents.keys().copied().collect());
-    let mut turn_count = 0;
+    let config = SimulationConfig {
+        max_turns,
+        ..SimulationConfig::default()
+    };
+    simulate_game_with(game, agents, &config).result
+}
+
+/// Simulates a game like [`simulate_game`], with full configuration and a summary of the game.
+///
+/// Panics in agents or in the game logic are caught and end the game with
+/// `SimulationError::AgentPanicked` or `SimulationError::GamePanicked`.
+///
+/// With a `TimeControl`, agents that take too long to move are dealt with according to the
+/// configured `TimeoutPolicy`.
+///
+/// Agents learn about the game through their lifecycle hooks: `Agent::on_game_start` before the
+/// first turn, `Agent::on_move_rejected` when the game logic rejects their move, and
+/// `Agent::on_game_over` once the game ends with final scores, or `Agent::on_game_aborted` once
+/// it ends with an error.
+///
+/// # Arguments
+/// * `game` - A reference to the game logic that defines the rules of the game.
+/// * `agents` - A mutable mapping of player IDs to their respective agents, in seat order.
+/// * `config` - The options for this simulation.
+///
+/// # Returns
+/// A `GameSummary` with the result of the game, the number of turns played and its duration.
+pub fn simulate_game_with<G, A>(
+    game: &G,
+    agents: &mut IndexMap<G::PID, A>,
+    config: &SimulationConfig<G>,
+) -> GameSummary<G::PID>
+where
+    G: GameLogic,
+    A: Agent<Game = G>,
+    G::PID: fmt::Debug,
+{
+    simulate(game, agents, config, &mut ())
+}
+
+/// Simulates a game like [`simulate_game_with`], reporting every step of the game to
+/// `observers` in order.
+///
+/// # Arguments
+/// * `game` - A reference to the game logic that defines the rules of the game.
+/// * `agents` - A mutable mapping of player IDs to their respective agents, in seat order.
+/// * `config` - The options for this simulation.
+/// * `observers` - The observers to notify, e.g. loggers or a [`GameRecorder`].
+///
+/// # Returns
+/// A `GameSummary` with the result of the game, the number of turns played and its duration.
+pub fn simulate_game_with_observers<G, A>(
+    game: &G,
+    agents: &mut IndexMap<G::PID, A>,
+    config: &SimulationConfig<G>,
+    observers: &mut [&mut dyn Observer<G>],
+) -> GameSummary<G::PID>
+where
+    G: GameLogic,
+    A: Agent<Game = G>,
+    G::PID: fmt::Debug,
+{
+    simulate(game, agents, config, observers)
+}
+
+/// Simulates a game like [`simulate_game_with`], also recording every move so the game can
+/// be analysed afterwards with a [`Replay`](super::Replay).
+///
+/// # Returns
+/// The `GameSummary` of the game, and a `GameRecord` of the players, seed, every turn's
+/// active players and moves, and the final `MoveResult`.
+pub fn simulate_game_recorded<G, A>(
+    game: &G,
+    agents: &mut IndexMap<G::PID, A>,
+    config: &SimulationConfig<G>,
+) -> (GameSummary<G::PID>, GameRecordOf<G>)
+where
+    G: GameLogic,
+    G::Move: Clone,
+    A: Agent<Game = G>,
+    G::PID: fmt::Debug,
+{
+    let mut recorder = GameRecorder::new();
+    let summary = simulate(game, agents, config, &mut recorder);
+    (summary, recorder.into_record())
+}
+
+pub(crate) fn simulate<G, A, O>(
+    game: &G,
+    agents: &mut IndexMap<G::PID, A>,
+    config: &SimulationConfig<G>,
+    observer: &mut O,
+) -> GameSummary<G::PID>
+where
+    G: GameLogic,
+    A: Agent<Game = G>,
+    O: Observer<G> + ?Sized,
+{
+    let start = Instant::now();
+    let mut turns = 0;
+    let result = run_game(game, agents, config, observer, &mut turns);
+
+    match &result {
+        Ok(outcome) => {
+            finish_agents(agents, &outcome.scores);
+            observer.on_game_over(&outcome.scores);
+        }
+        Err(error) => {
+            abort_agents(agents, error);
+            observer.on_error(error);
+        }
+    }
+
+    GameSummary::new(result, turns, start.elapsed())
+}
+
+fn run_game<G, A, O>(
+    game: &G,
+    agents: &mut IndexMap<G::PID, A>,
+    config: &SimulationConfig<G>,
+    observer: &mut O,
+    turn_count: &mut usize,
+) -> OutcomeResult<G::PID>
+where
+    G: GameLogic,
+    A: Agent<Game = G>,
+    O: Observer<G> + ?Sized,
+{
+    let players: Vec<G::PID> = agents.keys().copied().collect();
+    let mut session = catch_game(|| match config.seed {
+        Some(seed) => GameSession::new_seeded(game, players, seed),
+        None => GameSession::new(game, players),
+    })?;
+    start_agents(agents, session.players())?;
+    observer.on_game_start(
+        session.players(),
+        config.seed,
+        session.state(),
+        session.active_players(),
+    );
+    let mut clocks = config.time_control.map(Clocks::new);
 
     loop {
         // Check turn limit
-        if let Some(max) = max_turns {
-            if turn_count >= max {
+        if let Some(max) = config.max_turns {
+            if *turn_count >= max {
                 return Err(SimulationError::MaxTurnsExceeded(max));
             }
         }
-        turn_count += 1;
+        *turn_count += 1;
+        observer.on_turn_start(*turn_count, session.state(), session.active_players());
 
         // Collect moves from active players and notify inactive players
-        let player_moves = agents
-            .iter_mut()
-            .filter_map(|(&pid, agent_ref)| {
-                if current_players.contains(&pid) {
-                    Some((
-                        pid,
-                        agent_ref.calculate_next_move(game.mask_state(&current_state, pid)),
-                    ))
-                } else {
-                    agent_ref.digest_state(game.mask_state(&current_state, pid));
-                    None
+        for (&pid, agent) in agents.iter_mut() {
+            let view = catch_game(|| session.masked_view(pid))?;
+            if session.is_active(pid) {
+                let start = Instant::now();
+                let limit = clocks.as_ref().map(|clocks| clocks.limit(pid));
+                let player_move = catch_agent(pid, || ask_agent(agent, view, limit))?;
+                let elapsed = start.elapsed();
+
+                let forfeit = submit_move_timed(
+                    &mut clocks,
+                    config,
+                    &mut session,
+                    pid,
+                    elapsed,
+                    player_move,
+                )?;
+                if let Some(outcome) = forfeit {
+                    return Ok(outcome);
                 }
-            })
-            .collect();
+            } else {
+                catch_agent(pid, || agent.digest_state(view))?;
+            }
+        }
 
         // Apply moves and check result
-        match game.apply_moves(&mut current_state, player_moves) {
-            Ok(MoveResult::GameOver(result)) => {
-                return Ok(result);
+        let game_over = apply_turn(
+            game,
+            agents,
+            config,
+            &mut session,
+            &mut clocks,
+            observer,
+            *turn_count,
+        )?;
+        if let Some(outcome) = game_over {
+            return Ok(outcome);
+        }
+    }
+}
+
+/// Applies the submitted moves of a turn, dealing with rejected moves according to the
+/// `IllegalMovePolicy`. Returns the outcome once the game is over.
+pub(crate) fn apply_turn<G, A, O>(
+    game: &G,
+    agents: &mut IndexMap<G::PID, A>,
+    config: &SimulationConfig<G>,
+    session: &mut GameSession<'_, G>,
+    clocks: &mut Option<Clocks<G::PID>>,
+    observer: &mut O,
+    turn: usize,
+) -> Result<Option<Outcome<G::PID>>, SimulationError<G::PID>>
+where
+    G: GameLogic,
+    A: Agent<Game = G>,
+    O: Observer<G> + ?Sized,
+{
+    let mut rejected = HashMap::new();
+    loop {
+        let step = resolve_turn(
+            game,
+            config,
+            session,
+            observer,
+            turn,
+            &mut rejected,
+            |pid, error| {
+                if let Some(agent) = agents.get_mut(&pid) {
+                    // A panic here doesn't change what happens next, so it is ignored
+                    let _ = catch_agent(pid, || agent.on_move_rejected(error));
+                }
+            },
+        )?;
+        let pid = match step {
+            TurnStep::Over(outcome) => return Ok(outcome),
+            TurnStep::Retry(pid) => pid,
+        };
+
+        let agent = &mut agents[&pid];
+        let view = catch_game(|| session.masked_view(pid))?;
+        let start = Instant::now();
+        let limit = clocks.as_ref().map(|clocks| clocks.limit(pid));
+        let player_move = catch_agent(pid, || ask_agent(agent, view, limit))?;
+        let elapsed = start.elapsed();
+        if let Some(outcome) =
+            submit_move_timed(clocks, config, session, pid, elapsed, player_move)?
+        {
+            return Ok(Some(outcome));
+        }
+    }
+}
+
+/// Where a turn stands after its submitted moves were tried.
+pub(crate) enum TurnStep<G: GameLogic> {
+    /// The moves were applied, or the turn ended the game. Holds the outcome if the game is over.
+    Over(Option<Outcome<G::PID>>),
+    /// The player's move was rejected, and they are asked for another one.
+    Retry(G::PID),
+}
+
+/// Applies the submitted moves of a turn until they are accepted or a player has to be asked
+/// for another move, following the `IllegalMovePolicy`. `notify` tells the player whose move
+/// was rejected about it. Shared by the sync and async engines, which only differ in how they
+/// ask for moves.
+pub(crate) fn resolve_turn<G, O>(
+    game: &G,
+    config: &SimulationConfig<G>,
+    session: &mut GameSession<'_, G>,
+    observer: &mut O,
+    turn: usize,
+    rejected: &mut HashMap<G::PID, usize>,
+    mut notify: impl FnMut(G::PID, &GameError<G::PID>),
+) -> Result<TurnStep<G>, SimulationError<G::PID>>
+where
+    G: GameLogic,
+    O: Observer<G> + ?Sized,
+{
+    loop {
+        observer.on_moves(turn, session.submitted_moves());
+        let rollback = config.illegal_move_policy.rollback();
+        let error = match catch_game(|| session.step_with(rollback))? {
+            Ok(move_result) => {
+                observer.on_moves_applied(turn, session.state(), &move_result);
+                return Ok(TurnStep::Over(session.final_outcome().cloned()));
+            }
+            Err(error) => error,
+        };
+
+        if let Some(pid) = error.culprit() {
+            notify(pid, &error);
+        }
+        match handle_rejection(game, config, session, error, rejected) {
+            Rejection::Retry(pid) => return Ok(TurnStep::Retry(pid)),
+            Rejection::Substitute(pid, player_move) => {
+                session
+                    .submit_move(pid, player_move)
+                    .map_err(SimulationError::GameError)?;
             }
-            Ok(MoveResult::Continue(players)) => {
-                current_players = players;
+            Rejection::Forfeit(pid, penalty) => {
+                return Ok(TurnStep::Over(Some(forfeit(session, pid, penalty))))
             }
-            Err(e) => {
-                return Err(SimulationError::GameError(e));
+            Rejection::End(error) => return Err(error),
+        }
+    }
+}
+
+/// How to go on after the game logic rejected the moves of a turn.
+enum Rejection<G: GameLogic> {
+    /// Ask the player for another move.
+    Retry(G::PID),
+    /// Play the given move for the player instead.
+    Substitute(G::PID, G::Move),
+    /// The player forfeits the game with the given penalty.
+    Forfeit(G::PID, i32),
+    /// The game ends with an error.
+    End(SimulationError<G::PID>),
+}
+
+/// Decides how to go on after the game logic rejected the moves of a turn with `error`,
+/// according to the `IllegalMovePolicy`. `rejected` counts the rejected moves of every player
+/// this turn.
+fn handle_rejection<G: GameLogic>(
+    game: &G,
+    config: &SimulationConfig<G>,
+    session: &GameSession<'_, G>,
+    error: GameError<G::PID>,
+    rejected: &mut HashMap<G::PID, usize>,
+) -> Rejection<G> {
+    let culprit = match error.culprit() {
+        Some(pid) if session.players().contains(&pid) => pid,
+        _ => return Rejection::End(SimulationError::GameError(error)),
+    };
+    let count = rejected.entry(culprit).or_insert(0);
+    *count += 1;
+
+    match &config.illegal_move_policy {
+        IllegalMovePolicy::Retry { attempts, .. } if *count <= *attempts => {
+            Rejection::Retry(culprit)
+        }
+        IllegalMovePolicy::RandomLegalMove { legal_moves, .. } if *count == 1 => {
+            let mut moves =
+                match catch_game(|| legal_moves(game, &session.masked_view(culprit), culprit)) {
+                    Ok(moves) => moves,
+                    Err(panic) => return Rejection::End(panic),
+                };
+            if moves.is_empty() {
+                return Rejection::End(SimulationError::GameError(error));
             }
+            // Seeded games substitute the same moves every time
+            let mut rng = match config.seed {
+                Some(seed) => {
+                    let stream = derive_seed(seed, u64::MAX);
+                    StdRng::seed_from_u64(derive_seed(stream, session.turn() as u64))
+                }
+                None => StdRng::from_rng(&mut rand::rng()),
+            };
+            let index = rng.random_range(0..moves.len());
+            Rejection::Substitute(culprit, moves.swap_remove(index))
         }
+        IllegalMovePolicy::Forfeit { penalty } => Rejection::Forfeit(culprit, *penalty),
+        _ => Rejection::End(SimulationError::GameError(error)),
+    }
+}
+
+/// Tells every agent its ID and the players of the game, in seat order.
+pub(crate) fn start_agents<G, A>(
+    agents: &mut IndexMap<G::PID, A>,
+    players: &[G::PID],
+) -> Result<(), SimulationError<G::PID>>
+where
+    G: GameLogic,
+    A: Agent<Game = G>,
+{
+    for (&pid, agent) in agents.iter_mut() {
+        catch_agent(pid, || agent.on_game_start(pid, players))?;
     }
+    Ok(())
+}
+
+/// Tells every agent the final scores. Panics are ignored, since the game is already decided.
+pub(crate) fn finish_agents<G, A>(agents: &mut IndexMap<G::PID, A>, scores: &FinalScores<G::PID>)
+where
+    G: GameLogic,
+    A: Agent<Game = G>,
+{
+    for (&pid, agent) in agents.iter_mut() {
+        let _ = catch_agent(pid, || agent.on_game_over(scores));
+    }
+}
+
+/// Asks `agent` for its move, within `limit` if the game has a time control. Returns `None` if
+/// the agent ran out of time.
+pub(crate) fn ask_agent<A: Agent>(
+    agent: &mut A,
+    view: <A::Game as GameLogic>::MaskedState,
+    limit: Option<Duration>,
+) -> Option<<A::Game as GameLogic>::Move> {
+    match limit {
+        Some(limit) => agent.calculate_next_move_within(view, limit),
+        None => Some(agent.calculate_next_move(view)),
+    }
+}
+
+/// Tells every agent the game ended with `error`, except one that panicked. Panics are ignored,
+/// since the game is already over.
+pub(crate) fn abort_agents<G, A>(agents: &mut IndexMap<G::PID, A>, error: &SimulationError<G::PID>)
+where
+    G: GameLogic,
+    A: Agent<Game = G>,
+{
+    for (&pid, agent) in agents.iter_mut() {
+        if aborted_agent(error, pid) {
+            let _ = catch_agent(pid, || agent.on_game_aborted(error));
+        }
+    }
+}
+
+/// Returns whether `player`'s agent should hear that the game ended with `error`, which is
+/// the case unless the agent panicked.
+pub(crate) fn aborted_agent<PID: Id>(error: &SimulationError<PID>, player: PID) -> bool {
+    !matches!(error, SimulationError::AgentPanicked { player: culprit, .. } if *culprit == player)
+}
+
+/// What an agent did during a turn: digested the state, or answered with its move, which is
+/// `None` if it ran out of time.
+pub(crate) enum Reply<M> {
+    Digested,
+    Move(Option<M>),
+}
+
+/// What an agent produced during a turn, or the panic message if it panicked, and how long it
+/// took.
+pub(crate) type AgentResponse<M> = (Result<Reply<M>, String>, Duration);
+
+/// Charges and submits the moves all agents returned for a turn, in seat order, so that the game
+/// doesn't depend on which agent answered first. Returns the outcome if a player forfeited.
+pub(crate) fn submit_responses<G: GameLogic>(
+    clocks: &mut Option<Clocks<G::PID>>,
+    config: &SimulationConfig<G>,
+    session: &mut GameSession<'_, G>,
+    responses: Vec<AgentResponse<G::Move>>,
+) -> Result<Option<Outcome<G::PID>>, SimulationError<G::PID>> {
+    let seats = session.players().to_vec();
+    for (pid, (response, elapsed)) in seats.into_iter().zip(responses) {
+        let player_move = match response {
+            Ok(Reply::Move(player_move)) => player_move,
+            Ok(Reply::Digested) => continue,
+            Err(message) => {
+                return Err(SimulationError::AgentPanicked {
+                    player: pid,
+                    message,
+                })
+            }
+        };
+
+        if let Some(outcome) =
+            submit_move_timed(clocks, config, session, pid, elapsed, player_move)?
+        {
+            return Ok(Some(outcome));
+        }
+    }
+    Ok(None)
+}
+
+/// Charges `elapsed` to `player`'s clock and submits their move, or the one the timeout policy
+/// picks if they ran out of time or gave up on the move. Returns the outcome if the player
+/// forfeited.
+pub(crate) fn submit_move_timed<G: GameLogic>(
+    clocks: &mut Option<Clocks<G::PID>>,
+    config: &SimulationConfig<G>,
+    session: &mut GameSession<'_, G>,
+    player: G::PID,
+    elapsed: Duration,
+    player_move: Option<G::Move>,
+) -> Result<Option<Outcome<G::PID>>, SimulationError<G::PID>> {
+    match charge_move(clocks, config, session, player, elapsed, player_move)? {
+        TimedMove::Move(player_move) => {
+            session
+                .submit_move(player, player_move)
+                .map_err(SimulationError::GameError)?;
+            Ok(None)
+        }
+        TimedMove::Forfeit { penalty } => Ok(Some(forfeit(session, player, penalty))),
+    }
+}
+
+/// Ends the game with `player` forfeiting it, returning the outcome.
+pub(crate) fn forfeit<G: GameLogic>(
+    session: &mut GameSession<'_, G>,
+    player: G::PID,
+    penalty: i32,
+) -> Outcome<G::PID> {
+    let players = session.remaining_players();
+    session.end_with(Outcome::forfeit(player, penalty, &players));
+    session
+        .final_outcome()
+        .cloned()
+        .expect("a forfeited game is over")
+}
+
+/// A move after its time was charged: the move to play, or the penalty of a forfeited game.
+enum TimedMove<G: GameLogic> {
+    Move(G::Move),
+    Forfeit { penalty: i32 },
+}
+
+/// Charges `elapsed` to `player`'s clock and applies the timeout policy if they ran out of time.
+/// A missing move, from an agent that gave up once its time was up, is always a timeout.
+fn charge_move<G: GameLogic>(
+    clocks: &mut Option<Clocks<G::PID>>,
+    config: &SimulationConfig<G>,
+    session: &GameSession<'_, G>,
+    player: G::PID,
+    elapsed: Duration,
+    player_move: Option<G::Move>,
+) -> Result<TimedMove<G>, SimulationError<G::PID>> {
+    let in_time = clocks
+        .as_mut()
+        .map_or(true, |clocks| clocks.charge(player, elapsed));
+    if let (true, Some(player_move)) = (in_time, player_move) {
+        return Ok(TimedMove::Move(player_move));
+    }
+
+    match &config.timeout_policy {
+        TimeoutPolicy::DefaultMove(choose) => {
+            catch_game(|| choose(&session.masked_view(player), player)).map(TimedMove::Move)
+        }
+        TimeoutPolicy::Forfeit { penalty } => Ok(TimedMove::Forfeit { penalty: *penalty }),
+        TimeoutPolicy::Error => Err(SimulationError::TimedOut { player, elapsed }),
+    }
+}
+
+/// Runs agent code, turning a panic into `SimulationError::AgentPanicked`.
+///
+/// The agent is abandoned after a panic, so it doesn't matter if it was left inconsistent.
+pub(crate) fn catch_agent<PID: Id, T>(
+    player: PID,
+    f: impl FnOnce() -> T,
+) -> Result<T, SimulationError<PID>> {
+    catch_unwind(AssertUnwindSafe(f)).map_err(|payload| SimulationError::AgentPanicked {
+        player,
+        message: panic_message(payload),
+    })
+}
+
+/// Runs game logic code, turning a panic into `SimulationError::GamePanicked`.
+pub(crate) fn catch_game<PID: Id, T>(f: impl FnOnce() -> T) -> Result<T, SimulationError<PID>> {
+    catch_unwind(AssertUnwindSafe(f))
+        .map_err(|payload| SimulationError::GamePanicked(panic_message(payload)))
+}
+
+/// Extracts the message from a panic payload, which is a `&str` or `String` for `panic!`.
+pub(crate) fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic payload".to_string())
 }
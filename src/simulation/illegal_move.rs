@@ -0,0 +1,171 @@
+use std::{collections::HashMap, fmt};
+
+use crate::core::{GameLogic, LegalMoves};
+
+/// Lists the legal moves of a player, given the game, the player's view of the state and their ID.
+pub type LegalMovesFn<G> =
+    fn(&G, &<G as GameLogic>::MaskedState, <G as GameLogic>::PID) -> Vec<<G as GameLogic>::Move>;
+
+/// Copies the state and the moves of a turn before they are applied, so that a turn the game
+/// logic rejected can be undone. `GameLogic::apply_moves` may change the state before failing.
+pub struct Rollback<G: GameLogic> {
+    state: fn(&G::State) -> G::State,
+    player_move: fn(&G::Move) -> G::Move,
+}
+
+impl<G> Rollback<G>
+where
+    G: GameLogic,
+    G::State: Clone,
+    G::Move: Clone,
+{
+    /// Creates a rollback copying states and moves with `Clone`.
+    pub fn new() -> Self {
+        Rollback {
+            state: G::State::clone,
+            player_move: G::Move::clone,
+        }
+    }
+}
+
+impl<G> Default for Rollback<G>
+where
+    G: GameLogic,
+    G::State: Clone,
+    G::Move: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<G: GameLogic> Rollback<G> {
+    /// Copies the state and the moves about to be applied to it.
+    pub(crate) fn snapshot(
+        &self,
+        state: &G::State,
+        moves: &HashMap<G::PID, G::Move>,
+    ) -> (G::State, HashMap<G::PID, G::Move>) {
+        let moves = moves
+            .iter()
+            .map(|(&pid, player_move)| (pid, (self.player_move)(player_move)))
+            .collect();
+        ((self.state)(state), moves)
+    }
+}
+
+impl<G: GameLogic> Clone for Rollback<G> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<G: GameLogic> Copy for Rollback<G> {}
+
+impl<G: GameLogic> fmt::Debug for Rollback<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Rollback")
+    }
+}
+
+/// What happens when the game logic rejects a move with an error attributed to a player, e.g.
+/// `GameError::InvalidMove`. Errors no player is responsible for always end the game.
+///
+/// Policies that continue the game undo the rejected turn with a [`Rollback`] and apply it
+/// again, with the other players' moves unchanged. Use the constructors, which set it up.
+/// The agent that made the move is told with `Agent::on_move_rejected` in any case.
+#[derive(Default)]
+pub enum IllegalMovePolicy<G: GameLogic> {
+    /// The game ends with `SimulationError::GameError`.
+    #[default]
+    Abort,
+    /// The agent is asked for another move, up to `attempts` times a turn. The game ends with
+    /// `SimulationError::GameError` once the attempts are used up.
+    Retry {
+        attempts: usize,
+        rollback: Rollback<G>,
+    },
+    /// The move is replaced by a random legal move. The game ends with
+    /// `SimulationError::GameError` if there is none, or if the game rejects it too.
+    RandomLegalMove {
+        legal_moves: LegalMovesFn<G>,
+        rollback: Rollback<G>,
+    },
+    /// The game ends immediately. The agent scores `penalty` and everyone else scores 0.
+    Forfeit { penalty: i32 },
+}
+
+impl<G> IllegalMovePolicy<G>
+where
+    G: GameLogic,
+    G::State: Clone,
+    G::Move: Clone,
+{
+    /// Creates a `Retry` policy allowing `attempts` more moves a turn.
+    pub fn retry(attempts: usize) -> Self {
+        IllegalMovePolicy::Retry {
+            attempts,
+            rollback: Rollback::new(),
+        }
+    }
+
+    /// Creates a `RandomLegalMove` policy picking from `LegalMoves::legal_moves`.
+    pub fn random_legal_move() -> Self
+    where
+        G: LegalMoves,
+    {
+        IllegalMovePolicy::RandomLegalMove {
+            legal_moves: G::legal_moves,
+            rollback: Rollback::new(),
+        }
+    }
+}
+
+impl<G: GameLogic> IllegalMovePolicy<G> {
+    /// Returns how to undo a rejected turn, if the policy continues the game after one.
+    pub(crate) fn rollback(&self) -> Option<&Rollback<G>> {
+        match self {
+            IllegalMovePolicy::Retry { rollback, .. }
+            | IllegalMovePolicy::RandomLegalMove { rollback, .. } => Some(rollback),
+            IllegalMovePolicy::Abort | IllegalMovePolicy::Forfeit { .. } => None,
+        }
+    }
+}
+
+impl<G: GameLogic> Clone for IllegalMovePolicy<G> {
+    fn clone(&self) -> Self {
+        match self {
+            IllegalMovePolicy::Abort => IllegalMovePolicy::Abort,
+            IllegalMovePolicy::Retry { attempts, rollback } => IllegalMovePolicy::Retry {
+                attempts: *attempts,
+                rollback: *rollback,
+            },
+            IllegalMovePolicy::RandomLegalMove {
+                legal_moves,
+                rollback,
+            } => IllegalMovePolicy::RandomLegalMove {
+                legal_moves: *legal_moves,
+                rollback: *rollback,
+            },
+            IllegalMovePolicy::Forfeit { penalty } => {
+                IllegalMovePolicy::Forfeit { penalty: *penalty }
+            }
+        }
+    }
+}
+
+impl<G: GameLogic> fmt::Debug for IllegalMovePolicy<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IllegalMovePolicy::Abort => f.write_str("Abort"),
+            IllegalMovePolicy::Retry { attempts, .. } => f
+                .debug_struct("Retry")
+                .field("attempts", attempts)
+                .finish_non_exhaustive(),
+            IllegalMovePolicy::RandomLegalMove { .. } => f.write_str("RandomLegalMove(..)"),
+            IllegalMovePolicy::Forfeit { penalty } => {
+                f.debug_struct("Forfeit").field("penalty", penalty).finish()
+            }
+        }
+    }
+}
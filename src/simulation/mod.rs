@@ -1,3 +1,24 @@
+pub mod async_engine;
+pub mod clock;
+pub mod deadline;
 pub mod engine;
+pub mod illegal_move;
+pub mod observer;
+pub mod parallel;
+pub mod replay;
+pub mod session;
 
-pub use engine::{simulate_game, SimulationError};
+pub use async_engine::{
+    block_on, simulate_game_async, simulate_game_async_with, AsyncAdapter, BlockingAdapter,
+};
+pub use clock::{DefaultMoveFn, TimeControl, TimeoutPolicy};
+pub use deadline::DeadlineAgent;
+pub use engine::{
+    simulate_game, simulate_game_recorded, simulate_game_with, simulate_game_with_observers,
+    GameResult, GameSummary, SimulationConfig, SimulationError,
+};
+pub use illegal_move::{IllegalMovePolicy, LegalMovesFn, Rollback};
+pub use observer::Observer;
+pub use parallel::simulate_game_parallel;
+pub use replay::{GameRecord, GameRecordOf, GameRecorder, Replay, StepResult, TurnRecord};
+pub use session::GameSession;
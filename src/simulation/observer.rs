@@ -0,0 +1,93 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::core::{FinalScores, GameLogic, MoveResult};
+
+use super::engine::SimulationError;
+
+/// Watches a game as it is simulated, e.g. to log it, show progress, collect metrics or
+/// visualize it. Observers see the full state, not what the agents see.
+///
+/// Every callback does nothing by default, so implementors only pick the events they need.
+/// Turns are numbered from 1.
+pub trait Observer<G: GameLogic> {
+    /// Called once the game is set up, before the first turn.
+    /// `seed` is the seed the game was set up with, if any.
+    fn on_game_start(
+        &mut self,
+        _players: &[G::PID],
+        _seed: Option<u64>,
+        _state: &G::State,
+        _active_players: &HashSet<G::PID>,
+    ) {
+    }
+
+    /// Called at the start of every turn, before the agents are asked for moves.
+    fn on_turn_start(
+        &mut self,
+        _turn: usize,
+        _state: &G::State,
+        _active_players: &HashSet<G::PID>,
+    ) {
+    }
+
+    /// Called with the moves of a turn, just before they are applied. Called again for the same
+    /// turn with the new moves if an `IllegalMovePolicy` retries it.
+    fn on_moves(&mut self, _turn: usize, _moves: &HashMap<G::PID, G::Move>) {}
+
+    /// Called after the moves of a turn were applied successfully.
+    fn on_moves_applied(&mut self, _turn: usize, _state: &G::State, _result: &MoveResult<G::PID>) {}
+
+    /// Called when the game ends with an error.
+    fn on_error(&mut self, _error: &SimulationError<G::PID>) {}
+
+    /// Called when the game ends with final scores.
+    fn on_game_over(&mut self, _scores: &FinalScores<G::PID>) {}
+}
+
+/// Observes nothing.
+impl<G: GameLogic> Observer<G> for () {}
+
+/// Forwards every event to each observer in turn.
+impl<G: GameLogic> Observer<G> for [&mut dyn Observer<G>] {
+    fn on_game_start(
+        &mut self,
+        players: &[G::PID],
+        seed: Option<u64>,
+        state: &G::State,
+        active_players: &HashSet<G::PID>,
+    ) {
+        for observer in self.iter_mut() {
+            observer.on_game_start(players, seed, state, active_players);
+        }
+    }
+
+    fn on_turn_start(&mut self, turn: usize, state: &G::State, active_players: &HashSet<G::PID>) {
+        for observer in self.iter_mut() {
+            observer.on_turn_start(turn, state, active_players);
+        }
+    }
+
+    fn on_moves(&mut self, turn: usize, moves: &HashMap<G::PID, G::Move>) {
+        for observer in self.iter_mut() {
+            observer.on_moves(turn, moves);
+        }
+    }
+
+    fn on_moves_applied(&mut self, turn: usize, state: &G::State, result: &MoveResult<G::PID>) {
+        for observer in self.iter_mut() {
+            observer.on_moves_applied(turn, state, result);
+        }
+    }
+
+    fn on_error(&mut self, error: &SimulationError<G::PID>) {
+        for observer in self.iter_mut() {
+            observer.on_error(error);
+        }
+    }
+
+    fn on_game_over(&mut self, scores: &FinalScores<G::PID>) {
+        for observer in self.iter_mut() {
+            observer.on_game_over(scores);
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use std::{
+    fmt,
+    panic::{catch_unwind, AssertUnwindSafe},
+    thread,
+    time::Instant,
+};
+use indexmap::IndexMap;
+
+use crate::core::{Agent, GameLogic};
+
+use super::clock::Clocks;
+use super::engine::{
+    abort_agents, apply_turn, ask_agent, catch_game, finish_agents, panic_message, start_agents,
+    submit_responses, AgentResponse, GameSummary, OutcomeResult, Reply, SimulationConfig,
+    SimulationError,
+};
+use super::observer::Observer;
+use super::session::GameSession;
+
+/// Simulates a game like [`simulate_game_with`](super::simulate_game_with), asking all agents
+/// for their moves in parallel, each on its own thread.
+///
+/// Every turn, active agents compute their moves while the other agents digest the state, so a
+/// turn takes as long as its slowest agent rather than all of them together. The moves are
+/// charged and submitted in seat order once every agent is done, so the game is the same as
+/// with `simulate_game_with` for agents making the same moves. Time controls measure each agent
+/// on its own.
+///
+/// Worth it for simultaneous-move games with slow agents. For turn-based games, or agents that
+/// answer in microseconds, the threads cost more than they save.
+///
+/// # Returns
+/// A `GameSummary` with the result of the game, the number of turns played and its duration.
+pub fn simulate_game_parallel<G, A>(
+    game: &G,
+    agents: &mut IndexMap<G::PID, A>,
+    config: &SimulationConfig<G>,
+) -> GameSummary<G::PID>
+where
+    G: GameLogic,
+    G::MaskedState: Send,
+    G::Move: Send,
+    A: Agent<Game = G> + Send,
+    G::PID: fmt::Debug,
+{
+    simulate_parallel(game, agents, config, &mut ())
+}
+
+pub(crate) fn simulate_parallel<G, A, O>(
+    game: &G,
+    agents: &mut IndexMap<G::PID, A>,
+    config: &SimulationConfig<G>,
+    observer: &mut O,
+) -> GameSummary<G::PID>
+where
+    G: GameLogic,
+    G::MaskedState: Send,
+    G::Move: Send,
+    A: Agent<Game = G> + Send,
+    O: Observer<G> + ?Sized,
+{
+    let start = Instant::now();
+    let mut turns = 0;
+    let result = run_game_parallel(game, agents, config, observer, &mut turns);
+
+    match &result {
+        Ok(outcome) => {
+            finish_agents(agents, &outcome.scores);
+            observer.on_game_over(&outcome.scores);
+        }
+        Err(error) => {
+            abort_agents(agents, error);
+            observer.on_error(error);
+        }
+    }
+
+    GameSummary::new(result, turns, start.elapsed())
+}
+
+fn run_game_parallel<G, A, O>(
+    game: &G,
+    agents: &mut IndexMap<G::PID, A>,
+    config: &SimulationConfig<G>,
+    observer: &mut O,
+    turn_count: &mut usize,
+) -> OutcomeResult<G::PID>
+where
+    G: GameLogic,
+    G::MaskedState: Send,
+    G::Move: Send,
+    A: Agent<Game = G> + Send,
+    O: Observer<G> + ?Sized,
+{
+    let players: Vec<G::PID> = agents.keys().copied().collect();
+    let mut session = catch_game(|| match config.seed {
+        Some(seed) => GameSession::new_seeded(game, players, seed),
+        None => GameSession::new(game, players),
+    })?;
+    start_agents(agents, session.players())?;
+    observer.on_game_start(
+        session.players(),
+        config.seed,
+        session.state(),
+        session.active_players(),
+    );
+    let mut clocks = config.time_control.map(Clocks::new);
+
+    loop {
+        // Check turn limit
+        if let Some(max) = config.max_turns {
+            if *turn_count >= max {
+                return Err(SimulationError::MaxTurnsExceeded(max));
+            }
+        }
+        *turn_count += 1;
+        observer.on_turn_start(*turn_count, session.state(), session.active_players());
+
+        // Mask the state for everyone up front, so only agent code runs on the threads
+        let mut requests = Vec::with_capacity(agents.len());
+        for (&pid, agent) in agents.iter_mut() {
+            let view = catch_game(|| session.masked_view(pid))?;
+            let limit = clocks.as_ref().map(|clocks| clocks.limit(pid));
+            requests.push((agent, view, session.is_active(pid), limit));
+        }
+
+        // Ask active players for moves and notify inactive players, all at once
+        let responses: Vec<AgentResponse<G::Move>> = thread::scope(|scope| {
+            let handles: Vec<_> = requests
+                .into_iter()
+                .map(|(agent, view, active, limit)| {
+                    scope.spawn(move || {
+                        let start = Instant::now();
+                        let response = catch_unwind(AssertUnwindSafe(|| {
+                            if active {
+                                Reply::Move(ask_agent(agent, view, limit))
+                            } else {
+                                agent.digest_state(view);
+                                Reply::Digested
+                            }
+                        }))
+                        .map_err(panic_message);
+                        (response, start.elapsed())
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .expect("agent panics are caught on their thread")
+                })
+                .collect()
+        });
+
+        if let Some(outcome) = submit_responses(&mut clocks, config, &mut session, responses)? {
+            return Ok(outcome);
+        }
+
+        // Apply moves and check result
+        // Rejected moves are retried one at a time, on this thread
+        if let Some(outcome) = apply_turn(
+            game,
+            agents,
+            config,
+            &mut session,
+            &mut clocks,
+            observer,
+            *turn_count,
+        )? {
+            return Ok(outcome);
+        }
+    }
+}
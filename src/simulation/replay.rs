@@ -0,0 +1,260 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::core::{GameError, GameLogic, Id, MoveResult};
+
+use super::observer::Observer;
+
+/// The moves of a single turn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TurnRecord<PID: Id, M> {
+    /// The players who had to move this turn.
+    pub active_players: HashSet<PID>,
+    /// The moves that were applied, including moves substituted by a `TimeoutPolicy` or an
+    /// `IllegalMovePolicy`.
+    pub moves: HashMap<PID, M>,
+}
+
+/// Everything needed to play a simulated game again, move by move.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameRecord<PID: Id, M> {
+    /// The players passed to `GameLogic::init`, in seat order.
+    pub players: Vec<PID>,
+    /// The seed passed to `GameLogic::init_seeded`, if the game was seeded.
+    pub seed: Option<u64>,
+    /// Every turn whose moves reached the game logic, in order.
+    pub turns: Vec<TurnRecord<PID, M>>,
+    /// The result of the last turn, or `None` if the game ended without the game logic
+    /// deciding it (turn limit, panic, timeout) or applying the last moves failed.
+    pub final_result: Option<MoveResult<PID>>,
+}
+
+/// The `GameRecord` of a game of `G`.
+pub type GameRecordOf<G> = GameRecord<<G as GameLogic>::PID, <G as GameLogic>::Move>;
+
+/// The result of applying one turn of a replay.
+pub type StepResult<'a, PID> = Result<&'a MoveResult<PID>, GameError<PID>>;
+
+/// An observer that records a game into a `GameRecord`, to be replayed later.
+///
+/// A recorder can be reused: starting a new game starts a new record.
+pub struct GameRecorder<G: GameLogic> {
+    record: GameRecordOf<G>,
+    active_players: HashSet<G::PID>,
+}
+
+impl<G: GameLogic> GameRecorder<G> {
+    /// Creates a recorder with an empty record, filled in once it observes a game.
+    pub fn new() -> Self {
+        GameRecorder {
+            record: GameRecord {
+                players: Vec::new(),
+                seed: None,
+                turns: Vec::new(),
+                final_result: None,
+            },
+            active_players: HashSet::new(),
+        }
+    }
+
+    /// Returns the record of the current or last game.
+    pub fn record(&self) -> &GameRecordOf<G> {
+        &self.record
+    }
+
+    /// Returns the record of the current or last game, consuming the recorder.
+    pub fn into_record(self) -> GameRecordOf<G> {
+        self.record
+    }
+}
+
+impl<G: GameLogic> Default for GameRecorder<G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<G> Observer<G> for GameRecorder<G>
+where
+    G: GameLogic,
+    G::Move: Clone,
+{
+    fn on_game_start(
+        &mut self,
+        players: &[G::PID],
+        seed: Option<u64>,
+        _state: &G::State,
+        _active_players: &HashSet<G::PID>,
+    ) {
+        self.record = GameRecord {
+            players: players.to_vec(),
+            seed,
+            turns: Vec::new(),
+            final_result: None,
+        };
+    }
+
+    fn on_turn_start(&mut self, _turn: usize, _state: &G::State, active_players: &HashSet<G::PID>) {
+        self.active_players = active_players.clone();
+    }
+
+    fn on_moves(&mut self, turn: usize, moves: &HashMap<G::PID, G::Move>) {
+        let record = TurnRecord {
+            active_players: self.active_players.clone(),
+            moves: moves.clone(),
+        };
+        // A turn retried under an `IllegalMovePolicy` only keeps the moves that were applied
+        if self.record.turns.len() == turn {
+            self.record.turns[turn - 1] = record;
+        } else {
+            self.record.turns.push(record);
+        }
+    }
+
+    fn on_moves_applied(&mut self, _turn: usize, _state: &G::State, result: &MoveResult<G::PID>) {
+        if let MoveResult::GameOver(_) | MoveResult::Finished(_) = result {
+            self.record.final_result = Some(result.clone());
+        }
+    }
+}
+
+/// Plays a recorded game again, turn by turn, with the full state available at every point.
+///
+/// The game logic is re-driven with the recorded moves, so it has to be deterministic given
+/// the recorded players and seed. A replay starts before the first turn.
+pub struct Replay<'a, G: GameLogic> {
+    game: &'a G,
+    record: &'a GameRecordOf<G>,
+    state: G::State,
+    active_players: HashSet<G::PID>,
+    turn: usize,
+    result: Option<MoveResult<G::PID>>,
+}
+
+impl<'a, G> Replay<'a, G>
+where
+    G: GameLogic,
+    G::Move: Clone,
+{
+    /// Sets the game up as it was before the first turn.
+    pub fn new(game: &'a G, record: &'a GameRecordOf<G>) -> Self {
+        let (state, active_players) = Self::init(game, record);
+        Replay {
+            game,
+            record,
+            state,
+            active_players,
+            turn: 0,
+            result: None,
+        }
+    }
+
+    fn init(game: &G, record: &GameRecordOf<G>) -> (G::State, HashSet<G::PID>) {
+        let players = record.players.clone();
+        match record.seed {
+            Some(seed) => game.init_seeded(players, seed),
+            None => game.init(players),
+        }
+    }
+
+    /// Returns the number of turns applied so far.
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    /// Returns the number of recorded turns.
+    pub fn len(&self) -> usize {
+        self.record.turns.len()
+    }
+
+    /// Returns true if the record has no turns.
+    pub fn is_empty(&self) -> bool {
+        self.record.turns.is_empty()
+    }
+
+    /// Returns true if every recorded turn has been applied.
+    pub fn is_finished(&self) -> bool {
+        self.turn == self.len()
+    }
+
+    /// Returns the full game state at the current turn.
+    pub fn state(&self) -> &G::State {
+        &self.state
+    }
+
+    /// Returns the players who have to move next.
+    pub fn active_players(&self) -> &HashSet<G::PID> {
+        &self.active_players
+    }
+
+    /// Returns the state at the current turn as `player` saw it.
+    pub fn masked_state(&self, player: G::PID) -> G::MaskedState {
+        self.game.mask_state(&self.state, player)
+    }
+
+    /// Returns the moves the next turn is going to apply.
+    pub fn next_moves(&self) -> Option<&HashMap<G::PID, G::Move>> {
+        self.record.turns.get(self.turn).map(|turn| &turn.moves)
+    }
+
+    /// Returns the result of the last applied turn.
+    pub fn last_result(&self) -> Option<&MoveResult<G::PID>> {
+        self.result.as_ref()
+    }
+
+    /// Applies the next recorded turn. Returns `None` once every turn has been applied.
+    ///
+    /// # Errors
+    /// Returns the `GameError` the game logic reports for the recorded moves. For a record of
+    /// a game that ended in an error, this happens on the last turn.
+    pub fn step(&mut self) -> Option<StepResult<'_, G::PID>> {
+        let moves = self.next_moves()?.clone();
+        self.turn += 1;
+
+        match self.game.apply_moves(&mut self.state, moves) {
+            Ok(result) => {
+                self.active_players = match &result {
+                    MoveResult::Continue(players) => players.clone(),
+                    MoveResult::Eliminated { active, .. } => active.clone(),
+                    MoveResult::GameOver(_) | MoveResult::Finished(_) => HashSet::new(),
+                };
+                Some(Ok(self.result.insert(result)))
+            }
+            Err(error) => {
+                self.result = None;
+                Some(Err(error))
+            }
+        }
+    }
+
+    /// Moves the replay to just after `turn` turns have been applied. Seeking backwards
+    /// sets the game up again and replays it from the start.
+    ///
+    /// # Panics
+    /// Panics if `turn` is greater than the number of recorded turns.
+    ///
+    /// # Errors
+    /// Returns the first `GameError` the game logic reports on the way. The replay stays at
+    /// the turn that failed.
+    pub fn seek(&mut self, turn: usize) -> Result<(), GameError<G::PID>> {
+        assert!(
+            turn <= self.len(),
+            "cannot seek past the last recorded turn"
+        );
+
+        if turn < self.turn {
+            let (state, active_players) = Self::init(self.game, self.record);
+            self.state = state;
+            self.active_players = active_players;
+            self.turn = 0;
+            self.result = None;
+        }
+        while self.turn < turn {
+            if let Some(Err(error)) = self.step() {
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+}
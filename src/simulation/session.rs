@@ -0,0 +1,276 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::core::{GameError, GameLogic, MoveResult, Outcome};
+
+use super::engine::{GameResult, SimulationError};
+use super::illegal_move::Rollback;
+
+/// A game in progress, advanced one turn at a time by its owner.
+///
+/// A session is what `simulate_game` drives internally, exposed for callers that need to stay in
+/// control between turns, e.g. a GUI, a network server or a debugger. Moves are submitted for
+/// each pending player, then `step` applies them all at once.
+///
+/// The session doesn't catch panics in the game logic, and doesn't know about agents or time.
+pub struct GameSession<'a, G: GameLogic> {
+    game: &'a G,
+    players: Vec<G::PID>,
+    state: G::State,
+    active_players: HashSet<G::PID>,
+    moves: HashMap<G::PID, G::Move>,
+    turn: usize,
+    eliminated: Vec<HashSet<G::PID>>,
+    outcome: Option<GameResult<G::PID>>,
+    final_outcome: Option<Outcome<G::PID>>,
+}
+
+impl<'a, G: GameLogic> GameSession<'a, G> {
+    /// Sets up a new game with `GameLogic::init`. `players` are in seat order.
+    pub fn new(game: &'a G, players: Vec<G::PID>) -> Self {
+        let (state, active_players) = game.init(players.clone());
+        Self::from_parts(game, players, state, active_players)
+    }
+
+    /// Sets up a new game with `GameLogic::init_seeded`. `players` are in seat order.
+    pub fn new_seeded(game: &'a G, players: Vec<G::PID>, seed: u64) -> Self {
+        let (state, active_players) = game.init_seeded(players.clone(), seed);
+        Self::from_parts(game, players, state, active_players)
+    }
+
+    fn from_parts(
+        game: &'a G,
+        players: Vec<G::PID>,
+        state: G::State,
+        active_players: HashSet<G::PID>,
+    ) -> Self {
+        GameSession {
+            game,
+            players,
+            state,
+            active_players,
+            moves: HashMap::new(),
+            turn: 0,
+            eliminated: Vec::new(),
+            outcome: None,
+            final_outcome: None,
+        }
+    }
+
+    /// Returns the players of the game, in seat order.
+    pub fn players(&self) -> &[G::PID] {
+        &self.players
+    }
+
+    /// Returns the full game state.
+    pub fn state(&self) -> &G::State {
+        &self.state
+    }
+
+    /// Returns the number of turns applied so far.
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    /// Returns the players who have to move this turn.
+    pub fn active_players(&self) -> &HashSet<G::PID> {
+        &self.active_players
+    }
+
+    /// Returns the players eliminated so far, grouped by the turn they were eliminated in.
+    pub fn eliminated_players(&self) -> &[HashSet<G::PID>] {
+        &self.eliminated
+    }
+
+    /// Returns true if `player` was eliminated from the game.
+    pub fn is_eliminated(&self, player: G::PID) -> bool {
+        self.eliminated.iter().any(|group| group.contains(&player))
+    }
+
+    /// Returns true if `player` has to move this turn.
+    pub fn is_active(&self, player: G::PID) -> bool {
+        self.active_players.contains(&player)
+    }
+
+    /// Returns the active players who haven't submitted a move yet, in seat order.
+    /// Empty once the game is over.
+    pub fn pending_players(&self) -> Vec<G::PID> {
+        if self.is_over() {
+            return Vec::new();
+        }
+        self.players
+            .iter()
+            .copied()
+            .filter(|pid| self.active_players.contains(pid) && !self.moves.contains_key(pid))
+            .collect()
+    }
+
+    /// Returns the moves submitted so far this turn.
+    pub fn submitted_moves(&self) -> &HashMap<G::PID, G::Move> {
+        &self.moves
+    }
+
+    /// Returns the state as `player` sees it.
+    pub fn masked_view(&self, player: G::PID) -> G::MaskedState {
+        self.game.mask_state(&self.state, player)
+    }
+
+    /// Submits `player`'s move for this turn, replacing any move they submitted before.
+    ///
+    /// # Errors
+    /// Returns `GameError::WrongPlayer` if `player` doesn't have to move this turn, and
+    /// `GameError::IllegalState` if the game is over.
+    pub fn submit_move(
+        &mut self,
+        player: G::PID,
+        player_move: G::Move,
+    ) -> Result<(), GameError<G::PID>> {
+        if self.is_over() {
+            return Err(GameError::IllegalState("the game is over".to_string()));
+        }
+        if !self.is_active(player) {
+            return Err(GameError::WrongPlayer {
+                expected: self.active_players.clone(),
+                got: player,
+            });
+        }
+        self.moves.insert(player, player_move);
+        Ok(())
+    }
+
+    /// Applies the submitted moves, ending the turn.
+    ///
+    /// # Errors
+    /// Returns `GameError::MissingMoves` if some active players haven't submitted a move, and
+    /// `GameError::IllegalState` if the game is over. These leave the session as it was.
+    ///
+    /// Errors from `GameLogic::apply_moves` end the game, since the state may have been
+    /// partially updated; `outcome` then holds the error.
+    pub fn step(&mut self) -> Result<MoveResult<G::PID>, GameError<G::PID>> {
+        self.step_with(None)
+    }
+
+    /// Applies the submitted moves like `step`, but undoes the turn if `GameLogic::apply_moves`
+    /// fails: the state and the submitted moves are restored, and the game goes on. Moves can then
+    /// be replaced and the turn applied again.
+    ///
+    /// # Errors
+    /// Returns the same errors as `step`, none of which end the game.
+    pub fn try_step(&mut self) -> Result<MoveResult<G::PID>, GameError<G::PID>>
+    where
+        G::State: Clone,
+        G::Move: Clone,
+    {
+        self.step_with(Some(&Rollback::new()))
+    }
+
+    /// Applies the submitted moves, undoing the turn with `rollback` if applying them fails.
+    pub(crate) fn step_with(
+        &mut self,
+        rollback: Option<&Rollback<G>>,
+    ) -> Result<MoveResult<G::PID>, GameError<G::PID>> {
+        if self.is_over() {
+            return Err(GameError::IllegalState("the game is over".to_string()));
+        }
+        if !self.pending_players().is_empty() {
+            return Err(GameError::MissingMoves {
+                expected: self.active_players.clone(),
+                got: self.moves.keys().copied().collect(),
+            });
+        }
+
+        let snapshot = rollback.map(|rollback| rollback.snapshot(&self.state, &self.moves));
+        let moves = std::mem::take(&mut self.moves);
+        let result = self.game.apply_moves(&mut self.state, moves);
+        if let (Err(_), Some((state, moves))) = (&result, snapshot) {
+            self.state = state;
+            self.moves = moves;
+            return result;
+        }
+        self.turn += 1;
+
+        match &result {
+            Ok(MoveResult::Continue(players)) => self.active_players = players.clone(),
+            Ok(MoveResult::Eliminated { eliminated, active }) => {
+                self.eliminated.push(eliminated.clone());
+                self.active_players = active.clone();
+            }
+            Ok(MoveResult::GameOver(scores)) => {
+                let outcome = Outcome::from_scores(scores.clone(), &self.remaining_players());
+                self.end_with(outcome);
+            }
+            Ok(MoveResult::Finished(outcome)) => self.end_with(outcome.clone()),
+            Err(error) => {
+                self.active_players.clear();
+                self.outcome = Some(Err(SimulationError::GameError(error.clone())));
+            }
+        }
+        result
+    }
+
+    /// Ends the game early with the given result, e.g. when a player forfeits or disconnects.
+    /// Does nothing if the game is already over.
+    pub fn finish(&mut self, result: GameResult<G::PID>) {
+        if !self.is_over() {
+            match result {
+                Ok(scores) => {
+                    let outcome = Outcome::from_scores(scores, &self.remaining_players());
+                    self.end_with(outcome);
+                }
+                Err(error) => {
+                    self.active_players.clear();
+                    self.moves.clear();
+                    self.outcome = Some(Err(error));
+                }
+            }
+        }
+    }
+
+    /// Ends the game early with the given outcome, e.g. `Outcome::forfeit` when a player resigns.
+    /// Players eliminated earlier are ranked below everyone else, if the outcome doesn't rank
+    /// them. Does nothing if the game is already over.
+    pub fn finish_with(&mut self, outcome: Outcome<G::PID>) {
+        if !self.is_over() {
+            self.end_with(outcome);
+        }
+    }
+
+    /// Ends the game with the given outcome, even if it already ended with an error, e.g. when
+    /// the player whose move was rejected forfeits.
+    pub(crate) fn end_with(&mut self, outcome: Outcome<G::PID>) {
+        let outcome = outcome.rank_eliminated(&self.eliminated);
+        self.active_players.clear();
+        self.moves.clear();
+        self.outcome = Some(Ok(outcome.scores.clone()));
+        self.final_outcome = Some(outcome);
+    }
+
+    /// Returns the players who weren't eliminated, in seat order.
+    pub fn remaining_players(&self) -> Vec<G::PID> {
+        self.players
+            .iter()
+            .copied()
+            .filter(|&pid| !self.is_eliminated(pid))
+            .collect()
+    }
+
+    /// Returns true once the game is over.
+    pub fn is_over(&self) -> bool {
+        self.outcome.is_some()
+    }
+
+    /// Returns how the game ended, or `None` while it is in progress.
+    pub fn outcome(&self) -> Option<&GameResult<G::PID>> {
+        self.outcome.as_ref()
+    }
+
+    /// Returns the rankings, scores and termination reason of a game that ended with scores.
+    /// `None` while the game is in progress, or if it ended with an error.
+    pub fn final_outcome(&self) -> Option<&Outcome<G::PID>> {
+        self.final_outcome.as_ref()
+    }
+
+    /// Consumes the session, returning the final state and the outcome.
+    pub fn into_parts(self) -> (G::State, Option<GameResult<G::PID>>) {
+        (self.state, self.outcome)
+    }
+}
@@ -0,0 +1,159 @@
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    future::poll_fn,
+    panic::{catch_unwind, AssertUnwindSafe},
+    task::Poll,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    core::{derive_seed, AgentFuture, AsyncAgent, GameLogic, Id},
+    simulation::{
+        async_engine::{catch_unwind_async, simulate_async},
+        GameSummary, Observer, SimulationConfig, SimulationError,
+    },
+};
+
+use super::manager::{create_agents, take_logs, AgentFactory, IdGenerator, TournamentConfig};
+use super::matchmaker::{self, MatchMakerOutput, Matchup};
+use super::observer::{GameObserver, TournamentObserver};
+use super::report::{GameReport, TournamentReport};
+
+/// What a finished game produced: its summary and the logs of its agents.
+type Played<PID> = (GameSummary<PID>, HashMap<PID, String>);
+
+/// A game being played, with what is needed to report it once it is done.
+struct RunningGame<'a, PID: Id, GID> {
+    sequence: usize,
+    game_id: GID,
+    players: Matchup<PID>,
+    played: AgentFuture<'a, Played<PID>>,
+}
+
+/// Hosts a tournament like [`host_tournament_with_observers`](super::host_tournament_with_observers),
+/// with async agents.
+///
+/// Up to `TournamentConfig::max_concurrent_games` games are played concurrently within the
+/// returned future, which doesn't spawn tasks or threads. It runs on any executor, or on
+/// [`block_on`](crate::simulation::block_on). Games are reported to the matchmaker in the
+/// order they were scheduled, seeded and isolated from panics the same way as with sync agents.
+///
+/// # Returns
+/// A `TournamentReport` holding the matchmaker's result along with a report of every game.
+pub async fn host_tournament_async<G, AF, GG, M>(
+    game: &G,
+    agent_factories: HashMap<G::PID, AF>,
+    matchmaker: &mut M,
+    game_id_generator: &mut GG,
+    config: &TournamentConfig<G>,
+    observers: &[&dyn TournamentObserver<G, GG::Id>],
+) -> TournamentReport<G::PID, GG::Id>
+where
+    G: GameLogic,
+    AF: AgentFactory,
+    AF::Agent: AsyncAgent<Game = G>,
+    GG: IdGenerator,
+    M: matchmaker::MatchMaker<PID = G::PID, GID = GG::Id>,
+{
+    let max_running = config.worker_count();
+    let mut queue: VecDeque<Matchup<G::PID>> = matchmaker.initial_games().into();
+    let mut running: Vec<RunningGame<'_, G::PID, GG::Id>> = Vec::new();
+    let mut next_sequence = 0;
+    let mut next_to_digest = 0;
+    let mut finished = BTreeMap::new();
+    let mut games = Vec::new();
+
+    // Main loop: start queued games, wait for one to finish, run matchmaker, schedule or finish
+    loop {
+        while running.len() < max_running {
+            let Some(players) = queue.pop_front() else {
+                break;
+            };
+            let seed = config
+                .seed
+                .map(|seed| derive_seed(seed, next_sequence as u64));
+            let agents = create_agents(&agent_factories, &players, seed);
+            let game_id = game_id_generator.generate_id();
+            let mut observer = GameObserver { observers, game_id };
+            let simulation = SimulationConfig {
+                seed: seed.or(config.simulation.seed),
+                ..config.simulation.clone()
+            };
+
+            let play: AgentFuture<'_, Played<G::PID>> = match agents {
+                Ok(mut agents) => Box::pin(async move {
+                    let summary =
+                        simulate_async(game, &mut agents, &simulation, &mut observer).await;
+                    let logs = take_logs(&mut agents, AsyncAgent::take_log);
+                    // A panicking destructor must not take the tournament down with it
+                    let _ = catch_unwind(AssertUnwindSafe(|| drop(agents)));
+                    (summary, logs)
+                }),
+                Err(error) => Box::pin(async move {
+                    observer.on_error(&error);
+                    let summary = GameSummary {
+                        result: Err(error),
+                        outcome: None,
+                        turns: 0,
+                        duration: Duration::ZERO,
+                    };
+                    (summary, HashMap::new())
+                }),
+            };
+            // Agents and the game logic are isolated already, so only observers panic here
+            let start = Instant::now();
+            let played: AgentFuture<'_, Played<G::PID>> = Box::pin(async move {
+                catch_unwind_async(move || play)
+                    .await
+                    .unwrap_or_else(|message| {
+                        let error = SimulationError::ObserverPanicked(message);
+                        let summary = GameSummary::new(Err(error), 0, start.elapsed());
+                        (summary, HashMap::new())
+                    })
+            });
+            running.push(RunningGame {
+                sequence: next_sequence,
+                game_id,
+                players,
+                played,
+            });
+            next_sequence += 1;
+        }
+
+        assert!(
+            !running.is_empty(),
+            "the matchmaker stopped scheduling games without finishing the tournament"
+        );
+        let (index, (summary, logs)) = poll_fn(|context| {
+            for (index, game) in running.iter_mut().enumerate() {
+                if let Poll::Ready(played) = game.played.as_mut().poll(context) {
+                    return Poll::Ready((index, played));
+                }
+            }
+            Poll::Pending
+        })
+        .await;
+        let done = running.swap_remove(index);
+        let report = GameReport {
+            game_id: done.game_id,
+            players: done.players,
+            result: summary.result,
+            outcome: summary.outcome,
+            turns: summary.turns,
+            duration: summary.duration,
+            logs,
+        };
+        finished.insert(done.sequence, report);
+
+        while let Some(report) = finished.remove(&next_to_digest) {
+            next_to_digest += 1;
+            let output = matchmaker.digest_result(&report);
+            games.push(report);
+
+            match output {
+                MatchMakerOutput::Done(result) => return TournamentReport { result, games },
+                MatchMakerOutput::Continue(next_matchups) => queue.extend(next_matchups),
+            }
+        }
+    }
+}
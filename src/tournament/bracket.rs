@@ -0,0 +1,475 @@
+use std::cmp::Ordering;
+
+use crate::core::Id;
+use crate::simulation::GameResult;
+
+use super::manager::TournamentResult;
+use super::matchmaker::{unique_players, MatchMaker, MatchMakerOutput, Matchup};
+use super::report::GameReport;
+
+/// How a match is decided when both players share a place in the game's outcome,
+/// or when a game fails without either player being responsible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreaker {
+    /// The better seeded player advances.
+    HigherSeed,
+    /// The game is played again, up to `max_replays` times, before falling back to `HigherSeed`.
+    Replay { max_replays: usize },
+}
+
+/// The part of the bracket a match belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    /// The main bracket. This is the only section of a single-elimination bracket.
+    Winners,
+    /// The bracket for players with one loss in double elimination.
+    Losers,
+    /// The match between the winners and losers bracket champions.
+    GrandFinal,
+    /// The second grand final, played only if the losers bracket champion wins the first.
+    GrandFinalReset,
+}
+
+/// Where a player slot of a match gets its player from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feed {
+    /// The player with the given seed. Seeds past the number of players are byes.
+    Seed(usize),
+    /// The winner of the match at the given index.
+    Winner(usize),
+    /// The loser of the match at the given index.
+    Loser(usize),
+}
+
+/// The progress of a single bracket match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchStatus {
+    /// At least one slot is still waiting for an earlier match.
+    Waiting,
+    /// Both players are known and a game has been scheduled.
+    InProgress,
+    /// The match was decided by playing.
+    Finished,
+    /// Only one player arrived, who advances without playing.
+    Walkover,
+    /// No player arrived and the match is skipped.
+    Void,
+}
+
+/// A node of the bracket tree, fed by seeds or by the results of earlier matches.
+#[derive(Debug, Clone)]
+pub struct BracketMatch<PID: Id, GID: Id> {
+    /// The section of the bracket this match is in.
+    pub section: Section,
+    /// The round within the section, starting from 1.
+    pub round: usize,
+    /// Where each of the two slots gets its player from.
+    pub feeds: [Feed; 2],
+    /// The players in each slot, once known.
+    pub players: [Option<PID>; 2],
+    /// Every game played for this match, including replays.
+    pub games: Vec<(GID, GameResult<PID>)>,
+    /// The player advancing from this match.
+    pub winner: Option<PID>,
+    /// The player eliminated (or dropped to the losers bracket) by this match.
+    pub loser: Option<PID>,
+    /// The current progress of the match.
+    pub status: MatchStatus,
+}
+
+impl<PID: Id, GID: Id> BracketMatch<PID, GID> {
+    fn new(section: Section, round: usize, feeds: [Feed; 2]) -> Self {
+        BracketMatch {
+            section,
+            round,
+            feeds,
+            players: [None, None],
+            games: Vec::new(),
+            winner: None,
+            loser: None,
+            status: MatchStatus::Waiting,
+        }
+    }
+
+    fn is_resolved(&self) -> bool {
+        matches!(
+            self.status,
+            MatchStatus::Finished | MatchStatus::Walkover | MatchStatus::Void
+        )
+    }
+}
+
+/// A single- or double-elimination bracket matchmaker for two-player games.
+///
+/// The bracket is stored as a list of matches, each fed by seeds or by the winner or loser
+/// of earlier matches. The matchmaker is borrowed by `host_tournament`, so the full bracket
+/// can be inspected through [`Bracket::matches`] once the tournament returns.
+///
+/// A match is won by the player ranked higher in the game's outcome, and a player responsible
+/// for a failed game loses the match. The tournament result counts the matches each player won
+/// by playing (walkovers excluded).
+pub struct Bracket<PID: Id, GID: Id> {
+    seeds: Vec<PID>,
+    matches: Vec<BracketMatch<PID, GID>>,
+    tie_breaker: TieBreaker,
+    initial_games: Vec<Matchup<PID>>,
+}
+
+impl<PID: Id, GID: Id> Bracket<PID, GID> {
+    /// Creates a single-elimination bracket.
+    ///
+    /// # Arguments
+    /// * `seeds` - The participating players, best seed first. Duplicates are ignored.
+    ///   If the number of players isn't a power of two, the top seeds receive byes.
+    /// * `tie_breaker` - How drawn games are resolved.
+    ///
+    /// # Panics
+    /// Panics if there are fewer than two players.
+    pub fn single_elimination(
+        seeds: impl IntoIterator<Item = PID>,
+        tie_breaker: TieBreaker,
+    ) -> Self {
+        let seeds = unique_players(seeds);
+        assert!(seeds.len() >= 2, "a bracket needs at least two players");
+
+        let mut matches = Vec::new();
+        winners_bracket(seeds.len().next_power_of_two(), &mut matches);
+        Self::from_matches(seeds, matches, tie_breaker)
+    }
+
+    /// Creates a double-elimination bracket, in which a player is out after losing two matches.
+    ///
+    /// Losers of the winners bracket drop into the losers bracket, and the two bracket
+    /// champions meet in a grand final. If the losers bracket champion wins it, the grand
+    /// final is replayed, since both players then have one loss.
+    ///
+    /// # Arguments
+    /// * `seeds` - The participating players, best seed first. Duplicates are ignored.
+    /// * `tie_breaker` - How drawn games are resolved.
+    ///
+    /// # Panics
+    /// Panics if there are fewer than two players.
+    pub fn double_elimination(
+        seeds: impl IntoIterator<Item = PID>,
+        tie_breaker: TieBreaker,
+    ) -> Self {
+        let seeds = unique_players(seeds);
+        assert!(seeds.len() >= 2, "a bracket needs at least two players");
+
+        let size = seeds.len().next_power_of_two();
+        let mut matches = Vec::new();
+        let winners_rounds = winners_bracket(size, &mut matches);
+
+        // Round 1 of the losers bracket pairs up the losers of the first winners round.
+        // Every following winners round drops its losers in against the surviving losers,
+        // and between drop-ins the losers bracket halves itself.
+        let mut losers_champion = Feed::Loser(winners_rounds[0][0]);
+        if winners_rounds.len() > 1 {
+            let mut previous: Vec<usize> = winners_rounds[0]
+                .chunks(2)
+                .map(|pair| {
+                    push_match(
+                        &mut matches,
+                        Section::Losers,
+                        1,
+                        [Feed::Loser(pair[0]), Feed::Loser(pair[1])],
+                    )
+                })
+                .collect();
+            let mut round = 1;
+
+            for (i, dropping) in winners_rounds.iter().enumerate().skip(1) {
+                round += 1;
+                // Dropping losers in reverse order keeps early rematches apart
+                previous = previous
+                    .iter()
+                    .zip(dropping.iter().rev())
+                    .map(|(&survivor, &dropped)| {
+                        push_match(
+                            &mut matches,
+                            Section::Losers,
+                            round,
+                            [Feed::Winner(survivor), Feed::Loser(dropped)],
+                        )
+                    })
+                    .collect();
+
+                if i + 1 < winners_rounds.len() {
+                    round += 1;
+                    previous = previous
+                        .chunks(2)
+                        .map(|pair| {
+                            push_match(
+                                &mut matches,
+                                Section::Losers,
+                                round,
+                                [Feed::Winner(pair[0]), Feed::Winner(pair[1])],
+                            )
+                        })
+                        .collect();
+                }
+            }
+            losers_champion = Feed::Winner(previous[0]);
+        }
+
+        let winners_final = *winners_rounds
+            .last()
+            .and_then(|round| round.first())
+            .unwrap();
+        let grand_final = push_match(
+            &mut matches,
+            Section::GrandFinal,
+            1,
+            [Feed::Winner(winners_final), losers_champion],
+        );
+        push_match(
+            &mut matches,
+            Section::GrandFinalReset,
+            1,
+            [Feed::Winner(grand_final), Feed::Loser(grand_final)],
+        );
+
+        Self::from_matches(seeds, matches, tie_breaker)
+    }
+
+    fn from_matches(
+        seeds: Vec<PID>,
+        matches: Vec<BracketMatch<PID, GID>>,
+        tie_breaker: TieBreaker,
+    ) -> Self {
+        let mut bracket = Bracket {
+            seeds,
+            matches,
+            tie_breaker,
+            initial_games: Vec::new(),
+        };
+        bracket.initial_games = bracket.advance();
+        bracket
+    }
+
+    /// Returns every match of the bracket. Matches only depend on matches before them.
+    pub fn matches(&self) -> &[BracketMatch<PID, GID>] {
+        &self.matches
+    }
+
+    /// Returns the players in seed order.
+    pub fn seeds(&self) -> &[PID] {
+        &self.seeds
+    }
+
+    /// Returns the tournament winner, once the bracket is complete.
+    pub fn champion(&self) -> Option<PID> {
+        let last = self.matches.last()?;
+        match (last.section, last.status) {
+            (Section::GrandFinalReset, MatchStatus::Void) => {
+                self.matches[self.matches.len() - 2].winner
+            }
+            (_, MatchStatus::Finished | MatchStatus::Walkover) => last.winner,
+            _ => None,
+        }
+    }
+
+    /// Returns whether every match of the bracket has been resolved.
+    pub fn is_complete(&self) -> bool {
+        self.matches.iter().all(BracketMatch::is_resolved)
+    }
+
+    /// Returns the number of matches each player won by playing.
+    pub fn match_wins(&self) -> TournamentResult<PID> {
+        let mut wins: TournamentResult<PID> = self.seeds.iter().map(|&pid| (pid, 0)).collect();
+        for bracket_match in &self.matches {
+            if let (MatchStatus::Finished, Some(winner)) =
+                (bracket_match.status, bracket_match.winner)
+            {
+                *wins.entry(winner).or_insert(0) += 1;
+            }
+        }
+        wins
+    }
+
+    fn seed_of(&self, pid: PID) -> usize {
+        self.seeds
+            .iter()
+            .position(|&seed| seed == pid)
+            .unwrap_or(usize::MAX)
+    }
+
+    fn feed_player(&self, feed: Feed) -> Option<Option<PID>> {
+        match feed {
+            Feed::Seed(seed) => Some(self.seeds.get(seed).copied()),
+            Feed::Winner(index) => self.matches[index]
+                .is_resolved()
+                .then_some(self.matches[index].winner),
+            Feed::Loser(index) => self.matches[index]
+                .is_resolved()
+                .then_some(self.matches[index].loser),
+        }
+    }
+
+    /// Resolves every match whose feeds are known, returning the games that can now be played.
+    fn advance(&mut self) -> Vec<Matchup<PID>> {
+        let mut scheduled = Vec::new();
+
+        // Feeds always point backwards, so a single forward pass resolves everything possible
+        for index in 0..self.matches.len() {
+            if self.matches[index].status != MatchStatus::Waiting {
+                continue;
+            }
+            let (Some(first), Some(second)) = (
+                self.feed_player(self.matches[index].feeds[0]),
+                self.feed_player(self.matches[index].feeds[1]),
+            ) else {
+                continue;
+            };
+
+            let reset_unneeded = self.matches[index].section == Section::GrandFinalReset
+                && self.matches[index - 1].winner == self.matches[index - 1].players[0];
+
+            let bracket_match = &mut self.matches[index];
+            bracket_match.players = [first, second];
+            match (first, second) {
+                _ if reset_unneeded => bracket_match.status = MatchStatus::Void,
+                (Some(a), Some(b)) => {
+                    bracket_match.status = MatchStatus::InProgress;
+                    scheduled.push(vec![a, b]);
+                }
+                (Some(pid), None) | (None, Some(pid)) => {
+                    bracket_match.status = MatchStatus::Walkover;
+                    bracket_match.winner = Some(pid);
+                }
+                (None, None) => bracket_match.status = MatchStatus::Void,
+            }
+        }
+
+        scheduled
+    }
+}
+
+impl<PID: Id, GID: Id> MatchMaker for Bracket<PID, GID> {
+    type PID = PID;
+    type GID = GID;
+
+    fn initial_games(&self) -> Vec<Matchup<PID>> {
+        self.initial_games.clone()
+    }
+
+    fn digest_result(&mut self, report: &GameReport<PID, GID>) -> MatchMakerOutput<PID> {
+        let Some(index) = self.matches.iter().position(|bracket_match| {
+            bracket_match.status == MatchStatus::InProgress
+                && report
+                    .players
+                    .iter()
+                    .all(|pid| bracket_match.players.contains(&Some(*pid)))
+        }) else {
+            return MatchMakerOutput::Continue(Vec::new());
+        };
+
+        let [Some(a), Some(b)] = self.matches[index].players else {
+            unreachable!("matches in progress always have two players");
+        };
+        let comparison = match (&report.result, &report.outcome) {
+            // A better rank is a lower one
+            (Ok(_), Some(outcome)) => {
+                let rank = |pid| outcome.rank_of(pid).unwrap_or(usize::MAX);
+                rank(b).cmp(&rank(a))
+            }
+            (Ok(scores), None) => {
+                let score = |pid| scores.get(&pid).copied().unwrap_or(0);
+                score(a).cmp(&score(b))
+            }
+            (Err(error), _) => match error.culprit() {
+                Some(culprit) if culprit == a => Ordering::Less,
+                Some(culprit) if culprit == b => Ordering::Greater,
+                _ => Ordering::Equal,
+            },
+        };
+        self.matches[index]
+            .games
+            .push((report.game_id, report.result.clone()));
+
+        let a_wins = match comparison {
+            Ordering::Greater => true,
+            Ordering::Less => false,
+            Ordering::Equal => match self.tie_breaker {
+                TieBreaker::Replay { max_replays }
+                    if self.matches[index].games.len() <= max_replays =>
+                {
+                    return MatchMakerOutput::Continue(vec![vec![a, b]]);
+                }
+                _ => self.seed_of(a) < self.seed_of(b),
+            },
+        };
+
+        let bracket_match = &mut self.matches[index];
+        let (winner, loser) = if a_wins { (a, b) } else { (b, a) };
+        bracket_match.winner = Some(winner);
+        bracket_match.loser = Some(loser);
+        bracket_match.status = MatchStatus::Finished;
+
+        let next_games = self.advance();
+        if self.is_complete() {
+            MatchMakerOutput::Done(self.match_wins())
+        } else {
+            MatchMakerOutput::Continue(next_games)
+        }
+    }
+}
+
+fn push_match<PID: Id, GID: Id>(
+    matches: &mut Vec<BracketMatch<PID, GID>>,
+    section: Section,
+    round: usize,
+    feeds: [Feed; 2],
+) -> usize {
+    matches.push(BracketMatch::new(section, round, feeds));
+    matches.len() - 1
+}
+
+/// Builds a standard seeded winners bracket for `size` slots (a power of two).
+/// Returns the match indices of every round, first round first.
+fn winners_bracket<PID: Id, GID: Id>(
+    size: usize,
+    matches: &mut Vec<BracketMatch<PID, GID>>,
+) -> Vec<Vec<usize>> {
+    // Seed positions so that the top seeds can only meet in later rounds: 1v8, 4v5, 2v7, 3v6
+    let mut order = vec![0];
+    while order.len() < size {
+        let slots = order.len() * 2;
+        order = order
+            .iter()
+            .flat_map(|&seed| [seed, slots - 1 - seed])
+            .collect();
+    }
+
+    let mut rounds = vec![order
+        .chunks(2)
+        .map(|pair| {
+            push_match(
+                matches,
+                Section::Winners,
+                1,
+                [Feed::Seed(pair[0]), Feed::Seed(pair[1])],
+            )
+        })
+        .collect::<Vec<_>>()];
+
+    while rounds.last().unwrap().len() > 1 {
+        let round = rounds.len() + 1;
+        let next = rounds
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| {
+                push_match(
+                    matches,
+                    Section::Winners,
+                    round,
+                    [Feed::Winner(pair[0]), Feed::Winner(pair[1])],
+                )
+            })
+            .collect();
+        rounds.push(next);
+    }
+
+    rounds
+}
@@ -1,21 +1,40 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    panic::{catch_unwind, AssertUnwindSafe},
+    time::{Duration, Instant},
+};
 
 use indexmap::IndexMap;
 
 use crate::{
-    simulation::simulate_game,
-    core::{Agent, FinalScores, GameLogic, Id},
+    simulation::{
+        engine::{catch_agent, panic_message, simulate},
+        GameSummary, Observer, SimulationConfig, SimulationError,
+    },
+    core::{derive_seed, Agent, GameLogic, Id},
 };
 
-use super::matchmaker::{self, MatchMakerOutput};
+use super::matchmaker::{self, MatchMakerOutput, Matchup};
+use super::observer::{GameObserver, TournamentObserver};
+use super::report::{GameReport, TournamentReport};
 
 pub type TournamentResult<PID> = HashMap<PID, i32>;
 
-/// Factory trait for creating agents.
+/// Factory trait for creating agents, either `Agent`s or `AsyncAgent`s.
 /// For heterogeneous agent support, implement with `type Agent = Box<dyn Agent<Game = G> + Send>`.
 pub trait AgentFactory {
-    type Agent: Agent;
+    /// The agents created. Not bound to `Agent`, so that the same factories serve
+    /// [`host_tournament`], which requires `Agent`s, and
+    /// [`host_tournament_async`](super::host_tournament_async), which requires `AsyncAgent`s.
+    type Agent;
     fn create_agent(&self) -> Self::Agent;
+
+    /// Creates an agent whose randomness is fully determined by `seed`.
+    /// Called instead of `create_agent` when a tournament is seeded. Defaults to `create_agent`,
+    /// which is fine for deterministic agents.
+    fn create_agent_seeded(&self, _seed: u64) -> Self::Agent {
+        self.create_agent()
+    }
 }
 
 pub trait IdGenerator {
@@ -23,6 +42,115 @@ pub trait IdGenerator {
     fn generate_id(&mut self) -> Self::Id;
 }
 
+/// Options controlling how a tournament is hosted.
+pub struct TournamentConfig<G: GameLogic> {
+    /// The options every game of the tournament is simulated with.
+    pub simulation: SimulationConfig<G>,
+    /// The maximum number of games played at the same time, each on its own worker thread,
+    /// or as concurrent futures when hosted with `host_tournament_async`. Defaults to the
+    /// available parallelism of the machine. Matchups beyond the limit are queued and start as
+    /// workers free up.
+    pub max_concurrent_games: Option<usize>,
+    /// Master seed of the tournament. Every game gets a seed derived from it and the order
+    /// the game was scheduled in, and every agent a seed derived from its game's seed and
+    /// its seat. Overrides `simulation.seed`.
+    pub seed: Option<u64>,
+}
+
+impl<G: GameLogic> Default for TournamentConfig<G> {
+    fn default() -> Self {
+        TournamentConfig {
+            simulation: SimulationConfig::default(),
+            max_concurrent_games: None,
+            seed: None,
+        }
+    }
+}
+
+impl<G: GameLogic> Clone for TournamentConfig<G> {
+    fn clone(&self) -> Self {
+        TournamentConfig {
+            simulation: self.simulation.clone(),
+            max_concurrent_games: self.max_concurrent_games,
+            seed: self.seed,
+        }
+    }
+}
+
+impl<G: GameLogic> std::fmt::Debug for TournamentConfig<G> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TournamentConfig")
+            .field("simulation", &self.simulation)
+            .field("max_concurrent_games", &self.max_concurrent_games)
+            .field("seed", &self.seed)
+            .finish()
+    }
+}
+
+impl<G: GameLogic> TournamentConfig<G> {
+    pub(crate) fn worker_count(&self) -> usize {
+        self.max_concurrent_games
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()))
+            .max(1)
+    }
+}
+
+/// Creates the agents of a game, seated in matchup order, which is the order `GameLogic::init`
+/// sees. With a game seed, each agent gets a seed derived from it and the agent's seat.
+///
+/// # Errors
+/// Returns `SimulationError::AgentPanicked` if a factory panics.
+pub(crate) fn create_agents<PID: Id, AF: AgentFactory>(
+    agent_factories: &HashMap<PID, AF>,
+    players: &[PID],
+    seed: Option<u64>,
+) -> Result<IndexMap<PID, AF::Agent>, SimulationError<PID>> {
+    players
+        .iter()
+        .enumerate()
+        .filter_map(|(seat, pid)| {
+            agent_factories
+                .get(pid)
+                .map(|factory| (seat, *pid, factory))
+        })
+        .map(|(seat, pid, factory)| {
+            catch_agent(pid, || match seed {
+                Some(seed) => factory.create_agent_seeded(derive_seed(seed, seat as u64)),
+                None => factory.create_agent(),
+            })
+            .map(|agent| (pid, agent))
+        })
+        .collect()
+}
+
+/// Takes the logs of `agents` with `take`, leaving out those that logged nothing or panicked.
+pub(crate) fn take_logs<PID: Id, A>(
+    agents: &mut IndexMap<PID, A>,
+    take: impl Fn(&mut A) -> Option<String>,
+) -> HashMap<PID, String> {
+    agents
+        .iter_mut()
+        .filter_map(|(&pid, agent)| {
+            catch_agent(pid, || take(agent))
+                .ok()
+                .flatten()
+                .map(|log| (pid, log))
+        })
+        .collect()
+}
+
+/// Hosts a tournament, running the games the matchmaker schedules on a pool of worker threads.
+///
+/// # Arguments
+/// * `game` - The game logic every game is played with.
+/// * `agent_factories` - A factory for each participating player.
+/// * `matchmaker` - Decides which games are played and produces the final result.
+/// * `game_id_generator` - Generates an ID for every game.
+/// * `max_turns` - Optional maximum number of turns per game.
+///
+/// # Returns
+/// The tournament result produced by the matchmaker. Use [`host_tournament_with`] for a
+/// full report of every game, or to seed the tournament with `TournamentConfig::seed`.
 pub fn host_tournament<G, AF, GG, M>(
     game: &G,
     agent_factories: HashMap<G::PID, AF>,
@@ -39,50 +167,206 @@ where
     GG::Id: Send,
     M: matchmaker::MatchMaker<PID = G::PID, GID = GG::Id>,
 {
-    // Channel carries raw game results back from worker threads.
-    // digest_result is called on the main thread only.
-    let (sender, receiver) = std::sync::mpsc::channel::<(GG::Id, FinalScores<G::PID>)>();
-
-    crossbeam::thread::scope(|scope| {
-        // Captures scope, game_id_generator, sender, agent_factories, game, max_turns.
-        // Does not capture matchmaker or receiver -- those are used freely in the loop below.
-        let mut spawn_game = |players: &HashSet<G::PID>| {
-            let game_id = game_id_generator.generate_id();
-            let thread_sender = sender.clone();
-            let mut agents: IndexMap<G::PID, AF::Agent> = agent_factories
-                .iter()
-                .filter(|(pid, _)| players.contains(pid))
-                .map(|(pid, factory)| (pid.clone(), factory.create_agent()))
-                .collect();
+    let config = TournamentConfig {
+        simulation: SimulationConfig {
+            max_turns,
+            ..SimulationConfig::default()
+        },
+        ..TournamentConfig::default()
+    };
+    host_tournament_with(
+        game,
+        agent_factories,
+        matchmaker,
+        game_id_generator,
+        &config,
+    )
+    .result
+}
+
+/// Hosts a tournament like [`host_tournament`], with full configuration.
+///
+/// Results are handed to the matchmaker in the order the games were scheduled, no matter
+/// which game finishes first. A finished game waits for the games scheduled before it, so
+/// the matchmaker sees the same sequence of results -- and schedules the same games with
+/// the same IDs -- regardless of the number of workers. With `TournamentConfig::seed` set and
+/// seedable agents and games, rerunning a tournament reproduces every game and the result.
+///
+/// A panic in an agent factory, an agent, the game logic or an observer only ends the game it
+/// happened in. The game is reported as `SimulationError::AgentPanicked`,
+/// `SimulationError::GamePanicked` or `SimulationError::ObserverPanicked`, and the tournament
+/// continues. Observers aren't told about their own panics.
+///
+/// # Returns
+/// A `TournamentReport` holding the matchmaker's result along with a report of every game.
+pub fn host_tournament_with<G, AF, GG, M>(
+    game: &G,
+    agent_factories: HashMap<G::PID, AF>,
+    matchmaker: &mut M,
+    game_id_generator: &mut GG,
+    config: &TournamentConfig<G>,
+) -> TournamentReport<G::PID, GG::Id>
+where
+    G: GameLogic + Sync,
+    G::PID: Send + std::fmt::Debug,
+    AF: AgentFactory,
+    AF::Agent: Agent<Game = G> + Send,
+    GG: IdGenerator,
+    GG::Id: Send,
+    M: matchmaker::MatchMaker<PID = G::PID, GID = GG::Id>,
+{
+    host_tournament_with_observers(
+        game,
+        agent_factories,
+        matchmaker,
+        game_id_generator,
+        config,
+        &[],
+    )
+}
 
+/// Hosts a tournament like [`host_tournament_with`], reporting every step of every game to
+/// `observers`.
+///
+/// # Returns
+/// A `TournamentReport` holding the matchmaker's result along with a report of every game.
+pub fn host_tournament_with_observers<G, AF, GG, M>(
+    game: &G,
+    agent_factories: HashMap<G::PID, AF>,
+    matchmaker: &mut M,
+    game_id_generator: &mut GG,
+    config: &TournamentConfig<G>,
+    observers: &[&dyn TournamentObserver<G, GG::Id>],
+) -> TournamentReport<G::PID, GG::Id>
+where
+    G: GameLogic + Sync,
+    G::PID: Send + std::fmt::Debug,
+    AF: AgentFactory,
+    AF::Agent: Agent<Game = G> + Send,
+    GG: IdGenerator,
+    GG::Id: Send,
+    M: matchmaker::MatchMaker<PID = G::PID, GID = GG::Id>,
+{
+    // Jobs go out to the workers tagged with a sequence number, and results come back with it.
+    // digest_result is called on the main thread only, in sequence order.
+    // A job carries an error instead of agents if creating one of them panicked.
+    let (job_sender, job_receiver) = crossbeam::channel::unbounded::<(
+        usize,
+        GG::Id,
+        Matchup<G::PID>,
+        Option<u64>,
+        Result<IndexMap<G::PID, AF::Agent>, SimulationError<G::PID>>,
+    )>();
+    let (result_sender, result_receiver) =
+        crossbeam::channel::unbounded::<(usize, GameReport<G::PID, GG::Id>)>();
+    let workers = config.worker_count();
+    let mut games = Vec::new();
+
+    let result = crossbeam::thread::scope(|scope| {
+        for _ in 0..workers {
+            let job_receiver = job_receiver.clone();
+            let result_sender = result_sender.clone();
             scope.spawn(move |_| {
-                let game_result = match simulate_game(game, &mut agents, max_turns) {
-                    Ok(scores) => scores,
-                    Err(_) => FinalScores::new(),
-                };
-                thread_sender.send((game_id, game_result)).unwrap();
+                for (sequence, game_id, players, seed, agents) in job_receiver {
+                    let mut observer = GameObserver { observers, game_id };
+                    let start = Instant::now();
+                    // Agents and the game logic are isolated already, so only observers panic
+                    // here. The game is reported as failed rather than taking the worker down.
+                    let (summary, logs) = catch_unwind(AssertUnwindSafe(|| match agents {
+                        Ok(mut agents) => {
+                            let simulation = SimulationConfig {
+                                seed: seed.or(config.simulation.seed),
+                                ..config.simulation.clone()
+                            };
+                            let summary = simulate(game, &mut agents, &simulation, &mut observer);
+                            let logs = take_logs(&mut agents, Agent::take_log);
+                            // A panicking destructor must not take the worker down with it
+                            let _ = catch_unwind(AssertUnwindSafe(|| drop(agents)));
+                            (summary, logs)
+                        }
+                        Err(error) => {
+                            observer.on_error(&error);
+                            let summary = GameSummary {
+                                result: Err(error),
+                                outcome: None,
+                                turns: 0,
+                                duration: Duration::ZERO,
+                            };
+                            (summary, HashMap::new())
+                        }
+                    }))
+                    .unwrap_or_else(|payload| {
+                        let error = SimulationError::ObserverPanicked(panic_message(payload));
+                        let summary = GameSummary::new(Err(error), 0, start.elapsed());
+                        (summary, HashMap::new())
+                    });
+                    let report = GameReport {
+                        game_id,
+                        players,
+                        result: summary.result,
+                        outcome: summary.outcome,
+                        turns: summary.turns,
+                        duration: summary.duration,
+                        logs,
+                    };
+                    // The host stops listening once the tournament is done
+                    let _ = result_sender.send((sequence, report));
+                }
             });
-        };
-
-        for players in matchmaker.initial_games() {
-            spawn_game(&players);
         }
 
-        // Main loop: receive results, run matchmaker, schedule or finish
+        let mut queue: VecDeque<Matchup<G::PID>> = matchmaker.initial_games().into();
+        let mut in_flight = 0;
+        let mut next_sequence = 0;
+        let mut next_to_digest = 0;
+        let mut finished = BTreeMap::new();
+
+        // Main loop: start queued games, receive results, run matchmaker, schedule or finish
         loop {
-            let (game_id, game_result) = receiver.recv().unwrap();
+            while in_flight < workers {
+                let Some(players) = queue.pop_front() else {
+                    break;
+                };
+                let seed = config
+                    .seed
+                    .map(|seed| derive_seed(seed, next_sequence as u64));
+                // Agents are created only once a worker is free for them
+                let agents = create_agents(&agent_factories, &players, seed);
+                let game_id = game_id_generator.generate_id();
+                job_sender
+                    .send((next_sequence, game_id, players, seed, agents))
+                    .unwrap();
+                next_sequence += 1;
+                in_flight += 1;
+            }
 
-            match matchmaker.digest_result(game_id, game_result) {
-                MatchMakerOutput::Done(tournament_result) => {
-                    break tournament_result;
-                }
-                MatchMakerOutput::Continue(next_matchups) => {
-                    for players in next_matchups {
-                        spawn_game(&players);
+            assert!(
+                in_flight > 0,
+                "the matchmaker stopped scheduling games without finishing the tournament"
+            );
+            let (sequence, report) = result_receiver
+                .recv()
+                .expect("tournament workers stopped while games were in flight");
+            in_flight -= 1;
+            finished.insert(sequence, report);
+
+            while let Some(report) = finished.remove(&next_to_digest) {
+                next_to_digest += 1;
+                let output = matchmaker.digest_result(&report);
+                games.push(report);
+
+                match output {
+                    MatchMakerOutput::Done(tournament_result) => {
+                        // Dropping the job sender lets idle workers exit
+                        drop(job_sender);
+                        return tournament_result;
                     }
+                    MatchMakerOutput::Continue(next_matchups) => queue.extend(next_matchups),
                 }
             }
         }
     })
-    .unwrap()
+    .unwrap();
+
+    TournamentReport { result, games }
 }
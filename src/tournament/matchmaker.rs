@@ -1,15 +1,23 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
 
-use crate::core::{FinalScores, Id};
+use crate::core::{FinalScores, Id, Outcome};
+use crate::simulation::SimulationError;
 
 use super::manager::TournamentResult;
+use super::report::GameReport;
+
+/// The players seated at a single game, in seat order.
+/// The first player is passed to `GameLogic::init` first, so for games where seating
+/// matters (e.g. who moves first) the order is significant.
+pub type Matchup<PID> = Vec<PID>;
 
 /// The output of a matchmaker after processing a game result.
 /// Either more games to play, or the final tournament result.
 /// A matchmaker must return exactly one of these -- never a mix.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MatchMakerOutput<PID: Id> {
     /// The tournament continues with the given matchups.
-    Continue(Vec<HashSet<PID>>),
+    Continue(Vec<Matchup<PID>>),
     /// The tournament is over with the given final scores.
     Done(TournamentResult<PID>),
 }
@@ -19,15 +27,130 @@ pub trait MatchMaker {
     type GID: Id;
 
     /// Returns the initial set of matchups for the tournament.
-    fn initial_games(&self) -> Vec<HashSet<Self::PID>>;
+    fn initial_games(&self) -> Vec<Matchup<Self::PID>>;
 
     /// Processes the result of a completed game and returns either
     /// the next round of matchups or the final tournament result.
     /// Called sequentially on the tournament host thread -- safe to
     /// mutate internal state without synchronization.
+    ///
+    /// `report.players` is the matchup the game was played with, in seat order. Scores don't
+    /// necessarily list every player, so this is how a result is matched to its game.
+    ///
+    /// `report.result` is an error if the game didn't end normally, so that matchmakers can
+    /// decide how to treat failed games. `SimulationError::culprit` names the responsible
+    /// player when there is one. Otherwise `report.outcome` ranks the players and tells how
+    /// the game ended, e.g. by a forfeit.
     fn digest_result(
         &mut self,
-        game_id: Self::GID,
-        result: FinalScores<Self::PID>,
+        report: &GameReport<Self::PID, Self::GID>,
     ) -> MatchMakerOutput<Self::PID>;
 }
+
+/// How a matchmaker turns a game that ended normally into tournament points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Scoring {
+    /// Every player is credited their score in the game.
+    #[default]
+    Scores,
+    /// Players are credited by their place in the game's outcome: `win` for finishing first
+    /// alone, `draw` for sharing first place and `loss` otherwise.
+    Points { win: i32, draw: i32, loss: i32 },
+}
+
+impl Scoring {
+    /// Returns the points the players of a game that ended with `scores` are credited.
+    /// Games without an outcome are ranked from their scores.
+    pub(crate) fn award<PID: Id, GID: Id>(
+        &self,
+        report: &GameReport<PID, GID>,
+        scores: &FinalScores<PID>,
+    ) -> FinalScores<PID> {
+        let Scoring::Points { win, draw, loss } = *self else {
+            return scores.clone();
+        };
+
+        let outcome = report
+            .outcome
+            .clone()
+            .unwrap_or_else(|| Outcome::from_scores(scores.clone(), &report.players));
+        let winners = outcome.winners();
+
+        report
+            .players
+            .iter()
+            .map(|&pid| {
+                let points = match (winners.contains(&pid), winners.len()) {
+                    (true, 1) => win,
+                    (true, _) => draw,
+                    (false, _) => loss,
+                };
+                (pid, points)
+            })
+            .collect()
+    }
+}
+
+/// How a matchmaker treats games that ended with a `SimulationError`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FailurePolicy {
+    /// The game counts as if nobody scored.
+    #[default]
+    Ignore,
+    /// The player responsible for the failure is given `penalty` (usually negative) and
+    /// everyone else scores 0. Failures without a culprit are ignored.
+    Forfeit { penalty: i32 },
+    /// The game is played again with the same seating, up to `max_replays` times per
+    /// matchup. Once replays run out, the failure is ignored.
+    Replay { max_replays: usize },
+}
+
+/// What a matchmaker should do with a failed game after applying its `FailurePolicy`.
+pub(crate) enum Resolution<PID: Id> {
+    /// Count the game with these scores.
+    Scores(FinalScores<PID>),
+    /// Schedule the game again.
+    Replay,
+}
+
+impl FailurePolicy {
+    /// Applies the policy to a failed game. `replays` counts the replays used per matchup.
+    pub(crate) fn resolve<PID: Id>(
+        &self,
+        players: &[PID],
+        error: &SimulationError<PID>,
+        replays: &mut HashMap<Matchup<PID>, usize>,
+    ) -> Resolution<PID> {
+        match *self {
+            FailurePolicy::Ignore => Resolution::Scores(FinalScores::new()),
+            FailurePolicy::Forfeit { penalty } => Resolution::Scores(
+                error
+                    .culprit()
+                    .map(|culprit| FinalScores::from([(culprit, penalty)]))
+                    .unwrap_or_default(),
+            ),
+            FailurePolicy::Replay { max_replays } => {
+                let used = replays.entry(players.to_vec()).or_insert(0);
+                if *used < max_replays {
+                    *used += 1;
+                    Resolution::Replay
+                } else {
+                    Resolution::Scores(FinalScores::new())
+                }
+            }
+        }
+    }
+}
+
+/// Collects players into a list without duplicates, keeping the first occurrence of each.
+pub(crate) fn unique_players<PID: Id>(players: impl IntoIterator<Item = PID>) -> Vec<PID> {
+    let mut unique = Vec::new();
+    for pid in players {
+        if !unique.contains(&pid) {
+            unique.push(pid);
+        }
+    }
+    unique
+}
@@ -1,5 +1,22 @@
+pub mod async_manager;
+pub mod bracket;
 pub mod matchmaker;
 pub mod manager;
+pub mod observer;
+pub mod rating;
+pub mod report;
+pub mod round_robin;
+pub mod swiss;
 
-pub use manager::{host_tournament, AgentFactory, IdGenerator, TournamentResult};
-pub use matchmaker::{MatchMaker, MatchMakerOutput};
\ No newline at end of file
+pub use async_manager::host_tournament_async;
+pub use bracket::{Bracket, BracketMatch, Feed, MatchStatus, Section, TieBreaker};
+pub use manager::{
+    host_tournament, host_tournament_with, host_tournament_with_observers, AgentFactory,
+    IdGenerator, TournamentConfig, TournamentResult,
+};
+pub use matchmaker::{FailurePolicy, MatchMaker, MatchMakerOutput, Matchup, Scoring};
+pub use observer::TournamentObserver;
+pub use rating::{Elo, Glicko2, Glicko2Rating, Rated, Rating, RatingSystem};
+pub use report::{GameEnd, GameReport, Record, TournamentReport};
+pub use round_robin::RoundRobin;
+pub use swiss::Swiss;
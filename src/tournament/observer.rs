@@ -0,0 +1,104 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::core::{FinalScores, GameLogic, Id, MoveResult};
+use crate::simulation::{Observer, SimulationError};
+
+/// Watches the games of a tournament as they are played. The events are those of
+/// [`Observer`], with the ID of the game attached.
+///
+/// Games run concurrently on worker threads, so callbacks take `&self` and events of different
+/// games interleave. Events of a single game arrive in order.
+pub trait TournamentObserver<G: GameLogic, GID: Id>: Sync {
+    /// Called once a game is set up, before its first turn.
+    fn on_game_start(
+        &self,
+        _game_id: GID,
+        _players: &[G::PID],
+        _seed: Option<u64>,
+        _state: &G::State,
+        _active_players: &HashSet<G::PID>,
+    ) {
+    }
+
+    /// Called at the start of every turn of a game.
+    fn on_turn_start(
+        &self,
+        _game_id: GID,
+        _turn: usize,
+        _state: &G::State,
+        _active_players: &HashSet<G::PID>,
+    ) {
+    }
+
+    /// Called with the moves of a turn, just before they are applied.
+    fn on_moves(&self, _game_id: GID, _turn: usize, _moves: &HashMap<G::PID, G::Move>) {}
+
+    /// Called after the moves of a turn were applied successfully.
+    fn on_moves_applied(
+        &self,
+        _game_id: GID,
+        _turn: usize,
+        _state: &G::State,
+        _result: &MoveResult<G::PID>,
+    ) {
+    }
+
+    /// Called when a game ends with an error, including games whose agents couldn't be created.
+    fn on_error(&self, _game_id: GID, _error: &SimulationError<G::PID>) {}
+
+    /// Called when a game ends with final scores.
+    fn on_game_over(&self, _game_id: GID, _scores: &FinalScores<G::PID>) {}
+}
+
+/// Observes nothing.
+impl<G: GameLogic, GID: Id> TournamentObserver<G, GID> for () {}
+
+/// Adapts tournament observers to a single game, attaching its ID to every event.
+pub(crate) struct GameObserver<'a, G: GameLogic, GID: Id> {
+    pub(crate) observers: &'a [&'a dyn TournamentObserver<G, GID>],
+    pub(crate) game_id: GID,
+}
+
+impl<G: GameLogic, GID: Id> Observer<G> for GameObserver<'_, G, GID> {
+    fn on_game_start(
+        &mut self,
+        players: &[G::PID],
+        seed: Option<u64>,
+        state: &G::State,
+        active_players: &HashSet<G::PID>,
+    ) {
+        for observer in self.observers {
+            observer.on_game_start(self.game_id, players, seed, state, active_players);
+        }
+    }
+
+    fn on_turn_start(&mut self, turn: usize, state: &G::State, active_players: &HashSet<G::PID>) {
+        for observer in self.observers {
+            observer.on_turn_start(self.game_id, turn, state, active_players);
+        }
+    }
+
+    fn on_moves(&mut self, turn: usize, moves: &HashMap<G::PID, G::Move>) {
+        for observer in self.observers {
+            observer.on_moves(self.game_id, turn, moves);
+        }
+    }
+
+    fn on_moves_applied(&mut self, turn: usize, state: &G::State, result: &MoveResult<G::PID>) {
+        for observer in self.observers {
+            observer.on_moves_applied(self.game_id, turn, state, result);
+        }
+    }
+
+    fn on_error(&mut self, error: &SimulationError<G::PID>) {
+        for observer in self.observers {
+            observer.on_error(self.game_id, error);
+        }
+    }
+
+    fn on_game_over(&mut self, scores: &FinalScores<G::PID>) {
+        for observer in self.observers {
+            observer.on_game_over(self.game_id, scores);
+        }
+    }
+}
@@ -0,0 +1,391 @@
+use std::{collections::HashMap, f64::consts::PI};
+
+use crate::core::{FinalScores, Id, Outcome};
+
+use super::matchmaker::{MatchMaker, MatchMakerOutput, Matchup};
+use super::report::GameReport;
+
+/// A player's rating along with how uncertain it is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rating {
+    /// The estimated strength of the player.
+    pub value: f64,
+    /// The standard deviation of the estimate. Systems without uncertainty report 0.
+    pub deviation: f64,
+}
+
+/// A rating system that learns player strength from game results.
+///
+/// Games are ranked by score: a player beats every player with a lower score and draws with
+/// every player with an equal one. Players missing from a game's `FinalScores` count as
+/// scoring 0, which is why the full list of participants is passed alongside the scores.
+/// Games with an `Outcome` are ranked by its rankings instead, see `update_outcome`.
+pub trait RatingSystem<PID: Id> {
+    /// Updates the ratings of every participant of a single game.
+    ///
+    /// # Arguments
+    /// * `players` - Every player who took part in the game.
+    /// * `result` - The final scores of the game.
+    fn update(&mut self, players: &[PID], result: &FinalScores<PID>);
+
+    /// Updates the ratings from a game's outcome, ranking the players by their place in it
+    /// rather than by their score, e.g. so that a forfeit without a penalty still counts as a
+    /// loss. Players the outcome doesn't rank come last. Defaults to `update` with scores
+    /// that order the players the same way.
+    fn update_outcome(&mut self, players: &[PID], outcome: &Outcome<PID>) {
+        self.update(players, &rank_scores(players, outcome));
+    }
+
+    /// Returns a player's current rating, or the initial rating if they haven't played yet.
+    fn rating(&self, player: PID) -> Rating;
+
+    /// Returns the ratings of every player who has played at least one game.
+    fn ratings(&self) -> HashMap<PID, Rating>;
+
+    /// Updates the ratings from a stream of games, e.g. results loaded from disk.
+    fn update_all<I>(&mut self, games: I)
+    where
+        I: IntoIterator<Item = (Matchup<PID>, FinalScores<PID>)>,
+        Self: Sized,
+    {
+        for (players, result) in games {
+            self.update(&players, &result);
+        }
+    }
+}
+
+/// Scores `players` so that higher places in `outcome` score more.
+fn rank_scores<PID: Id>(players: &[PID], outcome: &Outcome<PID>) -> FinalScores<PID> {
+    let unranked = outcome.rankings.len() + 1;
+    players
+        .iter()
+        .map(|&pid| {
+            let rank = outcome.rank_of(pid).unwrap_or(unranked);
+            (pid, -(rank as i32))
+        })
+        .collect()
+}
+
+/// Returns every pairwise outcome of a game as `(player, opponent, score)`,
+/// where the score is 1 for a win, 0.5 for a draw and 0 for a loss.
+fn pairwise_outcomes<PID: Id>(players: &[PID], result: &FinalScores<PID>) -> Vec<(PID, PID, f64)> {
+    let score = |pid: &PID| result.get(pid).copied().unwrap_or(0);
+    let mut outcomes = Vec::new();
+
+    for player in players {
+        for opponent in players.iter().filter(|&opponent| opponent != player) {
+            let outcome = match score(player).cmp(&score(opponent)) {
+                std::cmp::Ordering::Greater => 1.0,
+                std::cmp::Ordering::Equal => 0.5,
+                std::cmp::Ordering::Less => 0.0,
+            };
+            outcomes.push((*player, *opponent, outcome));
+        }
+    }
+
+    outcomes
+}
+
+/// The classic Elo rating system, extended to multiplayer games by treating every game as
+/// a set of pairwise matches. Each player's change is scaled by `1 / (players - 1)`, so a
+/// single game is worth at most `k_factor` points regardless of its size.
+#[derive(Debug, Clone)]
+pub struct Elo<PID: Id> {
+    k_factor: f64,
+    initial_rating: f64,
+    ratings: HashMap<PID, f64>,
+}
+
+impl<PID: Id> Elo<PID> {
+    /// Creates an Elo system with the given K-factor and starting rating.
+    pub fn new(k_factor: f64, initial_rating: f64) -> Self {
+        Elo {
+            k_factor,
+            initial_rating,
+            ratings: HashMap::new(),
+        }
+    }
+
+    /// Sets a player's rating, e.g. to carry ratings over from an earlier tournament.
+    pub fn set_rating(&mut self, player: PID, rating: f64) {
+        self.ratings.insert(player, rating);
+    }
+
+    fn value(&self, player: PID) -> f64 {
+        self.ratings
+            .get(&player)
+            .copied()
+            .unwrap_or(self.initial_rating)
+    }
+}
+
+impl<PID: Id> Default for Elo<PID> {
+    /// K-factor 32 and starting rating 1500.
+    fn default() -> Self {
+        Elo::new(32.0, 1500.0)
+    }
+}
+
+impl<PID: Id> RatingSystem<PID> for Elo<PID> {
+    fn update(&mut self, players: &[PID], result: &FinalScores<PID>) {
+        if players.len() < 2 {
+            return;
+        }
+
+        let scale = self.k_factor / (players.len() - 1) as f64;
+        let mut changes: HashMap<PID, f64> = HashMap::new();
+        for (player, opponent, outcome) in pairwise_outcomes(players, result) {
+            let expected =
+                1.0 / (1.0 + 10f64.powf((self.value(opponent) - self.value(player)) / 400.0));
+            *changes.entry(player).or_insert(0.0) += scale * (outcome - expected);
+        }
+
+        // Apply after computing, so every pairing uses the ratings from before the game
+        for (player, change) in changes {
+            let new_value = self.value(player) + change;
+            self.ratings.insert(player, new_value);
+        }
+    }
+
+    fn rating(&self, player: PID) -> Rating {
+        Rating {
+            value: self.value(player),
+            deviation: 0.0,
+        }
+    }
+
+    fn ratings(&self) -> HashMap<PID, Rating> {
+        self.ratings
+            .keys()
+            .map(|&pid| (pid, self.rating(pid)))
+            .collect()
+    }
+}
+
+/// The Glicko-2 state of a single player, on the familiar Glicko scale.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Glicko2Rating {
+    /// The rating, centered on 1500.
+    pub rating: f64,
+    /// The rating deviation (RD).
+    pub deviation: f64,
+    /// How erratic the player's results are.
+    pub volatility: f64,
+}
+
+/// The ratio between the Glicko and Glicko-2 scales.
+const GLICKO2_SCALE: f64 = 173.7178;
+
+/// The Glicko-2 rating system, which tracks both a rating deviation and a volatility.
+///
+/// Every game is treated as its own rating period, in which a player has played every
+/// other participant with the outcome given by comparing their scores.
+#[derive(Debug, Clone)]
+pub struct Glicko2<PID: Id> {
+    initial: Glicko2Rating,
+    tau: f64,
+    ratings: HashMap<PID, Glicko2Rating>,
+}
+
+impl<PID: Id> Glicko2<PID> {
+    /// Creates a Glicko-2 system.
+    ///
+    /// # Arguments
+    /// * `initial` - The rating given to players before their first game.
+    /// * `tau` - Constrains how quickly volatility changes. Sensible values are 0.3 to 1.2.
+    pub fn new(initial: Glicko2Rating, tau: f64) -> Self {
+        Glicko2 {
+            initial,
+            tau,
+            ratings: HashMap::new(),
+        }
+    }
+
+    /// Returns the full Glicko-2 state of a player.
+    pub fn glicko2_rating(&self, player: PID) -> Glicko2Rating {
+        self.ratings.get(&player).copied().unwrap_or(self.initial)
+    }
+
+    /// Sets a player's state, e.g. to carry ratings over from an earlier tournament.
+    pub fn set_rating(&mut self, player: PID, rating: Glicko2Rating) {
+        self.ratings.insert(player, rating);
+    }
+
+    /// Computes a player's new state after facing the given opponents,
+    /// following the steps of Glickman's "Example of the Glicko-2 system".
+    fn rate(&self, player: Glicko2Rating, games: &[(Glicko2Rating, f64)]) -> Glicko2Rating {
+        let mu = (player.rating - 1500.0) / GLICKO2_SCALE;
+        let phi = player.deviation / GLICKO2_SCALE;
+        let sigma = player.volatility;
+
+        let g = |phi: f64| 1.0 / (1.0 + 3.0 * phi * phi / (PI * PI)).sqrt();
+        let mut v_inverse = 0.0;
+        let mut improvement = 0.0;
+        for (opponent, outcome) in games {
+            let opponent_mu = (opponent.rating - 1500.0) / GLICKO2_SCALE;
+            let opponent_g = g(opponent.deviation / GLICKO2_SCALE);
+            let expected = 1.0 / (1.0 + (-opponent_g * (mu - opponent_mu)).exp());
+            v_inverse += opponent_g * opponent_g * expected * (1.0 - expected);
+            improvement += opponent_g * (outcome - expected);
+        }
+        let v = 1.0 / v_inverse;
+        let delta = v * improvement;
+
+        // Find the new volatility with the Illinois algorithm
+        let a = (sigma * sigma).ln();
+        let f = |x: f64| {
+            let ex = x.exp();
+            let denominator = phi * phi + v + ex;
+            ex * (delta * delta - phi * phi - v - ex) / (2.0 * denominator * denominator)
+                - (x - a) / (self.tau * self.tau)
+        };
+        let mut low = a;
+        let mut high = if delta * delta > phi * phi + v {
+            (delta * delta - phi * phi - v).ln()
+        } else {
+            let mut k = 1.0;
+            while f(a - k * self.tau) < 0.0 {
+                k += 1.0;
+            }
+            a - k * self.tau
+        };
+        let (mut f_low, mut f_high) = (f(low), f(high));
+        while (high - low).abs() > 1e-6 {
+            let candidate = low + (low - high) * f_low / (f_high - f_low);
+            let f_candidate = f(candidate);
+            if f_candidate * f_high <= 0.0 {
+                low = high;
+                f_low = f_high;
+            } else {
+                f_low /= 2.0;
+            }
+            high = candidate;
+            f_high = f_candidate;
+        }
+        let new_sigma = (low / 2.0).exp();
+
+        let pre_period_phi = (phi * phi + new_sigma * new_sigma).sqrt();
+        let new_phi = 1.0 / (1.0 / (pre_period_phi * pre_period_phi) + 1.0 / v).sqrt();
+        let new_mu = mu + new_phi * new_phi * improvement;
+
+        Glicko2Rating {
+            rating: new_mu * GLICKO2_SCALE + 1500.0,
+            deviation: new_phi * GLICKO2_SCALE,
+            volatility: new_sigma,
+        }
+    }
+}
+
+impl<PID: Id> Default for Glicko2<PID> {
+    /// Rating 1500, RD 350, volatility 0.06 and tau 0.5.
+    fn default() -> Self {
+        Glicko2::new(
+            Glicko2Rating {
+                rating: 1500.0,
+                deviation: 350.0,
+                volatility: 0.06,
+            },
+            0.5,
+        )
+    }
+}
+
+impl<PID: Id> RatingSystem<PID> for Glicko2<PID> {
+    fn update(&mut self, players: &[PID], result: &FinalScores<PID>) {
+        let mut games: HashMap<PID, Vec<(Glicko2Rating, f64)>> = HashMap::new();
+        for (player, opponent, outcome) in pairwise_outcomes(players, result) {
+            games
+                .entry(player)
+                .or_default()
+                .push((self.glicko2_rating(opponent), outcome));
+        }
+
+        let updated: Vec<(PID, Glicko2Rating)> = games
+            .iter()
+            .map(|(&player, games)| (player, self.rate(self.glicko2_rating(player), games)))
+            .collect();
+        self.ratings.extend(updated);
+    }
+
+    fn rating(&self, player: PID) -> Rating {
+        let rating = self.glicko2_rating(player);
+        Rating {
+            value: rating.rating,
+            deviation: rating.deviation,
+        }
+    }
+
+    fn ratings(&self) -> HashMap<PID, Rating> {
+        self.ratings
+            .keys()
+            .map(|&pid| (pid, self.rating(pid)))
+            .collect()
+    }
+}
+
+/// Wraps a matchmaker so that every game result is also fed to a rating system.
+/// Games are rated by their outcome's rankings, see `RatingSystem::update_outcome`. Games that
+/// failed with a `SimulationError` are passed on without being rated.
+///
+/// The wrapped matchmaker behaves exactly as before, so any matchmaker can be rated:
+/// ```ignore
+/// let mut rated = Rated::new(RoundRobin::new(players, 2, 10), Glicko2::default());
+/// host_tournament(&game, factories, &mut rated, &mut ids, Some(1000));
+/// let ratings = rated.rating_system().ratings();
+/// ```
+pub struct Rated<M, R> {
+    matchmaker: M,
+    rating_system: R,
+}
+
+impl<M, R> Rated<M, R> {
+    /// Wraps `matchmaker`, feeding its results to `rating_system`.
+    pub fn new(matchmaker: M, rating_system: R) -> Self {
+        Rated {
+            matchmaker,
+            rating_system,
+        }
+    }
+
+    /// Returns the wrapped matchmaker.
+    pub fn matchmaker(&self) -> &M {
+        &self.matchmaker
+    }
+
+    /// Returns the rating system.
+    pub fn rating_system(&self) -> &R {
+        &self.rating_system
+    }
+
+    /// Unwraps into the matchmaker and the rating system.
+    pub fn into_parts(self) -> (M, R) {
+        (self.matchmaker, self.rating_system)
+    }
+}
+
+impl<M, R> MatchMaker for Rated<M, R>
+where
+    M: MatchMaker,
+    R: RatingSystem<M::PID>,
+{
+    type PID = M::PID;
+    type GID = M::GID;
+
+    fn initial_games(&self) -> Vec<Matchup<Self::PID>> {
+        self.matchmaker.initial_games()
+    }
+
+    fn digest_result(
+        &mut self,
+        report: &GameReport<Self::PID, Self::GID>,
+    ) -> MatchMakerOutput<Self::PID> {
+        match (&report.result, &report.outcome) {
+            (Ok(_), Some(outcome)) => self.rating_system.update_outcome(&report.players, outcome),
+            (Ok(scores), None) => self.rating_system.update(&report.players, scores),
+            (Err(_), _) => {}
+        }
+        self.matchmaker.digest_result(report)
+    }
+}
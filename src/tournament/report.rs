@@ -0,0 +1,184 @@
+use std::{collections::HashMap, time::Duration};
+
+use crate::core::{FinalScores, Id, Outcome, Termination};
+use crate::simulation::{GameResult, SimulationError};
+
+use super::manager::TournamentResult;
+use super::matchmaker::Matchup;
+
+/// How a game of the tournament ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameEnd {
+    /// The game reached its natural end and produced final scores.
+    Normal,
+    /// A player forfeited, e.g. by running out of time or making an illegal move, and the
+    /// game was scored against them. `Termination::Forfeit` names the player.
+    Forfeit,
+    /// The game was stopped after reaching the turn limit.
+    TurnLimit,
+    /// The game logic reported an error.
+    Error,
+    /// An agent, the game logic or an observer panicked. `SimulationError::culprit` names the
+    /// agent's player.
+    Panicked,
+    /// An agent ran out of time and the game was stopped.
+    TimedOut,
+}
+
+/// Everything recorded about a single game of a tournament.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameReport<PID: Id, GID: Id> {
+    /// The ID the game was given by the tournament's `IdGenerator`.
+    pub game_id: GID,
+    /// The players of the game, in seat order.
+    pub players: Matchup<PID>,
+    /// The final scores, or the error that ended the game.
+    pub result: GameResult<PID>,
+    /// The rankings, scores and termination reason, if the game ended normally.
+    pub outcome: Option<Outcome<PID>>,
+    /// The number of turns played.
+    pub turns: usize,
+    /// The wall-clock time the game took.
+    pub duration: Duration,
+    /// What the agents logged during the game by player, e.g. what sandboxed bots wrote to
+    /// stderr. Agents that logged nothing, or whose game was lost to a panicking observer, are
+    /// left out. See `Agent::take_log`.
+    pub logs: HashMap<PID, String>,
+}
+
+impl<PID: Id, GID: Id> GameReport<PID, GID> {
+    /// Returns how the game ended.
+    pub fn end(&self) -> GameEnd {
+        match &self.result {
+            Ok(_) => match self.outcome.as_ref().map(|outcome| &outcome.termination) {
+                Some(Termination::Forfeit(_)) => GameEnd::Forfeit,
+                _ => GameEnd::Normal,
+            },
+            Err(SimulationError::MaxTurnsExceeded(_)) => GameEnd::TurnLimit,
+            Err(SimulationError::GameError(_)) => GameEnd::Error,
+            Err(
+                SimulationError::AgentPanicked { .. }
+                | SimulationError::GamePanicked(_)
+                | SimulationError::ObserverPanicked(_),
+            ) => GameEnd::Panicked,
+            Err(SimulationError::TimedOut { .. }) => GameEnd::TimedOut,
+        }
+    }
+
+    /// Returns the final scores, if the game ended normally.
+    pub fn scores(&self) -> Option<&FinalScores<PID>> {
+        self.result.as_ref().ok()
+    }
+
+    /// Returns a player's score, counting players missing from the final scores as 0.
+    /// Returns `None` if the game didn't end normally.
+    pub fn score_of(&self, player: PID) -> Option<i32> {
+        self.scores()
+            .map(|scores| scores.get(&player).copied().unwrap_or(0))
+    }
+
+    /// Returns a player's finishing position, starting from 1 for the winners. Players the
+    /// outcome doesn't rank come after everyone else. Returns `None` if the game didn't end
+    /// normally.
+    pub fn rank_of(&self, player: PID) -> Option<usize> {
+        self.outcome
+            .as_ref()
+            .map(|outcome| outcome.rank_of(player).unwrap_or(usize::MAX))
+    }
+}
+
+/// Win, draw and loss counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Record {
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+}
+
+impl Record {
+    /// Returns the number of games counted in this record.
+    pub fn games(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+}
+
+/// A structured report of a whole tournament.
+///
+/// Only games that ended normally count towards win/draw/loss tables. A player wins a game
+/// by finishing first alone, draws by sharing first place, and loses otherwise. Games ranked
+/// from their scores, like those ending with `MoveResult::GameOver`, rank the highest score first.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TournamentReport<PID: Id, GID: Id> {
+    /// The result returned by the matchmaker.
+    pub result: TournamentResult<PID>,
+    /// Every game played, in the order the results were handed to the matchmaker.
+    pub games: Vec<GameReport<PID, GID>>,
+}
+
+impl<PID: Id, GID: Id> TournamentReport<PID, GID> {
+    /// Returns the games that ended with a `SimulationError`. Forfeited games were scored,
+    /// so they aren't counted.
+    pub fn failed_games(&self) -> impl Iterator<Item = &GameReport<PID, GID>> {
+        self.games.iter().filter(|game| game.result.is_err())
+    }
+
+    /// Returns each player's overall win/draw/loss record.
+    pub fn win_draw_loss(&self) -> HashMap<PID, Record> {
+        let mut records: HashMap<PID, Record> = HashMap::new();
+
+        for game in &self.games {
+            let Some(outcome) = &game.outcome else {
+                continue;
+            };
+            let winners = outcome.winners();
+            let leaders = winners.len();
+
+            for &pid in &game.players {
+                let record = records.entry(pid).or_default();
+                match (winners.contains(&pid), leaders) {
+                    (true, 1) => record.wins += 1,
+                    (true, _) => record.draws += 1,
+                    (false, _) => record.losses += 1,
+                }
+            }
+        }
+
+        records
+    }
+
+    /// Returns the head-to-head records between every pair of players who met.
+    ///
+    /// The record at `(a, b)` is from `a`'s point of view: a win is a game in which `a`
+    /// finished ahead of `b`, regardless of how the other players at the table did.
+    pub fn head_to_head(&self) -> HashMap<(PID, PID), Record> {
+        let mut matrix: HashMap<(PID, PID), Record> = HashMap::new();
+
+        for game in &self.games {
+            for &a in &game.players {
+                for &b in game.players.iter().filter(|&&b| b != a) {
+                    let (Some(rank_a), Some(rank_b)) = (game.rank_of(a), game.rank_of(b)) else {
+                        continue;
+                    };
+                    let record = matrix.entry((a, b)).or_default();
+                    match rank_a.cmp(&rank_b) {
+                        std::cmp::Ordering::Less => record.wins += 1,
+                        std::cmp::Ordering::Equal => record.draws += 1,
+                        std::cmp::Ordering::Greater => record.losses += 1,
+                    }
+                }
+            }
+        }
+
+        matrix
+    }
+
+    /// Returns the total wall-clock time spent in games.
+    /// Games run concurrently, so this is usually longer than the tournament itself.
+    pub fn total_game_time(&self) -> Duration {
+        self.games.iter().map(|game| game.duration).sum()
+    }
+}
@@ -0,0 +1,172 @@
+use std::{collections::HashMap, marker::PhantomData};
+
+use crate::core::Id;
+
+use super::manager::TournamentResult;
+use super::matchmaker::{
+    unique_players, FailurePolicy, MatchMaker, MatchMakerOutput, Matchup, Resolution, Scoring,
+};
+use super::report::GameReport;
+
+/// A matchmaker that plays every combination of `table_size` players.
+///
+/// All games are scheduled up front by `initial_games`. The scores of each game are
+/// summed per player, and the tournament is done once every scheduled game has reported.
+/// A `Scoring` with points for wins and draws can be used instead of the scores.
+/// Failed games are handled according to the `FailurePolicy`, which defaults to ignoring them.
+pub struct RoundRobin<PID: Id, GID: Id> {
+    players: Vec<PID>,
+    table_size: usize,
+    repeats: usize,
+    rotate_seats: bool,
+    remaining: usize,
+    totals: TournamentResult<PID>,
+    failure_policy: FailurePolicy,
+    scoring: Scoring,
+    replays: HashMap<Matchup<PID>, usize>,
+    _game_id: PhantomData<GID>,
+}
+
+impl<PID: Id, GID: Id> RoundRobin<PID, GID> {
+    /// Creates a round robin over the given players.
+    ///
+    /// # Arguments
+    /// * `players` - The participating players. Duplicates are ignored, and the first
+    ///   occurrence determines seating order within each combination.
+    /// * `table_size` - The number of players seated at every game.
+    /// * `repeats` - How many times the full schedule is played.
+    ///
+    /// # Panics
+    /// Panics if `table_size` is zero or larger than the number of players, or if `repeats`
+    /// is zero -- such a tournament would never schedule a game and could never finish.
+    pub fn new(players: impl IntoIterator<Item = PID>, table_size: usize, repeats: usize) -> Self {
+        let unique_players = unique_players(players);
+
+        assert!(
+            table_size > 0 && table_size <= unique_players.len(),
+            "table size must be between 1 and the number of players"
+        );
+        assert!(repeats > 0, "a round robin must be played at least once");
+
+        let totals = unique_players.iter().map(|&pid| (pid, 0)).collect();
+        let mut round_robin = RoundRobin {
+            players: unique_players,
+            table_size,
+            repeats,
+            rotate_seats: false,
+            remaining: 0,
+            totals,
+            failure_policy: FailurePolicy::default(),
+            scoring: Scoring::default(),
+            replays: HashMap::new(),
+            _game_id: PhantomData,
+        };
+        round_robin.remaining = round_robin.schedule().len();
+        round_robin
+    }
+
+    /// Plays every combination once per seat rotation, so that each player occupies
+    /// every seat of every table they are part of.
+    pub fn with_seat_rotation(mut self) -> Self {
+        self.rotate_seats = true;
+        self.remaining = self.schedule().len();
+        self
+    }
+
+    /// Sets how games that ended with a `SimulationError` are treated.
+    pub fn with_failure_policy(mut self, failure_policy: FailurePolicy) -> Self {
+        self.failure_policy = failure_policy;
+        self
+    }
+
+    /// Sets how games that ended normally are credited, e.g. with points for wins and draws.
+    pub fn with_scoring(mut self, scoring: Scoring) -> Self {
+        self.scoring = scoring;
+        self
+    }
+
+    /// Returns the full list of games this round robin plays, in scheduling order.
+    pub fn schedule(&self) -> Vec<Matchup<PID>> {
+        let rotations = if self.rotate_seats {
+            self.table_size
+        } else {
+            1
+        };
+        let mut round = Vec::new();
+
+        for combination in combinations(self.players.len(), self.table_size) {
+            for rotation in 0..rotations {
+                round.push(
+                    (0..self.table_size)
+                        .map(|seat| self.players[combination[(seat + rotation) % self.table_size]])
+                        .collect(),
+                );
+            }
+        }
+
+        (0..self.repeats)
+            .flat_map(|_| round.iter().cloned())
+            .collect()
+    }
+
+    /// Returns the number of games that have not reported a result yet.
+    pub fn remaining_games(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<PID: Id, GID: Id> MatchMaker for RoundRobin<PID, GID> {
+    type PID = PID;
+    type GID = GID;
+
+    fn initial_games(&self) -> Vec<Matchup<PID>> {
+        self.schedule()
+    }
+
+    fn digest_result(&mut self, report: &GameReport<PID, GID>) -> MatchMakerOutput<PID> {
+        let scores = match &report.result {
+            Ok(scores) => self.scoring.award(report, scores),
+            Err(error) => {
+                match self
+                    .failure_policy
+                    .resolve(&report.players, error, &mut self.replays)
+                {
+                    Resolution::Scores(scores) => scores,
+                    Resolution::Replay => {
+                        return MatchMakerOutput::Continue(vec![report.players.clone()])
+                    }
+                }
+            }
+        };
+
+        for (pid, score) in scores {
+            *self.totals.entry(pid).or_insert(0) += score;
+        }
+
+        self.remaining = self.remaining.saturating_sub(1);
+        if self.remaining == 0 {
+            MatchMakerOutput::Done(self.totals.clone())
+        } else {
+            MatchMakerOutput::Continue(Vec::new())
+        }
+    }
+}
+
+/// Returns every `k`-element combination of the indices `0..n`, in lexicographic order.
+fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    let mut result = Vec::new();
+    let mut current: Vec<usize> = (0..k).collect();
+
+    loop {
+        result.push(current.clone());
+
+        // Find the rightmost index that can still be advanced
+        let Some(i) = (0..k).rev().find(|&i| current[i] < n - k + i) else {
+            return result;
+        };
+        current[i] += 1;
+        for j in i + 1..k {
+            current[j] = current[j - 1] + 1;
+        }
+    }
+}
@@ -0,0 +1,414 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    marker::PhantomData,
+};
+
+use crate::core::Id;
+
+use super::manager::TournamentResult;
+use super::matchmaker::{
+    unique_players, FailurePolicy, MatchMaker, MatchMakerOutput, Matchup, Resolution, Scoring,
+};
+use super::report::GameReport;
+
+/// A round's pairings, along with the player sitting it out (if any).
+type Pairing<PID> = (Option<PID>, Vec<(PID, PID)>);
+
+/// The most steps the search for closest ranked pairings takes for one round before settling
+/// for any pairing without rematches.
+const SEARCH_STEPS: usize = 10_000;
+
+/// A Swiss-system matchmaker for two-player games.
+///
+/// Every round, players are ranked by their running totals and paired with the closest
+/// ranked opponent they have not met yet. With an odd number of players, the lowest ranked
+/// player who has not had a bye yet sits the round out and is credited `bye_score`.
+/// When rematches leave so few options that the closest ranked pairing takes too long to
+/// find, any pairing without rematches is used, which is found in polynomial time.
+///
+/// Totals add up the scores of each game, unless a `Scoring` with points for wins and draws
+/// is used. A new round is only scheduled once every game of the current round has reported.
+/// If no pairing without rematches exists, the tournament ends early.
+/// Failed games are handled according to the `FailurePolicy`, which defaults to ignoring them.
+/// Replayed games belong to the round they replace.
+pub struct Swiss<PID: Id, GID: Id> {
+    players: Vec<PID>,
+    rounds: usize,
+    bye_score: i32,
+    rounds_started: usize,
+    current_round: Vec<Matchup<PID>>,
+    pending: usize,
+    played: HashSet<(PID, PID)>,
+    byes: HashSet<PID>,
+    totals: TournamentResult<PID>,
+    failure_policy: FailurePolicy,
+    scoring: Scoring,
+    replays: HashMap<Matchup<PID>, usize>,
+    _game_id: PhantomData<GID>,
+}
+
+impl<PID: Id, GID: Id> Swiss<PID, GID> {
+    /// Creates a Swiss tournament and pairs its first round.
+    ///
+    /// # Arguments
+    /// * `players` - The participating players in seed order. Duplicates are ignored.
+    ///   Seed order breaks ties between players with equal totals.
+    /// * `rounds` - The number of rounds to play.
+    /// * `bye_score` - The score credited to a player sitting out a round.
+    ///
+    /// # Panics
+    /// Panics if there are fewer than two players or `rounds` is zero.
+    pub fn new(players: impl IntoIterator<Item = PID>, rounds: usize, bye_score: i32) -> Self {
+        let unique_players = unique_players(players);
+
+        assert!(
+            unique_players.len() >= 2,
+            "a Swiss tournament needs at least two players"
+        );
+        assert!(
+            rounds > 0,
+            "a Swiss tournament must have at least one round"
+        );
+
+        let totals = unique_players.iter().map(|&pid| (pid, 0)).collect();
+        let mut swiss = Swiss {
+            players: unique_players,
+            rounds,
+            bye_score,
+            rounds_started: 0,
+            current_round: Vec::new(),
+            pending: 0,
+            played: HashSet::new(),
+            byes: HashSet::new(),
+            totals,
+            failure_policy: FailurePolicy::default(),
+            scoring: Scoring::default(),
+            replays: HashMap::new(),
+            _game_id: PhantomData,
+        };
+        swiss.start_round();
+        swiss
+    }
+
+    /// Sets how games that ended with a `SimulationError` are treated.
+    pub fn with_failure_policy(mut self, failure_policy: FailurePolicy) -> Self {
+        self.failure_policy = failure_policy;
+        self
+    }
+
+    /// Sets how games that ended normally are credited, e.g. with points for wins and draws.
+    pub fn with_scoring(mut self, scoring: Scoring) -> Self {
+        self.scoring = scoring;
+        self
+    }
+
+    /// Returns the running totals of every player.
+    pub fn standings(&self) -> &TournamentResult<PID> {
+        &self.totals
+    }
+
+    /// Returns the number of rounds that have been paired so far.
+    pub fn rounds_started(&self) -> usize {
+        self.rounds_started
+    }
+
+    /// Returns the games of the round currently being played.
+    pub fn current_round(&self) -> &[Matchup<PID>] {
+        &self.current_round
+    }
+
+    /// Returns whether the two players have already been paired against each other.
+    pub fn have_met(&self, a: PID, b: PID) -> bool {
+        self.played.contains(&(a, b))
+    }
+
+    /// Pairs the next round and records it. Returns `false` if no valid pairing exists.
+    fn start_round(&mut self) -> bool {
+        // Stable sort, so seed order breaks ties
+        let mut ranked = self.players.clone();
+        ranked.sort_by_key(|pid| std::cmp::Reverse(self.totals[pid]));
+
+        let Some((bye, pairs)) = self.pair_with_bye(&ranked) else {
+            return false;
+        };
+
+        if let Some(pid) = bye {
+            self.byes.insert(pid);
+            *self.totals.entry(pid).or_insert(0) += self.bye_score;
+        }
+        for &(a, b) in &pairs {
+            self.played.insert((a, b));
+            self.played.insert((b, a));
+        }
+
+        self.current_round = pairs.into_iter().map(|(a, b)| vec![a, b]).collect();
+        self.pending = self.current_round.len();
+        self.rounds_started += 1;
+        true
+    }
+
+    /// Chooses the bye (if needed) and pairs everyone else.
+    /// Bye candidates are tried from the bottom of the standings upwards, preferring
+    /// players who have not had a bye yet.
+    fn pair_with_bye(&self, ranked: &[PID]) -> Option<Pairing<PID>> {
+        if ranked.len() % 2 == 0 {
+            return self.pair(ranked).map(|pairs| (None, pairs));
+        }
+
+        let candidates = ranked
+            .iter()
+            .rev()
+            .filter(|pid| !self.byes.contains(pid))
+            .chain(ranked.iter().rev().filter(|pid| self.byes.contains(pid)));
+
+        for &bye in candidates {
+            let rest: Vec<PID> = ranked.iter().copied().filter(|&pid| pid != bye).collect();
+            if let Some(pairs) = self.pair(&rest) {
+                return Some((Some(bye), pairs));
+            }
+        }
+        None
+    }
+
+    /// Pairs every player with an opponent they haven't met, or returns `None` if that's
+    /// impossible. Pairs the highest ranked player with the closest ranked opponent possible,
+    /// and so on down, unless finding those pairings takes more than `SEARCH_STEPS`.
+    fn pair(&self, ranked: &[PID]) -> Option<Vec<(PID, PID)>> {
+        let can_meet = |a: usize, b: usize| !self.have_met(ranked[a], ranked[b]);
+        let matching = maximum_matching(ranked.len(), can_meet);
+        if matching.iter().any(Option::is_none) {
+            return None;
+        }
+
+        let mut paired = vec![false; ranked.len()];
+        let mut pairs = Vec::new();
+        let mut steps = SEARCH_STEPS;
+        if closest_pairs(&can_meet, &mut paired, &mut pairs, &mut steps) {
+            return Some(
+                pairs
+                    .into_iter()
+                    .map(|(a, b)| (ranked[a], ranked[b]))
+                    .collect(),
+            );
+        }
+
+        // Any pairing without rematches will do, and the matching started out in rank order
+        Some(
+            matching
+                .iter()
+                .enumerate()
+                .filter_map(|(a, &b)| b.filter(|&b| a < b).map(|b| (ranked[a], ranked[b])))
+                .collect(),
+        )
+    }
+}
+
+/// Pairs the highest ranked unpaired player with the closest ranked opponent they can meet,
+/// backtracking when the others can't be paired. Returns `false` if there is no such pairing,
+/// or once `steps` run out.
+fn closest_pairs(
+    can_meet: &impl Fn(usize, usize) -> bool,
+    paired: &mut [bool],
+    pairs: &mut Vec<(usize, usize)>,
+    steps: &mut usize,
+) -> bool {
+    let Some(first) = paired.iter().position(|&done| !done) else {
+        return true;
+    };
+    for opponent in first + 1..paired.len() {
+        if paired[opponent] || !can_meet(first, opponent) {
+            continue;
+        }
+        if *steps == 0 {
+            return false;
+        }
+        *steps -= 1;
+
+        paired[first] = true;
+        paired[opponent] = true;
+        pairs.push((first, opponent));
+        if closest_pairs(can_meet, paired, pairs, steps) {
+            return true;
+        }
+        pairs.pop();
+        paired[first] = false;
+        paired[opponent] = false;
+    }
+    false
+}
+
+/// Finds a maximum matching between `n` players with Edmonds' blossom algorithm, in O(n³).
+/// Returns each player's partner, if they have one.
+///
+/// Players are first paired greedily in rank order, and the matching only grows from there,
+/// so it keeps most of those pairs.
+fn maximum_matching(n: usize, can_meet: impl Fn(usize, usize) -> bool) -> Vec<Option<usize>> {
+    let neighbours: Vec<Vec<usize>> = (0..n)
+        .map(|a| (0..n).filter(|&b| a != b && can_meet(a, b)).collect())
+        .collect();
+    let mut partner: Vec<Option<usize>> = vec![None; n];
+    for a in 0..n {
+        if partner[a].is_none() {
+            if let Some(&b) = neighbours[a].iter().find(|&&b| partner[b].is_none()) {
+                partner[a] = Some(b);
+                partner[b] = Some(a);
+            }
+        }
+    }
+
+    for root in 0..n {
+        if partner[root].is_some() {
+            continue;
+        }
+        // Flip the matching along the augmenting path, if there is one
+        if let Some((end, parent)) = augmenting_path(root, &neighbours, &partner) {
+            let mut vertex = Some(end);
+            while let Some(v) = vertex {
+                let p = parent[v].expect("the path leads back to the root");
+                vertex = partner[p];
+                partner[v] = Some(p);
+                partner[p] = Some(v);
+            }
+        }
+    }
+    partner
+}
+
+/// Searches for a path from the unmatched `root` to another unmatched player that alternates
+/// between unmatched and matched pairs, contracting odd cycles ("blossoms") along the way.
+/// Returns the path's end and the parent of every player on its unmatched pairs.
+fn augmenting_path(
+    root: usize,
+    neighbours: &[Vec<usize>],
+    partner: &[Option<usize>],
+) -> Option<(usize, Vec<Option<usize>>)> {
+    let n = neighbours.len();
+    let mut used = vec![false; n];
+    let mut parent: Vec<Option<usize>> = vec![None; n];
+    let mut base: Vec<usize> = (0..n).collect();
+    let mut queue = VecDeque::from([root]);
+    used[root] = true;
+
+    while let Some(v) = queue.pop_front() {
+        for &to in &neighbours[v] {
+            if base[v] == base[to] || partner[v] == Some(to) {
+                continue;
+            }
+            if to == root || partner[to].is_some_and(|p| parent[p].is_some()) {
+                // Contract the blossom closed by v and to
+                let lca = lowest_common_ancestor(&base, &parent, partner, v, to);
+                let mut blossom = vec![false; n];
+                mark_path(&base, &mut parent, partner, &mut blossom, v, lca, to);
+                mark_path(&base, &mut parent, partner, &mut blossom, to, lca, v);
+                for i in 0..n {
+                    if blossom[base[i]] {
+                        base[i] = lca;
+                        if !used[i] {
+                            used[i] = true;
+                            queue.push_back(i);
+                        }
+                    }
+                }
+            } else if parent[to].is_none() {
+                parent[to] = Some(v);
+                match partner[to] {
+                    None => return Some((to, parent)),
+                    Some(next) => {
+                        used[next] = true;
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Returns the base of the first blossom on both the path from `a` and from `b` to the root.
+fn lowest_common_ancestor(
+    base: &[usize],
+    parent: &[Option<usize>],
+    partner: &[Option<usize>],
+    mut a: usize,
+    mut b: usize,
+) -> usize {
+    let mut seen = vec![false; base.len()];
+    loop {
+        a = base[a];
+        seen[a] = true;
+        match partner[a] {
+            Some(p) => a = parent[p].expect("matched players on the path have parents"),
+            None => break,
+        }
+    }
+    loop {
+        b = base[b];
+        if seen[b] {
+            return b;
+        }
+        let p = partner[b].expect("the path from b meets the path from a");
+        b = parent[p].expect("matched players on the path have parents");
+    }
+}
+
+/// Marks the blossoms on the path from `v` down to the base `lca`, pointing the parents along
+/// it back towards `child`.
+fn mark_path(
+    base: &[usize],
+    parent: &mut [Option<usize>],
+    partner: &[Option<usize>],
+    blossom: &mut [bool],
+    mut v: usize,
+    lca: usize,
+    mut child: usize,
+) {
+    while base[v] != lca {
+        let p = partner[v].expect("players inside a blossom are matched");
+        blossom[base[v]] = true;
+        blossom[base[p]] = true;
+        parent[v] = Some(child);
+        child = p;
+        v = parent[p].expect("matched players on the path have parents");
+    }
+}
+
+impl<PID: Id, GID: Id> MatchMaker for Swiss<PID, GID> {
+    type PID = PID;
+    type GID = GID;
+
+    fn initial_games(&self) -> Vec<Matchup<PID>> {
+        self.current_round.clone()
+    }
+
+    fn digest_result(&mut self, report: &GameReport<PID, GID>) -> MatchMakerOutput<PID> {
+        let scores = match &report.result {
+            Ok(scores) => self.scoring.award(report, scores),
+            Err(error) => {
+                match self
+                    .failure_policy
+                    .resolve(&report.players, error, &mut self.replays)
+                {
+                    Resolution::Scores(scores) => scores,
+                    Resolution::Replay => {
+                        return MatchMakerOutput::Continue(vec![report.players.clone()])
+                    }
+                }
+            }
+        };
+
+        for (pid, score) in scores {
+            *self.totals.entry(pid).or_insert(0) += score;
+        }
+
+        self.pending = self.pending.saturating_sub(1);
+        if self.pending > 0 {
+            return MatchMakerOutput::Continue(Vec::new());
+        }
+
+        if self.rounds_started < self.rounds && self.start_round() {
+            MatchMakerOutput::Continue(self.current_round.clone())
+        } else {
+            MatchMakerOutput::Done(self.totals.clone())
+        }
+    }
+}
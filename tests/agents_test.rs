@@ -0,0 +1,427 @@
+// Search agent tests
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use indexmap::IndexMap;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use game_logic::agents::{AlphaBetaAgent, AlphaBetaConfig, Evaluator, HumanAgent, IsmctsAgent, MctsAgent, MctsConfig, PimcAgent, RolloutPolicy, SearchBudget, WIN_SCORE};
+use game_logic::core::{Agent, Determinize, GameError, GameLogic, LegalMoves, MoveResult, Outcome};
+use game_logic::simulation::{simulate_game_recorded, simulate_game_with, GameRecord, IllegalMovePolicy, SimulationConfig};
+
+mod common;
+use common::nim::{NimGameLogic, NimMove, NimPerfectAgent, NimPlayerId, NimRandomAgent, NimState};
+
+fn nim(pile_size: u32, max_takes: u32) -> Arc<NimGameLogic> {
+    Arc::new(NimGameLogic {
+        initial_pile_size: pile_size,
+        max_takes,
+    })
+}
+
+fn seeded_mcts(game: &Arc<NimGameLogic>, iterations: usize, seed: u64) -> MctsAgent<NimGameLogic> {
+    MctsAgent::new(
+        Arc::clone(game),
+        MctsConfig {
+            budget: SearchBudget::Iterations(iterations),
+            seed: Some(seed),
+            ..MctsConfig::default()
+        },
+    )
+}
+
+fn pile(pile_size: u32) -> NimState {
+    NimState {
+        pile_size,
+        players: vec![NimPlayerId(1), NimPlayerId(2)],
+    }
+}
+
+#[test]
+fn test_mcts_finds_winning_nim_moves() {
+    let game = nim(20, 3);
+    for pile_size in [5, 6, 7, 9, 10, 11] {
+        let mut agent = seeded_mcts(&game, 3000, 7);
+        agent.on_game_start(NimPlayerId(1), &[NimPlayerId(1), NimPlayerId(2)]);
+
+        let amount = agent.calculate_next_move(pile(pile_size)).amount;
+        assert_eq!(amount, pile_size % 4, "Should leave a multiple of 4 from a pile of {}", pile_size);
+    }
+}
+
+#[test]
+fn test_mcts_beats_perfect_agent_from_winning_position() {
+    let game = nim(10, 3);
+    let mut agents: IndexMap<NimPlayerId, Box<dyn Agent<Game = NimGameLogic>>> = [
+        (NimPlayerId(1), Box::new(seeded_mcts(&game, 2000, 1)) as Box<dyn Agent<Game = NimGameLogic>>),
+        (NimPlayerId(2), Box::new(NimPerfectAgent::new(&game))),
+    ]
+    .into();
+
+    let summary = simulate_game_with(&*game, &mut agents, &SimulationConfig::default());
+    let outcome = summary.outcome.expect("The game should complete");
+    assert_eq!(outcome.winners(), [NimPlayerId(1)]);
+}
+
+#[test]
+fn test_seeded_mcts_is_reproducible() {
+    let game = nim(15, 3);
+    let play = |seed| -> GameRecord<NimPlayerId, NimMove> {
+        let mut agents: IndexMap<NimPlayerId, MctsAgent<NimGameLogic>> = [
+            (NimPlayerId(1), seeded_mcts(&game, 200, seed)),
+            (NimPlayerId(2), seeded_mcts(&game, 200, seed + 1)),
+        ]
+        .into();
+        simulate_game_recorded(&*game, &mut agents, &SimulationConfig::default()).1
+    };
+    let amounts = |record: &GameRecord<NimPlayerId, NimMove>| -> Vec<u32> {
+        record.turns.iter().flat_map(|turn| turn.moves.values().map(|m| m.amount)).collect()
+    };
+
+    assert_eq!(amounts(&play(3)), amounts(&play(3)));
+}
+
+#[test]
+fn test_mcts_uses_custom_rollouts_and_time_budgets() {
+    let game = nim(30, 3);
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&calls);
+    let config = MctsConfig {
+        budget: SearchBudget::Time(Duration::from_millis(30)),
+        // Always taking one match is a poor policy, but a valid one
+        rollout: RolloutPolicy::Custom(Arc::new(move |_state: &NimState, _player, mut moves: Vec<NimMove>, _rng: &mut _| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            moves.swap_remove(0)
+        })),
+        seed: Some(5),
+        ..MctsConfig::default()
+    };
+    let mut agent = MctsAgent::new(Arc::clone(&game), config);
+    agent.on_game_start(NimPlayerId(1), &[NimPlayerId(1), NimPlayerId(2)]);
+
+    let start = Instant::now();
+    let amount = agent.calculate_next_move(pile(30)).amount;
+    assert!((1..=3).contains(&amount));
+    assert!(start.elapsed() < Duration::from_millis(500), "took {:?}", start.elapsed());
+    assert!(calls.load(Ordering::Relaxed) > 0, "The custom rollout policy should be used");
+}
+
+fn no_evaluation(_game: &NimGameLogic, _state: &NimState, _player: NimPlayerId) -> i32 {
+    0
+}
+
+fn alpha_beta<E: Evaluator<NimGameLogic>>(game: &Arc<NimGameLogic>, evaluator: E, budget: SearchBudget) -> AlphaBetaAgent<NimGameLogic, E> {
+    let mut agent = AlphaBetaAgent::new(
+        Arc::clone(game),
+        evaluator,
+        AlphaBetaConfig {
+            budget,
+            ..AlphaBetaConfig::default()
+        },
+    );
+    agent.on_game_start(NimPlayerId(1), &[NimPlayerId(1), NimPlayerId(2)]);
+    agent
+}
+
+#[test]
+fn test_alpha_beta_solves_nim_without_evaluation() {
+    let game = nim(20, 3);
+    for pile_size in 5..=12 {
+        let mut agent = alpha_beta(&game, no_evaluation, SearchBudget::Iterations(20));
+        let amount = agent.calculate_next_move(pile(pile_size)).amount;
+        let info = agent.last_search().unwrap();
+
+        if pile_size % 4 == 0 {
+            assert!(info.score < -WIN_SCORE / 2, "A pile of {} is lost", pile_size);
+        } else {
+            assert_eq!(amount, pile_size % 4, "Should leave a multiple of 4 from a pile of {}", pile_size);
+            assert!(info.score > WIN_SCORE / 2, "A pile of {} is won", pile_size);
+        }
+        assert!(info.depth < 20, "The search should stop once the game is solved");
+    }
+}
+
+#[test]
+fn test_alpha_beta_reaches_its_depth_on_every_move() {
+    // The game is far too long to solve, so every search should use its whole budget, even once
+    // the table remembers positions from earlier moves
+    let game = nim(100, 3);
+    let mut agent = alpha_beta(&game, no_evaluation, SearchBudget::Iterations(8));
+    let mut pile_size = 100;
+    for _ in 0..5 {
+        let amount = agent.calculate_next_move(pile(pile_size)).amount;
+        assert_eq!(agent.last_search().unwrap().depth, 8, "from a pile of {}", pile_size);
+        pile_size -= amount + 1;
+    }
+}
+
+#[test]
+fn test_alpha_beta_remembers_how_far_wins_are() {
+    let game = nim(20, 3);
+    let mut agent = alpha_beta(&game, no_evaluation, SearchBudget::Iterations(20));
+    agent.calculate_next_move(pile(11));
+    agent.calculate_next_move(pile(7));
+
+    // Leaving 4, the agent takes the last stone on its next move
+    assert_eq!(agent.last_search().unwrap().score, WIN_SCORE - 3);
+}
+
+#[test]
+fn test_alpha_beta_beats_random_agent() {
+    let game = nim(21, 3);
+    for seed in 0..5 {
+        let mut agents: IndexMap<NimPlayerId, Box<dyn Agent<Game = NimGameLogic>>> = [
+            (NimPlayerId(1), Box::new(alpha_beta(&game, no_evaluation, SearchBudget::Iterations(8))) as Box<dyn Agent<Game = NimGameLogic>>),
+            (NimPlayerId(2), Box::new(NimRandomAgent::with_seed(3, seed))),
+        ]
+        .into();
+
+        let summary = simulate_game_with(&*game, &mut agents, &SimulationConfig::default());
+        assert_eq!(summary.outcome.unwrap().winners(), [NimPlayerId(1)], "seed {}", seed);
+    }
+}
+
+/// Tries the winning move first, or last.
+struct Ordered {
+    good_first: bool,
+}
+
+impl Evaluator<NimGameLogic> for Ordered {
+    fn evaluate(&self, _game: &NimGameLogic, _state: &NimState, _player: NimPlayerId) -> i32 {
+        0
+    }
+
+    fn move_priority(&self, _game: &NimGameLogic, state: &NimState, _player: NimPlayerId, player_move: &NimMove) -> i32 {
+        let good = (state.pile_size - player_move.amount) % 4 == 0;
+        if good == self.good_first { 1 } else { 0 }
+    }
+}
+
+#[test]
+fn test_alpha_beta_move_ordering_prunes_more() {
+    let game = nim(30, 3);
+    let nodes = |good_first| {
+        let mut agent = alpha_beta(&game, Ordered { good_first }, SearchBudget::Iterations(30));
+        agent.calculate_next_move(pile(30));
+        agent.last_search().unwrap().nodes
+    };
+
+    assert!(nodes(true) < nodes(false), "{} should be less than {}", nodes(true), nodes(false));
+}
+
+#[test]
+fn test_alpha_beta_time_budget_deepens_until_time_is_up() {
+    // Scoring the pile keeps the search from ever seeing the end of the game
+    let game = nim(100_000, 5);
+    let evaluate = |_game: &NimGameLogic, state: &NimState, _player: NimPlayerId| (state.pile_size % 6) as i32;
+    let mut agent = alpha_beta(&game, evaluate, SearchBudget::Time(Duration::from_millis(50)));
+
+    let start = Instant::now();
+    let amount = agent.calculate_next_move(pile(100_000)).amount;
+    assert!((1..=5).contains(&amount));
+    assert!(start.elapsed() < Duration::from_millis(500), "took {:?}", start.elapsed());
+    assert!(agent.last_search().unwrap().depth > 1);
+}
+
+/// Each player gets a hidden card from 1 to 6. The first player bets or folds, then the second
+/// player calls or folds. The first player folding loses, the second player folding is a draw,
+/// and a call is won by the higher card, so calling only pays with a card above 3.
+struct HighCard;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Action {
+    Bet,
+    Call,
+    Fold,
+}
+
+#[derive(Clone)]
+struct Table {
+    players: Vec<NimPlayerId>,
+    cards: HashMap<NimPlayerId, u32>,
+    actions: Vec<Action>,
+}
+
+#[derive(Clone)]
+struct Hand {
+    players: Vec<NimPlayerId>,
+    card: u32,
+    actions: Vec<Action>,
+}
+
+impl GameLogic for HighCard {
+    type PID = NimPlayerId;
+    type Move = Action;
+    type State = Table;
+    type MaskedState = Hand;
+
+    fn init(&self, players: Vec<NimPlayerId>) -> (Table, HashSet<NimPlayerId>) {
+        self.init_seeded(players, rand::rng().random())
+    }
+
+    fn init_seeded(&self, players: Vec<NimPlayerId>, seed: u64) -> (Table, HashSet<NimPlayerId>) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let first = rng.random_range(1..=6);
+        let second = (first + rng.random_range(0..5)) % 6 + 1;
+        let cards = HashMap::from([(players[0], first), (players[1], second)]);
+        let active = HashSet::from([players[0]]);
+        (Table { players, cards, actions: Vec::new() }, active)
+    }
+
+    fn apply_moves(&self, state: &mut Table, moves: HashMap<NimPlayerId, Action>) -> Result<MoveResult<NimPlayerId>, GameError<NimPlayerId>> {
+        let (&player, &action) = moves.iter().next().unwrap();
+        let [first, second] = [state.players[0], state.players[1]];
+        state.actions.push(action);
+        Ok(match state.actions.as_slice() {
+            [Action::Bet] => MoveResult::Continue(HashSet::from([second])),
+            [Action::Bet, Action::Call] => {
+                let winner = if state.cards[&first] > state.cards[&second] { first } else { second };
+                MoveResult::Finished(Outcome::win(winner, &state.players))
+            }
+            [Action::Fold] => MoveResult::Finished(Outcome::forfeit(player, 0, &state.players)),
+            [Action::Bet, Action::Fold] => MoveResult::Finished(Outcome::draw(&state.players)),
+            _ => return Err(GameError::InvalidMove { player, reason: format!("{:?} is out of turn", action) }),
+        })
+    }
+
+    fn mask_state(&self, state: &Table, player: NimPlayerId) -> Hand {
+        Hand {
+            players: state.players.clone(),
+            card: state.cards[&player],
+            actions: state.actions.clone(),
+        }
+    }
+}
+
+impl LegalMoves for HighCard {
+    fn legal_moves(&self, state: &Hand, _player: NimPlayerId) -> Vec<Action> {
+        match state.actions.len() {
+            0 => vec![Action::Bet, Action::Fold],
+            _ => vec![Action::Call, Action::Fold],
+        }
+    }
+}
+
+impl Determinize for HighCard {
+    fn determinize(&self, state: &Hand, player: NimPlayerId, rng: &mut StdRng) -> (Table, HashSet<NimPlayerId>) {
+        // The other card is any card but ours
+        let other = (state.card + rng.random_range(0..5)) % 6 + 1;
+        let opponent = *state.players.iter().find(|&&pid| pid != player).unwrap();
+        let table = Table {
+            players: state.players.clone(),
+            cards: HashMap::from([(player, state.card), (opponent, other)]),
+            actions: state.actions.clone(),
+        };
+        (table, HashSet::from([player]))
+    }
+}
+
+fn hand(card: u32, actions: &[Action]) -> Hand {
+    Hand {
+        players: vec![NimPlayerId(1), NimPlayerId(2)],
+        card,
+        actions: actions.to_vec(),
+    }
+}
+
+fn hidden_info_config(seed: u64) -> MctsConfig<HighCard> {
+    MctsConfig {
+        budget: SearchBudget::Iterations(400),
+        seed: Some(seed),
+        ..MctsConfig::default()
+    }
+}
+
+/// Asks the second player whether to call a bet, holding each card.
+fn calls<A: Agent<Game = HighCard>>(agent: &mut A) -> Vec<bool> {
+    agent.on_game_start(NimPlayerId(2), &[NimPlayerId(1), NimPlayerId(2)]);
+    (1..=6)
+        .map(|card| agent.calculate_next_move(hand(card, &[Action::Bet])) == Action::Call)
+        .collect()
+}
+
+#[test]
+fn test_ismcts_calls_with_good_cards_only() {
+    let mut agent = IsmctsAgent::new(Arc::new(HighCard), hidden_info_config(11));
+
+    // Calling with a card of 4 or more wins more often than folding
+    assert_eq!(calls(&mut agent), vec![false, false, false, true, true, true]);
+}
+
+#[test]
+fn test_pimc_calls_with_good_cards_only() {
+    let mut agent = PimcAgent::new(Arc::new(HighCard), 20, hidden_info_config(12));
+
+    assert_eq!(calls(&mut agent), vec![false, false, false, true, true, true]);
+}
+
+#[test]
+fn test_ismcts_plays_whole_games() {
+    let game = HighCard;
+    for seed in 0..10 {
+        let mut agents: IndexMap<NimPlayerId, IsmctsAgent<HighCard>> = [
+            (NimPlayerId(1), IsmctsAgent::new(Arc::new(HighCard), hidden_info_config(seed))),
+            (NimPlayerId(2), IsmctsAgent::new(Arc::new(HighCard), hidden_info_config(seed + 100))),
+        ]
+        .into();
+        let config = SimulationConfig {
+            seed: Some(seed),
+            ..SimulationConfig::default()
+        };
+
+        let summary = simulate_game_with(&game, &mut agents, &config);
+        assert_eq!(summary.turns, 2, "The first player should always bet, seed {}", seed);
+    }
+}
+
+fn transcript<R>(agent: &HumanAgent<NimGameLogic, R, Vec<u8>>) -> String {
+    String::from_utf8(agent.output().clone()).unwrap()
+}
+
+#[test]
+fn test_human_agent_lists_moves_and_reprompts() {
+    let mut agent = HumanAgent::new("oops\n\n2\n".as_bytes(), Vec::new()).with_legal_moves(nim(10, 3));
+    agent.on_game_start(NimPlayerId(1), &[NimPlayerId(1), NimPlayerId(2)]);
+
+    assert_eq!(agent.calculate_next_move(pile(10)).amount, 2);
+    let output = transcript(&agent);
+    assert!(output.contains("Matches left: 10\n"));
+    assert!(output.contains("  1) take 1\n  2) take 2\n  3) take 3\n"));
+    assert!(output.contains("Couldn't read \"oops\" as a move"));
+
+    // Only the lines that changed are shown after the first state
+    agent.digest_state(pile(7));
+    assert!(transcript(&agent).ends_with("- Matches left: 10\n+ Matches left: 7\n"));
+}
+
+#[test]
+fn test_human_agent_parses_moves_without_legal_moves() {
+    let mut agent: HumanAgent<NimGameLogic, _, _> = HumanAgent::new("take 3\n".as_bytes(), Vec::new());
+    agent.on_game_start(NimPlayerId(1), &[NimPlayerId(1), NimPlayerId(2)]);
+
+    assert_eq!(agent.calculate_next_move(pile(10)).amount, 3);
+    assert!(!transcript(&agent).contains("Your moves:"));
+}
+
+#[test]
+fn test_human_agent_plays_a_game_with_retries() {
+    let game = nim(5, 3);
+    // Taking 4 is rejected, then the human leaves 4, the perfect agent takes 1 and the human
+    // takes the last 3
+    let human = HumanAgent::new("4\n1\n3\n".as_bytes(), Vec::new()).with_legal_moves(Arc::clone(&game));
+    let mut agents: IndexMap<NimPlayerId, Box<dyn Agent<Game = NimGameLogic>>> = [
+        (NimPlayerId(1), Box::new(human) as Box<dyn Agent<Game = NimGameLogic>>),
+        (NimPlayerId(2), Box::new(NimPerfectAgent::new(&game))),
+    ]
+    .into();
+    let config = SimulationConfig {
+        illegal_move_policy: IllegalMovePolicy::retry(3),
+        ..SimulationConfig::default()
+    };
+
+    let summary = simulate_game_with(&*game, &mut agents, &config);
+    assert_eq!(summary.result.unwrap()[&NimPlayerId(1)], 1);
+}
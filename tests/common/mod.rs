@@ -2,12 +2,17 @@
 // This module can be extended with other game implementations in the future
 
 pub mod nim {
+    // Every test binary compiles the fixtures, and each one only uses some of them
+    #[allow(dead_code)]
     #[path = "../../../examples/nim/game.rs"]
     pub mod game;
 
+    #[allow(dead_code)]
     #[path = "../../../examples/nim/agents.rs"]
     pub mod agents;
 
+    #[allow(unused_imports)]
     pub use game::{NimGameLogic, NimMove, NimPlayerId, NimState};
+    #[allow(unused_imports)]
     pub use agents::{NimPerfectAgent, NimRandomAgent, PerfectFactory, RandomFactory};
 }
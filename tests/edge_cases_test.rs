@@ -98,8 +98,10 @@ fn test_exact_match_wins() {
     );
 
     match result {
-        Ok(MoveResult::GameOver(scores)) => {
-            assert_eq!(scores[&NimPlayerId(1)], 1, "Player should win by taking last match");
+        Ok(MoveResult::Finished(outcome)) => {
+            assert_eq!(outcome.winners(), [NimPlayerId(1)], "Player should win by taking last match");
+            assert_eq!(outcome.score_of(NimPlayerId(1)), 1);
+            assert_eq!(outcome.score_of(NimPlayerId(2)), 0);
         }
         _ => panic!("Should be game over with a win"),
     }
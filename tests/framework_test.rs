@@ -4,8 +4,22 @@
 mod common;
 
 use indexmap::IndexMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::Duration;
+
+use game_logic::core::{Agent, AgentFuture, AsyncAgent, FinalScores, GameError, GameLogic, MoveResult, Termination};
 use game_logic::simulate_game;
-use common::nim::{NimGameLogic, NimPerfectAgent, NimPlayerId};
+use game_logic::simulation::{simulate_game_with, SimulationConfig, SimulationError, TimeControl, TimeoutPolicy};
+use game_logic::simulation::DeadlineAgent;
+use game_logic::simulation::{GameRecord, IllegalMovePolicy};
+use game_logic::simulation::{block_on, simulate_game_async, simulate_game_async_with, simulate_game_parallel, AsyncAdapter, BlockingAdapter};
+use game_logic::simulation::{simulate_game_recorded, simulate_game_with_observers, GameRecorder, GameSession, Observer, Replay};
+use common::nim::{NimGameLogic, NimMove, NimPerfectAgent, NimPlayerId, NimState};
 
 #[test]
 fn test_perfect_agent_wins_from_losing_position() {
@@ -35,9 +49,9 @@ fn test_perfect_agent_wins_from_losing_position() {
                 max_takes
             );
             assert_eq!(
-                result.get(&NimPlayerId(1)),
-                None,
-                "Player 1 should not be in results (didn't win)"
+                result[&NimPlayerId(1)],
+                0,
+                "Player 1 should score 0 (didn't win)"
             );
         }
     }
@@ -93,12 +107,12 @@ fn test_simulate_game_returns_correct_result_format() {
 
     let result = simulate_game(&game, &mut agents, None).expect("Game should complete");
 
-    // Result should contain exactly one winner with score 1
-    assert_eq!(result.len(), 1, "Result should contain exactly one entry");
+    // Result should contain both players, exactly one winner with score 1
+    assert_eq!(result.len(), 2, "Result should contain every player");
     assert_eq!(
-        *result.values().next().unwrap(),
+        result.values().filter(|&&score| score == 1).count(),
         1,
-        "Winner should have score 1"
+        "Exactly one winner should have score 1"
     );
 }
 
@@ -119,7 +133,7 @@ fn test_game_terminates_in_reasonable_time() {
     // If this hangs, the test will timeout
     let result = simulate_game(&game, &mut agents, Some(1000)).expect("Game should complete");
 
-    assert!(result.len() > 0, "Game should produce a result");
+    assert!(!result.is_empty(), "Game should produce a result");
 }
 
 #[test]
@@ -138,3 +152,1043 @@ fn test_agents_initialized_correctly() {
 
     assert_eq!(agents.len(), 2, "Should have 2 agents");
 }
+
+#[test]
+fn test_simulate_game_with_summarizes_the_game() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+
+    let mut agents: IndexMap<NimPlayerId, NimPerfectAgent> = [
+        (NimPlayerId(1), NimPerfectAgent::new(&game)),
+        (NimPlayerId(2), NimPerfectAgent::new(&game)),
+    ]
+    .into();
+
+    let summary = simulate_game_with(&game, &mut agents, &SimulationConfig::default());
+
+    // 10 -> 8 -> 7 -> 4 -> 3 -> 0
+    assert_eq!(summary.turns, 5);
+    assert_eq!(summary.result.expect("Game should complete")[&NimPlayerId(1)], 1);
+}
+
+#[test]
+fn test_simulate_game_with_reports_turn_limit() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+
+    let mut agents: IndexMap<NimPlayerId, NimPerfectAgent> = [
+        (NimPlayerId(1), NimPerfectAgent::new(&game)),
+        (NimPlayerId(2), NimPerfectAgent::new(&game)),
+    ]
+    .into();
+
+    let config = SimulationConfig {
+        max_turns: Some(3),
+        ..SimulationConfig::default()
+    };
+    let summary = simulate_game_with(&game, &mut agents, &config);
+
+    assert_eq!(summary.turns, 3);
+    assert!(matches!(summary.result, Err(SimulationError::MaxTurnsExceeded(3))));
+}
+
+/// Nim whose rules panic once the pile gets low.
+struct PanickyNim(NimGameLogic);
+
+impl GameLogic for PanickyNim {
+    type PID = NimPlayerId;
+    type Move = NimMove;
+    type State = NimState;
+    type MaskedState = NimState;
+
+    fn init(&self, players: Vec<NimPlayerId>) -> (NimState, HashSet<NimPlayerId>) {
+        self.0.init(players)
+    }
+
+    fn apply_moves(
+        &self,
+        state: &mut NimState,
+        moves: HashMap<NimPlayerId, NimMove>,
+    ) -> Result<MoveResult<NimPlayerId>, GameError<NimPlayerId>> {
+        let result = self.0.apply_moves(state, moves);
+        if state.pile_size < 5 {
+            panic!("pile too small: {}", state.pile_size);
+        }
+        result
+    }
+
+    fn mask_state(&self, state: &NimState, player: NimPlayerId) -> NimState {
+        self.0.mask_state(state, player)
+    }
+}
+
+/// An agent that always takes a single match.
+struct TakeOneAgent;
+
+impl Agent for TakeOneAgent {
+    type Game = PanickyNim;
+
+    fn digest_state(&mut self, _new_state: NimState) {}
+
+    fn calculate_next_move(&mut self, _new_state: NimState) -> NimMove {
+        NimMove { amount: 1 }
+    }
+}
+
+#[test]
+fn test_simulate_game_catches_game_logic_panics() {
+    let game = PanickyNim(NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    });
+
+    let mut agents: IndexMap<NimPlayerId, TakeOneAgent> =
+        [(NimPlayerId(1), TakeOneAgent), (NimPlayerId(2), TakeOneAgent)].into();
+
+    let summary = simulate_game_with(&game, &mut agents, &SimulationConfig::default());
+
+    // 10 -> 9 -> ... -> 4 panics
+    assert_eq!(summary.turns, 6);
+    match summary.result {
+        Err(SimulationError::GamePanicked(message)) => assert_eq!(message, "pile too small: 4"),
+        result => panic!("Unexpected result: {:?}", result),
+    }
+}
+
+/// A Nim agent that takes a single match after thinking for a while.
+struct SlowAgent(Duration);
+
+impl Agent for SlowAgent {
+    type Game = NimGameLogic;
+
+    fn digest_state(&mut self, _new_state: NimState) {}
+
+    fn calculate_next_move(&mut self, _new_state: NimState) -> NimMove {
+        thread::sleep(self.0);
+        NimMove { amount: 1 }
+    }
+}
+
+/// Seats a slow agent first against a perfect agent.
+fn slow_against_perfect(game: &NimGameLogic, delay: Duration) -> IndexMap<NimPlayerId, Box<dyn Agent<Game = NimGameLogic>>> {
+    let mut agents: IndexMap<NimPlayerId, Box<dyn Agent<Game = NimGameLogic>>> = IndexMap::new();
+    agents.insert(NimPlayerId(1), Box::new(SlowAgent(delay)));
+    agents.insert(NimPlayerId(2), Box::new(NimPerfectAgent::new(game)));
+    agents
+}
+
+#[test]
+fn test_per_move_timeout_is_an_error() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let mut agents = slow_against_perfect(&game, Duration::from_millis(30));
+
+    let config = SimulationConfig {
+        time_control: Some(TimeControl::PerMove(Duration::from_millis(10))),
+        ..SimulationConfig::default()
+    };
+    let summary = simulate_game_with(&game, &mut agents, &config);
+
+    assert_eq!(summary.turns, 1);
+    match summary.result {
+        Err(SimulationError::TimedOut { player, elapsed }) => {
+            assert_eq!(player, NimPlayerId(1));
+            assert!(elapsed >= Duration::from_millis(30));
+        }
+        result => panic!("Unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn test_timeout_default_move_replaces_the_agents_move() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let mut agents = slow_against_perfect(&game, Duration::from_millis(30));
+
+    // The default move plays perfectly, so the slow agent wins from its winning position
+    let config = SimulationConfig {
+        time_control: Some(TimeControl::PerMove(Duration::from_millis(10))),
+        timeout_policy: TimeoutPolicy::default_move(|state: &NimState, _| match state.pile_size % 4 {
+            0 => NimMove { amount: 1 },
+            x => NimMove { amount: x },
+        }),
+        ..SimulationConfig::default()
+    };
+    let result = simulate_game_with(&game, &mut agents, &config).result;
+
+    assert_eq!(result.expect("Game should complete")[&NimPlayerId(1)], 1);
+}
+
+#[test]
+fn test_clock_increment_keeps_agents_in_time() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let mut agents = slow_against_perfect(&game, Duration::from_millis(20));
+
+    let config = SimulationConfig {
+        time_control: Some(TimeControl::Clock {
+            initial: Duration::from_millis(50),
+            increment: Duration::from_millis(40),
+        }),
+        ..SimulationConfig::default()
+    };
+    let result = simulate_game_with(&game, &mut agents, &config).result;
+
+    assert_eq!(result.expect("Game should complete")[&NimPlayerId(2)], 1);
+}
+
+#[test]
+fn test_per_game_budget_forfeit() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let mut agents = slow_against_perfect(&game, Duration::from_millis(20));
+
+    // The budget runs out on the slow agent's third move at the latest
+    let config = SimulationConfig {
+        time_control: Some(TimeControl::PerGame(Duration::from_millis(50))),
+        timeout_policy: TimeoutPolicy::Forfeit { penalty: -1 },
+        ..SimulationConfig::default()
+    };
+    let summary = simulate_game_with(&game, &mut agents, &config);
+
+    assert!(summary.turns <= 5);
+    let result = summary.result.expect("A forfeit ends the game normally");
+    assert_eq!(result, HashMap::from([(NimPlayerId(1), -1), (NimPlayerId(2), 0)]));
+    let outcome = summary.outcome.expect("A forfeit has an outcome");
+    assert_eq!(outcome.termination, Termination::Forfeit(NimPlayerId(1)));
+    assert_eq!(outcome.winners(), [NimPlayerId(2)]);
+}
+
+/// A Nim agent that never makes up its mind.
+struct StuckAgent;
+
+impl Agent for StuckAgent {
+    type Game = NimGameLogic;
+
+    fn digest_state(&mut self, _new_state: NimState) {}
+
+    fn calculate_next_move(&mut self, _new_state: NimState) -> NimMove {
+        loop {
+            thread::sleep(Duration::from_secs(3600));
+        }
+    }
+}
+
+/// Seats a stuck agent first against a perfect agent, both on their own threads.
+fn stuck_against_perfect(game: &NimGameLogic) -> IndexMap<NimPlayerId, DeadlineAgent<NimGameLogic>> {
+    let mut agents = IndexMap::new();
+    agents.insert(NimPlayerId(1), DeadlineAgent::new(StuckAgent));
+    agents.insert(NimPlayerId(2), DeadlineAgent::new(NimPerfectAgent::new(game)));
+    agents
+}
+
+#[test]
+fn test_deadline_agent_that_never_moves_forfeits() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let mut agents = stuck_against_perfect(&game);
+
+    let config = SimulationConfig {
+        time_control: Some(TimeControl::PerMove(Duration::from_millis(20))),
+        timeout_policy: TimeoutPolicy::Forfeit { penalty: -1 },
+        ..SimulationConfig::default()
+    };
+    let summary = simulate_game_with(&game, &mut agents, &config);
+
+    assert_eq!(summary.turns, 1);
+    assert_eq!(summary.result.expect("A forfeit ends the game normally")[&NimPlayerId(1)], -1);
+    assert!(agents[&NimPlayerId(1)].is_abandoned());
+    assert!(!agents[&NimPlayerId(2)].is_abandoned());
+}
+
+#[test]
+fn test_abandoned_deadline_agent_plays_default_moves() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let mut agents = stuck_against_perfect(&game);
+
+    // Only the first move is waited for, the abandoned agent's later moves time out at once
+    let config = SimulationConfig {
+        time_control: Some(TimeControl::Clock {
+            initial: Duration::from_millis(20),
+            increment: Duration::from_secs(60),
+        }),
+        timeout_policy: TimeoutPolicy::default_move(|state: &NimState, _| match state.pile_size % 4 {
+            0 => NimMove { amount: 1 },
+            x => NimMove { amount: x },
+        }),
+        ..SimulationConfig::default()
+    };
+    let summary = simulate_game_with(&game, &mut agents, &config);
+
+    assert!(summary.duration < Duration::from_secs(10));
+    assert_eq!(summary.result.expect("Game should complete")[&NimPlayerId(1)], 1);
+}
+
+#[test]
+fn test_recorded_game_replays_to_the_same_result() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+
+    let mut agents: IndexMap<NimPlayerId, NimPerfectAgent> = [
+        (NimPlayerId(1), NimPerfectAgent::new(&game)),
+        (NimPlayerId(2), NimPerfectAgent::new(&game)),
+    ]
+    .into();
+
+    let (summary, record) = simulate_game_recorded(&game, &mut agents, &SimulationConfig::default());
+
+    assert_eq!(record.players, vec![NimPlayerId(1), NimPlayerId(2)]);
+    assert_eq!(record.turns.len(), summary.turns);
+    assert_eq!(record.turns[0].active_players, HashSet::from([NimPlayerId(1)]));
+    assert_eq!(record.turns[1].active_players, HashSet::from([NimPlayerId(2)]));
+    assert_eq!(record.turns[0].moves[&NimPlayerId(1)].amount, 2);
+    assert_eq!(record.final_result, Some(MoveResult::Finished(summary.outcome.unwrap())));
+
+    // 10 -> 8 -> 7 -> 4 -> 3 -> 0
+    let mut replay = Replay::new(&game, &record);
+    let mut piles = vec![replay.state().pile_size];
+    while let Some(result) = replay.step() {
+        result.expect("Recorded moves are valid");
+        piles.push(replay.state().pile_size);
+    }
+    assert_eq!(piles, vec![10, 8, 7, 4, 3, 0]);
+    assert_eq!(replay.last_result(), record.final_result.as_ref());
+    assert!(replay.is_finished());
+}
+
+#[test]
+fn test_replay_seeks_in_both_directions() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+
+    let mut agents: IndexMap<NimPlayerId, NimPerfectAgent> = [
+        (NimPlayerId(1), NimPerfectAgent::new(&game)),
+        (NimPlayerId(2), NimPerfectAgent::new(&game)),
+    ]
+    .into();
+    let (_, record) = simulate_game_recorded(&game, &mut agents, &SimulationConfig::default());
+    let mut replay = Replay::new(&game, &record);
+
+    replay.seek(3).unwrap();
+    assert_eq!(replay.turn(), 3);
+    assert_eq!(replay.masked_state(NimPlayerId(2)).pile_size, 4);
+    assert_eq!(replay.active_players(), &HashSet::from([NimPlayerId(2)]));
+
+    replay.seek(1).unwrap();
+    assert_eq!(replay.state().pile_size, 8);
+    assert_eq!(replay.next_moves().unwrap()[&NimPlayerId(2)].amount, 1);
+
+    replay.seek(0).unwrap();
+    assert_eq!(replay.state().pile_size, 10);
+    assert_eq!(replay.last_result(), None);
+}
+
+#[test]
+fn test_replay_reports_invalid_recorded_moves() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+
+    let mut agents: IndexMap<NimPlayerId, Box<dyn Agent<Game = NimGameLogic>>> = IndexMap::new();
+    agents.insert(NimPlayerId(1), Box::new(SlowAgent(Duration::ZERO)));
+    agents.insert(NimPlayerId(2), Box::new(NimPerfectAgent::new(&game)));
+    let (summary, mut record) = simulate_game_recorded(&game, &mut agents, &SimulationConfig::default());
+    assert!(summary.result.is_ok());
+
+    // Tamper with the last turn so that it takes more matches than are left
+    record.turns.last_mut().unwrap().moves.values_mut().for_each(|m| m.amount = 4);
+
+    let mut replay = Replay::new(&game, &record);
+    let last = replay.len() - 1;
+    replay.seek(last).unwrap();
+    assert!(matches!(replay.step(), Some(Err(GameError::InvalidMove { .. }))));
+    assert!(replay.step().is_none());
+}
+
+/// Logs every event of a Nim game as a line of text.
+#[derive(Default)]
+struct EventLog(Vec<String>);
+
+impl Observer<NimGameLogic> for EventLog {
+    fn on_game_start(&mut self, players: &[NimPlayerId], _seed: Option<u64>, state: &NimState, _active_players: &HashSet<NimPlayerId>) {
+        self.0.push(format!("start {} players, pile {}", players.len(), state.pile_size));
+    }
+
+    fn on_turn_start(&mut self, turn: usize, _state: &NimState, active_players: &HashSet<NimPlayerId>) {
+        let player = active_players.iter().next().unwrap();
+        self.0.push(format!("turn {}: player {}", turn, player.0));
+    }
+
+    fn on_moves(&mut self, turn: usize, moves: &HashMap<NimPlayerId, NimMove>) {
+        let amount = moves.values().next().unwrap().amount;
+        self.0.push(format!("turn {}: takes {}", turn, amount));
+    }
+
+    fn on_moves_applied(&mut self, turn: usize, state: &NimState, _result: &MoveResult<NimPlayerId>) {
+        self.0.push(format!("turn {}: pile {}", turn, state.pile_size));
+    }
+
+    fn on_error(&mut self, error: &SimulationError<NimPlayerId>) {
+        self.0.push(format!("error: {}", error));
+    }
+
+    fn on_game_over(&mut self, scores: &FinalScores<NimPlayerId>) {
+        let winners = scores.values().filter(|&&score| score > 0).count();
+        self.0.push(format!("game over: {} winner(s)", winners));
+    }
+}
+
+#[test]
+fn test_observers_see_every_event_in_order() {
+    let game = NimGameLogic {
+        initial_pile_size: 4,
+        max_takes: 3,
+    };
+
+    let mut agents: IndexMap<NimPlayerId, NimPerfectAgent> = [
+        (NimPlayerId(1), NimPerfectAgent::new(&game)),
+        (NimPlayerId(2), NimPerfectAgent::new(&game)),
+    ]
+    .into();
+
+    let mut log = EventLog::default();
+    let mut recorder = GameRecorder::new();
+    simulate_game_with_observers(&game, &mut agents, &SimulationConfig::default(), &mut [&mut log, &mut recorder]);
+
+    assert_eq!(
+        log.0,
+        vec![
+            "start 2 players, pile 4",
+            "turn 1: player 1",
+            "turn 1: takes 1",
+            "turn 1: pile 3",
+            "turn 2: player 2",
+            "turn 2: takes 3",
+            "turn 2: pile 0",
+            "game over: 1 winner(s)",
+        ]
+    );
+    assert_eq!(recorder.record().turns.len(), 2, "Every observer sees the game");
+}
+
+#[test]
+fn test_observers_see_errors() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+
+    let mut agents: IndexMap<NimPlayerId, NimPerfectAgent> = [
+        (NimPlayerId(1), NimPerfectAgent::new(&game)),
+        (NimPlayerId(2), NimPerfectAgent::new(&game)),
+    ]
+    .into();
+
+    let config = SimulationConfig {
+        max_turns: Some(1),
+        ..SimulationConfig::default()
+    };
+    let mut log = EventLog::default();
+    simulate_game_with_observers(&game, &mut agents, &config, &mut [&mut log]);
+
+    assert_eq!(log.0.last().unwrap(), "error: Game exceeded maximum of 1 turns without ending");
+}
+
+#[test]
+fn test_game_session_step_by_step() {
+    let game = NimGameLogic {
+        initial_pile_size: 5,
+        max_takes: 3,
+    };
+    let mut session = GameSession::new(&game, vec![NimPlayerId(1), NimPlayerId(2)]);
+
+    assert_eq!(session.pending_players(), vec![NimPlayerId(1)]);
+    assert!(matches!(
+        session.submit_move(NimPlayerId(2), NimMove { amount: 1 }),
+        Err(GameError::WrongPlayer { got: NimPlayerId(2), .. })
+    ));
+    assert!(matches!(session.step(), Err(GameError::MissingMoves { .. })));
+    assert_eq!(session.turn(), 0, "Missing moves don't end the turn");
+
+    // A move can be replaced until the turn ends
+    session.submit_move(NimPlayerId(1), NimMove { amount: 3 }).unwrap();
+    session.submit_move(NimPlayerId(1), NimMove { amount: 1 }).unwrap();
+    assert!(session.pending_players().is_empty());
+    assert_eq!(session.step().unwrap(), MoveResult::Continue(HashSet::from([NimPlayerId(2)])));
+    assert_eq!(session.masked_view(NimPlayerId(2)).pile_size, 4);
+
+    session.submit_move(NimPlayerId(2), NimMove { amount: 1 }).unwrap();
+    session.step().unwrap();
+    session.submit_move(NimPlayerId(1), NimMove { amount: 3 }).unwrap();
+    assert!(matches!(session.step().unwrap(), MoveResult::Finished(_)));
+
+    assert_eq!(session.turn(), 3);
+    assert!(session.is_over());
+    assert_eq!(session.outcome().unwrap().as_ref().unwrap()[&NimPlayerId(1)], 1);
+    assert!(session.pending_players().is_empty());
+    assert!(matches!(
+        session.submit_move(NimPlayerId(2), NimMove { amount: 1 }),
+        Err(GameError::IllegalState(_))
+    ));
+}
+
+#[test]
+fn test_game_session_finish_and_invalid_moves() {
+    let game = NimGameLogic {
+        initial_pile_size: 5,
+        max_takes: 3,
+    };
+
+    let mut session = GameSession::new(&game, vec![NimPlayerId(1), NimPlayerId(2)]);
+    session.finish(Ok(HashMap::from([(NimPlayerId(1), -1)])));
+    assert!(session.is_over());
+    assert!(session.step().is_err());
+
+    // Invalid moves end the game, since the state may have been changed
+    let mut session = GameSession::new(&game, vec![NimPlayerId(1), NimPlayerId(2)]);
+    session.submit_move(NimPlayerId(1), NimMove { amount: 0 }).unwrap();
+    assert!(matches!(session.step(), Err(GameError::InvalidMove { .. })));
+    assert!(matches!(session.outcome(), Some(Err(SimulationError::GameError(_)))));
+}
+
+/// Returns `Pending` once before completing, like a future waiting on IO.
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            context.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// Wraps a perfect agent, logging when each request starts and ends.
+struct LoggingAsyncAgent {
+    name: &'static str,
+    inner: NimPerfectAgent,
+    log: Arc<Mutex<Vec<String>>>,
+}
+
+impl AsyncAgent for LoggingAsyncAgent {
+    type Game = NimGameLogic;
+
+    fn digest_state(&mut self, new_state: NimState) -> AgentFuture<'_, ()> {
+        Box::pin(async move {
+            self.log.lock().unwrap().push(format!("{} digest start", self.name));
+            YieldOnce(false).await;
+            self.inner.digest_state(new_state);
+            self.log.lock().unwrap().push(format!("{} digest end", self.name));
+        })
+    }
+
+    fn calculate_next_move(&mut self, new_state: NimState) -> AgentFuture<'_, NimMove> {
+        Box::pin(async move {
+            self.log.lock().unwrap().push(format!("{} move start", self.name));
+            YieldOnce(false).await;
+            let player_move = self.inner.calculate_next_move(new_state);
+            self.log.lock().unwrap().push(format!("{} move end", self.name));
+            player_move
+        })
+    }
+}
+
+#[test]
+fn test_async_agents_are_requested_concurrently() {
+    let game = NimGameLogic {
+        initial_pile_size: 8,
+        max_takes: 3,
+    };
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut agents = IndexMap::new();
+    for (pid, name) in [(NimPlayerId(1), "first"), (NimPlayerId(2), "second")] {
+        let inner = NimPerfectAgent::new(&game);
+        agents.insert(pid, LoggingAsyncAgent { name, inner, log: log.clone() });
+    }
+
+    let result = block_on(simulate_game_async(&game, &mut agents, None)).unwrap();
+    assert_eq!(result[&NimPlayerId(2)], 1, "The second player wins from a multiple of 4");
+
+    // Both agents start before either of them is done
+    assert_eq!(
+        log.lock().unwrap()[..4],
+        ["first move start", "second digest start", "first move end", "second digest end"]
+    );
+}
+
+struct PanickingAsyncAgent;
+
+impl AsyncAgent for PanickingAsyncAgent {
+    type Game = NimGameLogic;
+
+    fn digest_state(&mut self, _new_state: NimState) -> AgentFuture<'_, ()> {
+        Box::pin(async {})
+    }
+
+    fn calculate_next_move(&mut self, _new_state: NimState) -> AgentFuture<'_, NimMove> {
+        Box::pin(async {
+            YieldOnce(false).await;
+            panic!("lost connection")
+        })
+    }
+}
+
+#[test]
+fn test_async_agent_panics_end_the_game() {
+    let game = NimGameLogic {
+        initial_pile_size: 8,
+        max_takes: 3,
+    };
+    let mut agents: IndexMap<NimPlayerId, Box<dyn AsyncAgent<Game = NimGameLogic>>> = IndexMap::new();
+    agents.insert(NimPlayerId(1), Box::new(PanickingAsyncAgent));
+    agents.insert(NimPlayerId(2), Box::new(AsyncAdapter(NimPerfectAgent::new(&game))));
+
+    let result = block_on(simulate_game_async(&game, &mut agents, None));
+    assert!(matches!(
+        result,
+        Err(SimulationError::AgentPanicked { player: NimPlayerId(1), ref message }) if message == "lost connection"
+    ));
+}
+
+/// Never comes up with a move.
+struct PendingAsyncAgent;
+
+impl AsyncAgent for PendingAsyncAgent {
+    type Game = NimGameLogic;
+
+    fn digest_state(&mut self, _new_state: NimState) -> AgentFuture<'_, ()> {
+        Box::pin(async {})
+    }
+
+    fn calculate_next_move(&mut self, _new_state: NimState) -> AgentFuture<'_, NimMove> {
+        Box::pin(std::future::pending())
+    }
+}
+
+#[test]
+fn test_async_agents_that_never_move_run_out_of_time() {
+    let game = NimGameLogic {
+        initial_pile_size: 8,
+        max_takes: 3,
+    };
+    let mut agents: IndexMap<NimPlayerId, Box<dyn AsyncAgent<Game = NimGameLogic>>> = IndexMap::new();
+    agents.insert(NimPlayerId(1), Box::new(PendingAsyncAgent));
+    agents.insert(NimPlayerId(2), Box::new(AsyncAdapter(NimPerfectAgent::new(&game))));
+
+    let config = SimulationConfig {
+        time_control: Some(TimeControl::PerMove(Duration::from_millis(20))),
+        timeout_policy: TimeoutPolicy::Forfeit { penalty: -1 },
+        ..SimulationConfig::default()
+    };
+    let summary = block_on(simulate_game_async_with(&game, &mut agents, &config));
+
+    assert_eq!(summary.turns, 1);
+    assert_eq!(summary.result.expect("A forfeit ends the game normally")[&NimPlayerId(1)], -1);
+}
+
+#[test]
+fn test_agent_adapters_play_like_the_wrapped_agent() {
+    let game = NimGameLogic {
+        initial_pile_size: 13,
+        max_takes: 3,
+    };
+
+    let mut sync_agents: IndexMap<NimPlayerId, BlockingAdapter<AsyncAdapter<NimPerfectAgent>>> = IndexMap::new();
+    let mut async_agents: IndexMap<NimPlayerId, AsyncAdapter<NimPerfectAgent>> = IndexMap::new();
+    for pid in [NimPlayerId(1), NimPlayerId(2)] {
+        sync_agents.insert(pid, BlockingAdapter(AsyncAdapter(NimPerfectAgent::new(&game))));
+        async_agents.insert(pid, AsyncAdapter(NimPerfectAgent::new(&game)));
+    }
+
+    let sync_result = simulate_game(&game, &mut sync_agents, None).unwrap();
+    let async_result = block_on(simulate_game_async(&game, &mut async_agents, None)).unwrap();
+    assert_eq!(sync_result, async_result);
+    assert_eq!(sync_result[&NimPlayerId(1)], 1, "The first player wins from 13");
+}
+
+/// A simultaneous-move game: every turn, every player adds to their score at the same time.
+struct Race {
+    rounds: usize,
+}
+
+impl GameLogic for Race {
+    type PID = NimPlayerId;
+    type Move = NimMove;
+    type State = (usize, HashMap<NimPlayerId, i32>);
+    type MaskedState = usize;
+
+    fn init(&self, players: Vec<NimPlayerId>) -> (Self::State, HashSet<NimPlayerId>) {
+        let scores = players.iter().map(|&pid| (pid, 0)).collect();
+        ((0, scores), players.into_iter().collect())
+    }
+
+    fn apply_moves(
+        &self,
+        state: &mut Self::State,
+        moves: HashMap<NimPlayerId, NimMove>,
+    ) -> Result<MoveResult<NimPlayerId>, GameError<NimPlayerId>> {
+        let (round, scores) = state;
+        for (pid, player_move) in moves {
+            *scores.get_mut(&pid).unwrap() += player_move.amount as i32;
+        }
+        *round += 1;
+        if *round == self.rounds {
+            Ok(MoveResult::GameOver(scores.clone()))
+        } else {
+            Ok(MoveResult::Continue(scores.keys().copied().collect()))
+        }
+    }
+
+    fn mask_state(&self, state: &Self::State, _player: NimPlayerId) -> usize {
+        state.0
+    }
+}
+
+/// Takes its time, then runs `amount` steps, or panics if `amount` is 0.
+struct Runner {
+    delay: Duration,
+    amount: u32,
+}
+
+impl Agent for Runner {
+    type Game = Race;
+
+    fn digest_state(&mut self, _new_state: usize) {}
+
+    fn calculate_next_move(&mut self, _new_state: usize) -> NimMove {
+        thread::sleep(self.delay);
+        assert!(self.amount > 0, "tripped");
+        NimMove { amount: self.amount }
+    }
+}
+
+fn runners(delay: Duration, amounts: &[u32]) -> IndexMap<NimPlayerId, Runner> {
+    amounts
+        .iter()
+        .enumerate()
+        .map(|(seat, &amount)| (NimPlayerId(seat as u32 + 1), Runner { delay, amount }))
+        .collect()
+}
+
+#[test]
+fn test_parallel_moves_take_as_long_as_the_slowest_agent() {
+    let game = Race { rounds: 3 };
+    let delay = Duration::from_millis(40);
+    let mut agents = runners(delay, &[1, 2, 3, 4]);
+
+    let summary = simulate_game_parallel(&game, &mut agents, &SimulationConfig::default());
+    let scores = summary.result.unwrap();
+    assert_eq!(scores, HashMap::from([(NimPlayerId(1), 3), (NimPlayerId(2), 6), (NimPlayerId(3), 9), (NimPlayerId(4), 12)]));
+    assert_eq!(summary.turns, 3);
+    // Asking the agents one after the other would take 12 delays
+    assert!(summary.duration < delay * 6, "took {:?}", summary.duration);
+}
+
+#[test]
+fn test_parallel_moves_report_errors_in_seat_order() {
+    let game = Race { rounds: 3 };
+
+    // The third player panics first, but the second player comes first at the table
+    let mut agents = runners(Duration::ZERO, &[1, 0, 0]);
+    agents[1].delay = Duration::from_millis(20);
+    let summary = simulate_game_parallel(&game, &mut agents, &SimulationConfig::default());
+    assert!(matches!(
+        summary.result,
+        Err(SimulationError::AgentPanicked { player: NimPlayerId(2), ref message }) if message == "tripped"
+    ));
+    assert_eq!(summary.turns, 1);
+}
+
+/// Plays a fixed amount every turn, logging the lifecycle events it receives.
+struct HookedAgent {
+    amount: u32,
+    log: Arc<Mutex<Vec<String>>>,
+}
+
+impl Agent for HookedAgent {
+    type Game = NimGameLogic;
+
+    fn digest_state(&mut self, _new_state: NimState) {}
+
+    fn calculate_next_move(&mut self, _new_state: NimState) -> NimMove {
+        NimMove { amount: self.amount }
+    }
+
+    fn on_game_start(&mut self, pid: NimPlayerId, players: &[NimPlayerId]) {
+        self.log.lock().unwrap().push(format!("start {:?} of {:?}", pid, players));
+    }
+
+    fn on_game_over(&mut self, scores: &FinalScores<NimPlayerId>) {
+        self.log.lock().unwrap().push(format!("over {:?}", scores.get(&NimPlayerId(2))));
+    }
+
+    fn on_game_aborted(&mut self, error: &SimulationError<NimPlayerId>) {
+        self.log.lock().unwrap().push(format!("aborted {:?}", error.culprit()));
+    }
+
+    fn on_move_rejected(&mut self, error: &GameError<NimPlayerId>) {
+        self.log.lock().unwrap().push(format!("rejected {:?}", error.culprit()));
+    }
+}
+
+fn hooked_agents(amounts: [u32; 2], log: &Arc<Mutex<Vec<String>>>) -> IndexMap<NimPlayerId, Box<dyn Agent<Game = NimGameLogic>>> {
+    let mut agents: IndexMap<NimPlayerId, Box<dyn Agent<Game = NimGameLogic>>> = IndexMap::new();
+    for (pid, amount) in [NimPlayerId(1), NimPlayerId(2)].into_iter().zip(amounts) {
+        agents.insert(pid, Box::new(HookedAgent { amount, log: log.clone() }));
+    }
+    agents
+}
+
+#[test]
+fn test_agents_hear_about_the_start_and_end_of_the_game() {
+    let game = NimGameLogic {
+        initial_pile_size: 4,
+        max_takes: 3,
+    };
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut agents = hooked_agents([3, 1], &log);
+
+    simulate_game(&game, &mut agents, None).unwrap();
+    assert_eq!(
+        *log.lock().unwrap(),
+        [
+            "start NimPlayerId(1) of [NimPlayerId(1), NimPlayerId(2)]",
+            "start NimPlayerId(2) of [NimPlayerId(1), NimPlayerId(2)]",
+            "over Some(1)",
+            "over Some(1)",
+        ]
+    );
+}
+
+#[test]
+fn test_agents_hear_about_rejected_moves() {
+    let game = NimGameLogic {
+        initial_pile_size: 4,
+        max_takes: 3,
+    };
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut agents = hooked_agents([1, 0], &log);
+
+    let result = simulate_game(&game, &mut agents, None);
+    assert!(matches!(result, Err(SimulationError::GameError(GameError::InvalidMove { .. }))));
+    let expected = ["rejected Some(NimPlayerId(2))", "aborted Some(NimPlayerId(2))", "aborted Some(NimPlayerId(2))"];
+    assert_eq!(log.lock().unwrap()[2..], expected, "Only the culprit hears about the rejection");
+
+    // Async agents hear about it too
+    log.lock().unwrap().clear();
+    let mut agents: IndexMap<NimPlayerId, _> = hooked_agents([1, 0], &log).into_iter().map(|(pid, agent)| (pid, AsyncAdapter(agent))).collect();
+    assert!(block_on(simulate_game_async(&game, &mut agents, None)).is_err());
+    assert_eq!(log.lock().unwrap()[2..], expected);
+}
+
+#[test]
+fn test_agents_hear_about_games_ending_with_errors() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut agents = hooked_agents([1, 1], &log);
+
+    let result = simulate_game(&game, &mut agents, Some(2));
+    assert!(matches!(result, Err(SimulationError::MaxTurnsExceeded(2))));
+    assert_eq!(log.lock().unwrap()[2..], ["aborted None", "aborted None"]);
+
+    // The same holds for parallel games
+    log.lock().unwrap().clear();
+    let mut agents: IndexMap<NimPlayerId, HookedAgent> =
+        [1, 2].map(|id| (NimPlayerId(id), HookedAgent { amount: 1, log: log.clone() })).into();
+    let config = SimulationConfig {
+        max_turns: Some(2),
+        ..SimulationConfig::default()
+    };
+    assert!(simulate_game_parallel(&game, &mut agents, &config).result.is_err());
+    assert_eq!(log.lock().unwrap()[2..], ["aborted None", "aborted None"]);
+}
+
+/// Panics when asked for a move, logging if it hears about the end of the game.
+struct PanickingAgent(Arc<Mutex<Vec<String>>>);
+
+impl Agent for PanickingAgent {
+    type Game = NimGameLogic;
+
+    fn digest_state(&mut self, _new_state: NimState) {}
+
+    fn calculate_next_move(&mut self, _new_state: NimState) -> NimMove {
+        panic!("no move");
+    }
+
+    fn on_game_aborted(&mut self, _error: &SimulationError<NimPlayerId>) {
+        self.0.lock().unwrap().push("panicked agent aborted".to_string());
+    }
+}
+
+#[test]
+fn test_agents_that_panicked_do_not_hear_about_the_end() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut agents: IndexMap<NimPlayerId, Box<dyn Agent<Game = NimGameLogic>>> = IndexMap::new();
+    agents.insert(NimPlayerId(1), Box::new(HookedAgent { amount: 1, log: log.clone() }));
+    agents.insert(NimPlayerId(2), Box::new(PanickingAgent(log.clone())));
+
+    let result = simulate_game(&game, &mut agents, None);
+    assert!(matches!(result, Err(SimulationError::AgentPanicked { player: NimPlayerId(2), .. })));
+    assert_eq!(log.lock().unwrap()[1..], ["aborted Some(NimPlayerId(2))"]);
+}
+
+/// Plays the given amounts in order, then takes a single match. Counts its rejected moves.
+struct ScriptedAgent {
+    amounts: VecDeque<u32>,
+    rejections: usize,
+}
+
+impl ScriptedAgent {
+    fn new(amounts: &[u32]) -> Self {
+        ScriptedAgent {
+            amounts: amounts.iter().copied().collect(),
+            rejections: 0,
+        }
+    }
+}
+
+impl Agent for ScriptedAgent {
+    type Game = NimGameLogic;
+
+    fn digest_state(&mut self, _new_state: NimState) {}
+
+    fn calculate_next_move(&mut self, _new_state: NimState) -> NimMove {
+        NimMove { amount: self.amounts.pop_front().unwrap_or(1) }
+    }
+
+    fn on_move_rejected(&mut self, _error: &GameError<NimPlayerId>) {
+        self.rejections += 1;
+    }
+}
+
+fn scripted_agents(first: &[u32], second: &[u32]) -> IndexMap<NimPlayerId, ScriptedAgent> {
+    IndexMap::from([(NimPlayerId(1), ScriptedAgent::new(first)), (NimPlayerId(2), ScriptedAgent::new(second))])
+}
+
+fn illegal_move_config(policy: IllegalMovePolicy<NimGameLogic>) -> SimulationConfig<NimGameLogic> {
+    SimulationConfig {
+        illegal_move_policy: policy,
+        ..SimulationConfig::default()
+    }
+}
+
+#[test]
+fn test_illegal_moves_can_be_retried() {
+    let game = NimGameLogic {
+        initial_pile_size: 4,
+        max_takes: 3,
+    };
+
+    // The second player gets it right on the third try
+    let mut agents = scripted_agents(&[1], &[0, 7, 3]);
+    let config = illegal_move_config(IllegalMovePolicy::retry(2));
+    let (summary, record) = simulate_game_recorded(&game, &mut agents, &config);
+    assert_eq!(summary.result.unwrap()[&NimPlayerId(2)], 1);
+    assert_eq!(summary.turns, 2);
+    assert_eq!(agents[&NimPlayerId(2)].rejections, 2);
+    assert_eq!(record.turns.len(), 2, "Only the accepted moves are recorded");
+    assert_eq!(record.turns[1].moves[&NimPlayerId(2)].amount, 3);
+
+    // Out of attempts
+    let mut agents = scripted_agents(&[1], &[0, 0, 0]);
+    let summary = simulate_game_with(&game, &mut agents, &config);
+    assert!(matches!(summary.result, Err(SimulationError::GameError(GameError::InvalidMove { player: NimPlayerId(2), .. }))));
+    assert_eq!(agents[&NimPlayerId(2)].rejections, 3);
+}
+
+#[test]
+fn test_illegal_moves_can_be_replaced_by_random_legal_moves() {
+    let game = NimGameLogic {
+        initial_pile_size: 20,
+        max_takes: 3,
+    };
+    let config = SimulationConfig {
+        seed: Some(5),
+        ..illegal_move_config(IllegalMovePolicy::random_legal_move())
+    };
+
+    // The first player never makes a legal move
+    let (summary, record) = simulate_game_recorded(&game, &mut scripted_agents(&[0; 20], &[]), &config);
+    assert!(summary.result.is_ok());
+    assert!(record.turns.iter().all(|turn| turn.moves.values().all(|m| (1..=3).contains(&m.amount))));
+
+    let (_, again) = simulate_game_recorded(&game, &mut scripted_agents(&[0; 20], &[]), &config);
+    let amounts = |record: &GameRecord<NimPlayerId, NimMove>| -> Vec<u32> {
+        record.turns.iter().flat_map(|turn| turn.moves.values().map(|m| m.amount)).collect()
+    };
+    assert_eq!(amounts(&record), amounts(&again), "Seeded games substitute the same moves");
+}
+
+#[test]
+fn test_illegal_moves_can_forfeit_the_game() {
+    let game = NimGameLogic {
+        initial_pile_size: 4,
+        max_takes: 3,
+    };
+    let mut agents = scripted_agents(&[1], &[9]);
+    let config = illegal_move_config(IllegalMovePolicy::Forfeit { penalty: -5 });
+
+    let summary = simulate_game_with(&game, &mut agents, &config);
+    assert_eq!(summary.result.unwrap(), HashMap::from([(NimPlayerId(1), 0), (NimPlayerId(2), -5)]));
+    assert_eq!(summary.outcome.unwrap().termination, Termination::Forfeit(NimPlayerId(2)));
+    assert_eq!(agents[&NimPlayerId(2)].rejections, 1);
+
+    // Async agents are held to the same policy
+    let mut agents: IndexMap<NimPlayerId, _> = scripted_agents(&[1], &[9]).into_iter().map(|(pid, agent)| (pid, AsyncAdapter(agent))).collect();
+    let summary = block_on(simulate_game_async_with(&game, &mut agents, &config));
+    assert_eq!(summary.result.unwrap(), HashMap::from([(NimPlayerId(1), 0), (NimPlayerId(2), -5)]));
+}
+
+#[test]
+fn test_game_session_try_step_undoes_rejected_turns() {
+    let game = NimGameLogic {
+        initial_pile_size: 5,
+        max_takes: 3,
+    };
+    let mut session = GameSession::new(&game, vec![NimPlayerId(1), NimPlayerId(2)]);
+
+    session.submit_move(NimPlayerId(1), NimMove { amount: 4 }).unwrap();
+    assert!(matches!(session.try_step(), Err(GameError::InvalidMove { .. })));
+    assert!(!session.is_over());
+    assert_eq!(session.turn(), 0);
+    assert_eq!(session.state().pile_size, 5);
+    assert_eq!(session.submitted_moves()[&NimPlayerId(1)].amount, 4, "The rejected move is still there");
+
+    session.submit_move(NimPlayerId(1), NimMove { amount: 2 }).unwrap();
+    assert!(session.try_step().is_ok());
+    assert_eq!(session.state().pile_size, 3);
+}
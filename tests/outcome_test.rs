@@ -0,0 +1,226 @@
+// Outcome, ranking and elimination tests
+
+use std::collections::{HashMap, HashSet};
+
+use game_logic::core::{Agent, FinalScores, GameError, GameLogic, Id, MoveResult, Outcome, Termination};
+use game_logic::simulation::{simulate_game_with, GameSession, SimulationConfig};
+use game_logic::tournament::{host_tournament_with, AgentFactory, IdGenerator, Record, RoundRobin, TournamentConfig};
+use indexmap::IndexMap;
+
+mod common;
+use common::nim::{NimMove, NimPlayerId};
+
+fn pids(ids: &[u32]) -> Vec<NimPlayerId> {
+    ids.iter().map(|&id| NimPlayerId(id)).collect()
+}
+
+fn sorted(mut group: Vec<NimPlayerId>) -> Vec<NimPlayerId> {
+    group.sort_by_key(|pid| pid.0);
+    group
+}
+
+#[test]
+fn test_outcome_from_scores_groups_ties() {
+    let players = pids(&[1, 2, 3, 4]);
+    let outcome = Outcome::from_scores(
+        HashMap::from([(NimPlayerId(1), 2), (NimPlayerId(2), 5), (NimPlayerId(3), 2)]),
+        &players,
+    );
+
+    assert_eq!(outcome.rankings, vec![pids(&[2]), pids(&[1, 3]), pids(&[4])]);
+    assert_eq!(outcome.score_of(NimPlayerId(4)), 0, "Missing players score 0");
+    assert_eq!(outcome.rank_of(NimPlayerId(3)), Some(2));
+    assert_eq!(outcome.rank_of(NimPlayerId(4)), Some(3));
+    assert_eq!(outcome.rank_of(NimPlayerId(5)), None);
+    assert_eq!(outcome.winners(), pids(&[2]));
+    assert!(!outcome.is_draw());
+    assert_eq!(outcome.termination, Termination::Completed);
+}
+
+#[test]
+fn test_outcome_constructors() {
+    let players = pids(&[1, 2, 3]);
+
+    let win = Outcome::win(NimPlayerId(2), &players);
+    assert_eq!(win.rankings, vec![pids(&[2]), pids(&[1, 3])]);
+    assert_eq!(win.scores, HashMap::from([(NimPlayerId(1), 0), (NimPlayerId(2), 2), (NimPlayerId(3), 0)]), "Players score the number of players below them");
+
+    let draw = Outcome::draw(&players);
+    assert!(draw.is_draw());
+    assert_eq!(draw.winners(), players);
+    assert!(draw.scores.values().all(|&score| score == 0));
+
+    let forfeit = Outcome::forfeit(NimPlayerId(1), -3, &players);
+    assert_eq!(forfeit.rankings, vec![pids(&[2, 3]), pids(&[1])]);
+    assert_eq!(forfeit.score_of(NimPlayerId(1)), -3);
+    assert_eq!(forfeit.termination, Termination::Forfeit(NimPlayerId(1)));
+    assert!(!forfeit.is_draw(), "Players tying for first isn't a draw if someone lost");
+
+    let ranked = Outcome::from_rankings(vec![pids(&[3]), pids(&[1]), pids(&[2])]);
+    assert_eq!(ranked.scores, HashMap::from([(NimPlayerId(1), 1), (NimPlayerId(2), 0), (NimPlayerId(3), 2)]));
+    let scores: FinalScores<NimPlayerId> = ranked.with_scores(HashMap::from([(NimPlayerId(3), 10)])).into();
+    assert_eq!(scores, HashMap::from([(NimPlayerId(3), 10)]));
+}
+
+/// Every turn, the players still in bid at once and the lowest bidders are eliminated.
+/// The last player standing wins. If everyone left bids the same, they draw.
+struct Lowball;
+
+impl GameLogic for Lowball {
+    type PID = NimPlayerId;
+    type Move = NimMove;
+    type State = HashSet<NimPlayerId>;
+    type MaskedState = usize;
+
+    fn init(&self, players: Vec<NimPlayerId>) -> (Self::State, HashSet<NimPlayerId>) {
+        let players: HashSet<NimPlayerId> = players.into_iter().collect();
+        (players.clone(), players)
+    }
+
+    fn apply_moves(
+        &self,
+        state: &mut Self::State,
+        moves: HashMap<NimPlayerId, NimMove>,
+    ) -> Result<MoveResult<NimPlayerId>, GameError<NimPlayerId>> {
+        let lowest = moves.values().map(|bid| bid.amount).min().unwrap();
+        let eliminated: HashSet<NimPlayerId> = moves
+            .iter()
+            .filter(|(_, bid)| bid.amount == lowest)
+            .map(|(&pid, _)| pid)
+            .collect();
+
+        if eliminated.len() == state.len() {
+            let players = sorted(state.drain().collect());
+            return Ok(MoveResult::Finished(Outcome::draw(&players)));
+        }
+        state.retain(|pid| !eliminated.contains(pid));
+        if state.len() == 1 {
+            let winner = *state.iter().next().unwrap();
+            return Ok(MoveResult::GameOver(HashMap::from([(winner, 1)])));
+        }
+        Ok(MoveResult::Eliminated {
+            eliminated,
+            active: state.clone(),
+        })
+    }
+
+    fn mask_state(&self, state: &Self::State, _player: NimPlayerId) -> usize {
+        state.len()
+    }
+}
+
+/// Always bids the same amount, and counts the turns it watched.
+#[derive(Clone)]
+struct Bidder {
+    amount: u32,
+    watched: usize,
+}
+
+impl Agent for Bidder {
+    type Game = Lowball;
+
+    fn digest_state(&mut self, _new_state: usize) {
+        self.watched += 1;
+    }
+
+    fn calculate_next_move(&mut self, _new_state: usize) -> NimMove {
+        NimMove { amount: self.amount }
+    }
+}
+
+fn bidders(amounts: &[u32]) -> IndexMap<NimPlayerId, Bidder> {
+    amounts
+        .iter()
+        .enumerate()
+        .map(|(seat, &amount)| (NimPlayerId(seat as u32 + 1), Bidder { amount, watched: 0 }))
+        .collect()
+}
+
+#[test]
+fn test_eliminated_players_rank_by_how_long_they_lasted() {
+    let mut agents = bidders(&[3, 1, 2, 1]);
+    let summary = simulate_game_with(&Lowball, &mut agents, &SimulationConfig::default());
+
+    assert_eq!(summary.turns, 2);
+    let outcome = summary.outcome.expect("The game should complete");
+    let rankings: Vec<Vec<NimPlayerId>> = outcome.rankings.iter().cloned().map(sorted).collect();
+    assert_eq!(rankings, vec![pids(&[1]), pids(&[3]), pids(&[2, 4])]);
+    assert_eq!(outcome.rank_of(NimPlayerId(4)), Some(3));
+    assert_eq!(
+        summary.result.unwrap(),
+        HashMap::from([(NimPlayerId(1), 1), (NimPlayerId(2), 0), (NimPlayerId(3), 0), (NimPlayerId(4), 0)]),
+        "Eliminated players score 0"
+    );
+
+    // Eliminated players keep watching the game
+    assert_eq!(agents[&NimPlayerId(2)].watched, 1);
+    assert_eq!(agents[&NimPlayerId(1)].watched, 0);
+}
+
+#[test]
+fn test_game_session_tracks_eliminations() {
+    let mut session = GameSession::new(&Lowball, pids(&[1, 2, 3]));
+    for (pid, amount) in [(1, 2), (2, 1), (3, 2)] {
+        session.submit_move(NimPlayerId(pid), NimMove { amount }).unwrap();
+    }
+    assert!(matches!(session.step().unwrap(), MoveResult::Eliminated { .. }));
+    assert!(session.is_eliminated(NimPlayerId(2)));
+    assert!(!session.is_active(NimPlayerId(2)));
+    assert_eq!(session.remaining_players(), pids(&[1, 3]));
+
+    // The remaining players draw, and rank above the eliminated one
+    session.submit_move(NimPlayerId(1), NimMove { amount: 1 }).unwrap();
+    session.submit_move(NimPlayerId(3), NimMove { amount: 1 }).unwrap();
+    assert!(matches!(session.step().unwrap(), MoveResult::Finished(_)));
+    let outcome = session.final_outcome().unwrap();
+    assert_eq!(outcome.rankings, vec![pids(&[1, 3]), pids(&[2])]);
+    assert_eq!(outcome.winners(), pids(&[1, 3]));
+    assert!(!outcome.is_draw());
+}
+
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
+struct TestGameId(u32);
+
+impl Id for TestGameId {}
+
+struct SimpleIdGenerator(u32);
+
+impl IdGenerator for SimpleIdGenerator {
+    type Id = TestGameId;
+
+    fn generate_id(&mut self) -> TestGameId {
+        self.0 += 1;
+        TestGameId(self.0)
+    }
+}
+
+impl AgentFactory for Bidder {
+    type Agent = Bidder;
+
+    fn create_agent(&self) -> Bidder {
+        self.clone()
+    }
+}
+
+#[test]
+fn test_tournament_records_count_ranked_draws() {
+    let players = pids(&[1, 2, 3]);
+    let factories: HashMap<NimPlayerId, Bidder> =
+        bidders(&[1, 1, 1]).into_iter().collect();
+
+    let mut round_robin = RoundRobin::new(players, 3, 1);
+    let report = host_tournament_with(
+        &Lowball,
+        factories,
+        &mut round_robin,
+        &mut SimpleIdGenerator(0),
+        &TournamentConfig::default(),
+    );
+
+    assert_eq!(report.games.len(), 1);
+    assert!(report.games[0].outcome.as_ref().unwrap().is_draw());
+    for record in report.win_draw_loss().values() {
+        assert_eq!(*record, Record { wins: 0, draws: 1, losses: 0 });
+    }
+    assert_eq!(report.head_to_head()[&(NimPlayerId(1), NimPlayerId(2))], Record { wins: 0, draws: 1, losses: 0 });
+}
@@ -0,0 +1,98 @@
+// Rating system tests
+
+use std::collections::HashMap;
+
+use game_logic::tournament::{Elo, Glicko2, Glicko2Rating, RatingSystem};
+
+mod common;
+use common::nim::NimPlayerId;
+
+const P1: NimPlayerId = NimPlayerId(1);
+const P2: NimPlayerId = NimPlayerId(2);
+const P3: NimPlayerId = NimPlayerId(3);
+const P4: NimPlayerId = NimPlayerId(4);
+
+#[test]
+fn test_elo_winner_gains_what_loser_loses() {
+    let mut elo = Elo::default();
+
+    // Nim only reports the winner -- the loser is still rated
+    elo.update(&[P1, P2], &HashMap::from([(P1, 1)]));
+
+    let winner = elo.rating(P1).value;
+    let loser = elo.rating(P2).value;
+    assert!((winner - 1516.0).abs() < 1e-9, "Equal players exchange half the K-factor");
+    assert!((loser - 1484.0).abs() < 1e-9);
+    assert_eq!(elo.ratings().len(), 2);
+}
+
+#[test]
+fn test_elo_draw_between_equals_changes_nothing() {
+    let mut elo = Elo::default();
+
+    elo.update(&[P1, P2], &HashMap::new());
+
+    assert_eq!(elo.rating(P1).value, 1500.0);
+    assert_eq!(elo.rating(P2).value, 1500.0);
+}
+
+#[test]
+fn test_elo_multiplayer_ranks_by_score() {
+    let mut elo = Elo::default();
+
+    elo.update(&[P1, P2, P3], &HashMap::from([(P1, 5), (P2, 3), (P3, 1)]));
+
+    assert!(elo.rating(P1).value > elo.rating(P2).value);
+    assert!(elo.rating(P2).value > elo.rating(P3).value);
+    // The middle player won one and lost one against equals
+    assert!((elo.rating(P2).value - 1500.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_elo_offline_stream() {
+    let mut elo = Elo::default();
+
+    elo.update_all((0..20).map(|_| (vec![P1, P2], HashMap::from([(P1, 1)]))));
+
+    assert!(elo.rating(P1).value > 1600.0);
+    assert!(elo.rating(P2).value < 1400.0);
+}
+
+#[test]
+fn test_glicko2_matches_reference_example() {
+    // The worked example from Glickman's "Example of the Glicko-2 system":
+    // a 1500 player (RD 200) beats a 1400 (RD 30) and loses to a 1550 (RD 100) and a 1700 (RD 300).
+    // Played as a single four-player game, the pairwise outcomes are exactly those three games.
+    let rating = |rating, deviation| Glicko2Rating {
+        rating,
+        deviation,
+        volatility: 0.06,
+    };
+    let mut glicko = Glicko2::new(rating(1500.0, 200.0), 0.5);
+    glicko.set_rating(P2, rating(1400.0, 30.0));
+    glicko.set_rating(P3, rating(1550.0, 100.0));
+    glicko.set_rating(P4, rating(1700.0, 300.0));
+
+    glicko.update(&[P1, P2, P3, P4], &HashMap::from([(P1, 2), (P2, 1), (P3, 3), (P4, 3)]));
+
+    let rating = glicko.glicko2_rating(P1);
+    assert!((rating.rating - 1464.06).abs() < 0.01, "rating was {}", rating.rating);
+    assert!((rating.deviation - 151.52).abs() < 0.01, "deviation was {}", rating.deviation);
+    assert!((rating.volatility - 0.05999).abs() < 0.00001, "volatility was {}", rating.volatility);
+}
+
+#[test]
+fn test_glicko2_uncertainty_shrinks_with_games() {
+    let mut glicko = Glicko2::default();
+
+    glicko.update(&[P1, P2], &HashMap::from([(P1, 1)]));
+    let after_one = glicko.rating(P1);
+    for _ in 0..10 {
+        glicko.update(&[P1, P2], &HashMap::from([(P1, 1)]));
+    }
+    let after_many = glicko.rating(P1);
+
+    assert!(after_one.deviation < 350.0);
+    assert!(after_many.deviation < after_one.deviation);
+    assert!(after_many.value > after_one.value);
+}
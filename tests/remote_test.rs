@@ -0,0 +1,240 @@
+// Remote bots over TCP and child processes, only built with the `remote` feature
+#![cfg(feature = "remote")]
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+
+use game_logic::agents::remote::PROTOCOL_VERSION;
+use game_logic::agents::{RemoteAgent, RemoteConfig, RemoteError};
+use game_logic::core::Agent;
+use game_logic::simulate_game;
+use game_logic::simulation::{simulate_game_with, SimulationConfig, TimeControl, TimeoutPolicy};
+use indexmap::IndexMap;
+use serde_json::{json, Value};
+
+mod common;
+use common::nim::{NimGameLogic, NimPlayerId, NimRandomAgent, NimState};
+
+/// Serves one connection as a perfect Nim bot, returning the types of the messages it got.
+fn serve_perfect_bot(listener: TcpListener, max_takes: u32) -> thread::JoinHandle<Vec<String>> {
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut writer = stream.try_clone().unwrap();
+        let mut received = Vec::new();
+        for line in BufReader::new(stream).lines() {
+            let message: Value = serde_json::from_str(&line.unwrap()).unwrap();
+            let kind = message["type"].as_str().unwrap().to_string();
+            let reply = match kind.as_str() {
+                "hello" => Some(json!({"type": "hello", "version": 1, "name": "perfect"})),
+                "next_move" => {
+                    let pile = message["state"]["pile_size"].as_u64().unwrap() as u32;
+                    let amount = match pile % (max_takes + 1) {
+                        0 => 1,
+                        x => x,
+                    };
+                    Some(json!({"type": "move", "move": {"amount": amount}}))
+                }
+                _ => None,
+            };
+            received.push(kind.clone());
+            if let Some(reply) = reply {
+                writeln!(writer, "{}", reply).unwrap();
+            }
+            if kind == "game_over" {
+                break;
+            }
+        }
+        received
+    })
+}
+
+#[test]
+fn test_remote_agent_plays_over_tcp() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let bot = serve_perfect_bot(listener, 3);
+
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let remote: RemoteAgent<NimGameLogic> =
+        RemoteAgent::connect(address, RemoteConfig::default()).unwrap();
+    assert_eq!(remote.name(), Some("perfect"));
+    assert_eq!(remote.version(), PROTOCOL_VERSION);
+
+    let mut agents: IndexMap<NimPlayerId, Box<dyn Agent<Game = NimGameLogic>>> = [
+        (
+            NimPlayerId(1),
+            Box::new(remote) as Box<dyn Agent<Game = NimGameLogic>>,
+        ),
+        (NimPlayerId(2), Box::new(NimRandomAgent::new(3))),
+    ]
+    .into();
+    let scores = simulate_game(&game, &mut agents, Some(100)).unwrap();
+    assert_eq!(
+        scores[&NimPlayerId(1)],
+        1,
+        "The perfect bot wins from 10 matches"
+    );
+
+    let received = bot.join().unwrap();
+    assert_eq!(received.first().map(String::as_str), Some("hello"));
+    assert_eq!(received[1], "game_start");
+    assert!(received.iter().any(|kind| kind == "next_move"));
+    assert!(received.iter().any(|kind| kind == "digest_state"));
+    assert_eq!(received.last().map(String::as_str), Some("game_over"));
+}
+
+fn pile(pile_size: u32) -> NimState {
+    NimState {
+        pile_size,
+        players: vec![NimPlayerId(1), NimPlayerId(2)],
+    }
+}
+
+/// Starts a shell script as a bot.
+#[cfg(unix)]
+fn spawn_script(
+    script: &str,
+    config: RemoteConfig,
+) -> Result<RemoteAgent<NimGameLogic>, RemoteError> {
+    RemoteAgent::spawn(
+        std::process::Command::new("sh").args(["-c", script]),
+        config,
+    )
+}
+
+#[cfg(unix)]
+const ECHO_BOT: &str = r#"
+while read -r line; do
+    case "$line" in
+        *'"hello"'*) echo '{"type":"hello","version":1,"name":"echo"}' ;;
+        *'"next_move"'*) echo '{"type":"move","move":{"amount":1}}' ;;
+    esac
+done
+"#;
+
+#[cfg(unix)]
+#[test]
+fn test_remote_agent_talks_to_child_process() {
+    let mut agent = spawn_script(ECHO_BOT, RemoteConfig::default()).unwrap();
+    assert_eq!(agent.name(), Some("echo"));
+
+    agent.on_game_start(NimPlayerId(2), &[NimPlayerId(1), NimPlayerId(2)]);
+    agent.digest_state(pile(10));
+    assert_eq!(agent.calculate_next_move(pile(9)).amount, 1);
+    assert_eq!(agent.calculate_next_move(pile(7)).amount, 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_remote_agent_rejects_unsupported_versions() {
+    let script = r#"read -r line; echo '{"type":"hello","version":99}'; read -r line"#;
+    match spawn_script(script, RemoteConfig::default()) {
+        Err(RemoteError::UnsupportedVersion(99)) => {}
+        Err(e) => panic!("Expected an unsupported version, got {}", e),
+        Ok(_) => panic!("Expected an unsupported version"),
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_remote_agent_times_out_and_detects_closed_bots() {
+    let config = RemoteConfig {
+        handshake_timeout: Duration::from_secs(5),
+        move_timeout: Duration::from_millis(100),
+        ..RemoteConfig::default()
+    };
+
+    // Answers the handshake, then never moves
+    let silent = r#"read -r line; echo '{"type":"hello","version":1}'; sleep 10"#;
+    let mut agent = spawn_script(silent, config).unwrap();
+    assert!(matches!(
+        agent.request_move(&pile(5)),
+        Err(RemoteError::TimedOut(_))
+    ));
+
+    // Answers the handshake, then exits
+    let quitter = r#"read -r line; echo '{"type":"hello","version":1}'"#;
+    let mut agent = spawn_script(quitter, config).unwrap();
+    assert!(matches!(
+        agent.request_move(&pile(5)),
+        Err(RemoteError::Closed | RemoteError::Io(_))
+    ));
+
+    // Failures make the agent panic, which the simulation reports as a panicked agent
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        agent.calculate_next_move(pile(5))
+    }));
+    assert!(result.is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_remote_agent_skips_replies_that_came_too_late() {
+    let config = RemoteConfig {
+        move_timeout: Duration::from_millis(400),
+        ..RemoteConfig::default()
+    };
+
+    // Answers the first move request after its timeout, and the second one right away
+    let slow_once = r#"
+read -r line; echo '{"type":"hello","version":1}'
+read -r line; sleep 0.6; echo '{"type":"move","move":{"amount":1}}'
+read -r line; echo '{"type":"move","move":{"amount":2}}'
+read -r line
+"#;
+    let mut agent = spawn_script(slow_once, config).unwrap();
+    assert!(matches!(agent.request_move(&pile(5)), Err(RemoteError::TimedOut(_))));
+    assert_eq!(agent.request_move(&pile(5)).unwrap().amount, 2, "The late reply belongs to the first request");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_remote_agent_times_out_writing_to_bots_that_stop_reading() {
+    let config = RemoteConfig {
+        write_timeout: Duration::from_millis(100),
+        ..RemoteConfig::default()
+    };
+
+    // Answers the handshake, then never reads again, so its input fills up
+    let deaf = r#"read -r line; echo '{"type":"hello","version":1}'; sleep 10"#;
+    let mut agent = spawn_script(deaf, config).unwrap();
+    let start = std::time::Instant::now();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        for _ in 0..1_000_000 {
+            agent.digest_state(pile(5));
+        }
+    }));
+    assert!(result.is_err(), "Writing to a bot that doesn't read should fail");
+    assert!(start.elapsed() < Duration::from_secs(5), "took {:?}", start.elapsed());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_slow_remote_bots_get_the_timeout_policy() {
+    let game = NimGameLogic { initial_pile_size: 10, max_takes: 3 };
+    // Answers the handshake, then takes far longer than the time control allows
+    let slow = r#"read -r line; echo '{"type":"hello","version":1}'; while read -r line; do sleep 5; done"#;
+    let bot = spawn_script(slow, RemoteConfig::default()).unwrap();
+    let mut agents: IndexMap<NimPlayerId, Box<dyn Agent<Game = NimGameLogic>>> = [
+        (NimPlayerId(1), Box::new(bot) as Box<dyn Agent<Game = NimGameLogic>>),
+        (NimPlayerId(2), Box::new(NimRandomAgent::with_seed(3, 0))),
+    ]
+    .into();
+
+    let config = SimulationConfig {
+        time_control: Some(TimeControl::PerMove(Duration::from_millis(200))),
+        timeout_policy: TimeoutPolicy::Forfeit { penalty: -1 },
+        ..SimulationConfig::default()
+    };
+    let start = std::time::Instant::now();
+    let summary = simulate_game_with(&game, &mut agents, &config);
+
+    assert_eq!(summary.turns, 1);
+    assert_eq!(summary.result.expect("A forfeit ends the game normally")[&NimPlayerId(1)], -1);
+    assert!(start.elapsed() < Duration::from_secs(4), "took {:?}", start.elapsed());
+}
@@ -0,0 +1,165 @@
+// Sandboxed bot processes, only built with the `sandbox` feature on Unix
+#![cfg(all(feature = "sandbox", unix))]
+
+use std::collections::HashMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::time::Duration;
+
+use game_logic::agents::{ResourceLimits, SandboxConfig, SandboxFactory};
+use game_logic::core::{Agent, Id};
+use game_logic::tournament::{host_tournament_with, AgentFactory, IdGenerator, RoundRobin, TournamentConfig};
+
+mod common;
+use common::nim::{NimGameLogic, NimPlayerId, NimState};
+
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
+struct TestGameId(u32);
+
+impl Id for TestGameId {}
+
+struct SimpleIdGenerator(u32);
+
+impl IdGenerator for SimpleIdGenerator {
+    type Id = TestGameId;
+
+    fn generate_id(&mut self) -> TestGameId {
+        self.0 += 1;
+        TestGameId(self.0)
+    }
+}
+
+/// Takes one match at a time, logging every request to stderr.
+const SLOW_BOT: &str = r#"
+while read -r line; do
+    echo "got $line" >&2
+    case "$line" in
+        *'"hello"'*) echo '{"type":"hello","version":1}' ;;
+        *'"next_move"'*) echo '{"type":"move","move":{"amount":1}}' ;;
+    esac
+done
+"#;
+
+fn script_factory(script: &str, config: SandboxConfig) -> SandboxFactory<NimGameLogic> {
+    SandboxFactory::new("sh", config).args(["-c", script])
+}
+
+fn pile(pile_size: u32) -> NimState {
+    NimState {
+        pile_size,
+        players: vec![NimPlayerId(1), NimPlayerId(2)],
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    payload
+        .downcast_ref::<String>()
+        .cloned()
+        .or_else(|| payload.downcast_ref::<&str>().map(|message| message.to_string()))
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_sandboxed_bots_play_a_tournament() {
+    let game = NimGameLogic { initial_pile_size: 6, max_takes: 3 };
+    let players = [NimPlayerId(1), NimPlayerId(2)];
+    let factories: HashMap<NimPlayerId, SandboxFactory<NimGameLogic>> = players
+        .iter()
+        .map(|&pid| (pid, script_factory(SLOW_BOT, SandboxConfig::default())))
+        .collect();
+
+    let mut round_robin = RoundRobin::new(players, 2, 1).with_seat_rotation();
+    let report = host_tournament_with(&game, factories, &mut round_robin, &mut SimpleIdGenerator(0), &TournamentConfig::default());
+
+    assert_eq!(report.games.len(), 2);
+    for game_report in &report.games {
+        let scores = game_report.result.as_ref().expect("Sandboxed bots finish their games");
+        // Taking one match at a time from 6, the second seat takes the last one
+        assert_eq!(scores[&game_report.players[1]], 1);
+        // Each bot's stderr is kept with its own game, under its own player
+        for pid in &game_report.players {
+            assert!(game_report.logs[pid].contains(&format!(r#""player":{}"#, pid.0)), "{}", game_report.logs[pid]);
+            assert!(game_report.logs[pid].contains("game_over"), "{}", game_report.logs[pid]);
+        }
+    }
+}
+
+#[test]
+fn test_sandbox_captures_stderr_and_starts_a_process_per_game() {
+    let factory = script_factory(SLOW_BOT, SandboxConfig::default());
+
+    for _ in 0..2 {
+        let mut agent = factory.create_agent();
+        agent.on_game_start(NimPlayerId(1), &[NimPlayerId(1), NimPlayerId(2)]);
+        assert_eq!(agent.calculate_next_move(pile(4)).amount, 1);
+
+        let log = agent.take_log().expect("The bot wrote to stderr");
+        assert!(log.starts_with(r#"got {"type":"hello","versions":[1]}"#), "Every game starts a fresh bot: {}", log);
+        assert!(log.contains("game_start"));
+        assert_eq!(agent.take_log(), None, "Taking the log empties it");
+    }
+}
+
+#[test]
+fn test_sandbox_restarts_bots_that_fail_to_start() {
+    let marker = std::env::temp_dir().join(format!("game_logic_sandbox_{}", std::process::id()));
+    let _ = std::fs::remove_file(&marker);
+    // Crashes the first time, and works once the marker exists
+    let script = format!(
+        r#"if [ ! -e '{}' ]; then touch '{}'; echo "first start" >&2; exit 3; fi
+{}"#,
+        marker.display(),
+        marker.display(),
+        SLOW_BOT
+    );
+    let factory = script_factory(&script, SandboxConfig::default());
+
+    let mut agent = factory.create_agent();
+    assert_eq!(agent.calculate_next_move(pile(4)).amount, 1);
+    assert!(agent.take_log().unwrap().contains("next_move"));
+    let _ = std::fs::remove_file(&marker);
+
+    // A bot that never starts makes the factory give up after its restarts
+    let config = SandboxConfig {
+        restarts: 1,
+        ..SandboxConfig::default()
+    };
+    let factory = script_factory(r#"echo "missing library" >&2; exit 1"#, config);
+    let message = panic_message(catch_unwind(AssertUnwindSafe(|| factory.create_agent())).err().unwrap());
+    assert!(message.contains("failed to start 2 times"), "{}", message);
+    assert!(message.contains("missing library"), "{}", message);
+}
+
+#[test]
+fn test_sandbox_enforces_resource_limits() {
+    // Burns CPU instead of answering, until the CPU limit kills it
+    let spinner = r#"read -r line; echo '{"type":"hello","version":1}'; while :; do :; done"#;
+    let config = SandboxConfig {
+        limits: ResourceLimits {
+            cpu_time: Some(Duration::from_secs(1)),
+            ..ResourceLimits::default()
+        },
+        remote: game_logic::agents::RemoteConfig {
+            handshake_timeout: Duration::from_secs(5),
+            move_timeout: Duration::from_secs(20),
+            ..game_logic::agents::RemoteConfig::default()
+        },
+        ..SandboxConfig::default()
+    };
+    let factory = script_factory(spinner, config);
+    let mut agent = factory.create_agent();
+    let message = panic_message(catch_unwind(AssertUnwindSafe(|| agent.calculate_next_move(pile(4)))).err().unwrap());
+    assert!(message.contains("closed"), "{}", message);
+    assert!(message.contains("signal"), "{}", message);
+
+    // Too little memory to even start the shell
+    let config = SandboxConfig {
+        limits: ResourceLimits {
+            memory_bytes: Some(1 << 16),
+            ..ResourceLimits::default()
+        },
+        restarts: 0,
+        ..SandboxConfig::default()
+    };
+    let factory = script_factory(SLOW_BOT, config);
+    assert!(catch_unwind(AssertUnwindSafe(|| factory.create_agent())).is_err());
+}
@@ -0,0 +1,109 @@
+// Serialization round trips, only built with the `serde` feature
+#![cfg(feature = "serde")]
+
+use std::collections::HashMap;
+
+use indexmap::IndexMap;
+use game_logic::core::{GameError, Id, MoveResult, Outcome, Termination};
+use game_logic::simulation::{simulate_game_recorded, GameRecordOf, Replay, SimulationConfig, SimulationError};
+use game_logic::tournament::{host_tournament_with, IdGenerator, MatchMakerOutput, RoundRobin, TournamentConfig, TournamentReport};
+
+mod common;
+use common::nim::{NimGameLogic, NimPerfectAgent, NimPlayerId, PerfectFactory};
+
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+struct TestGameId(u32);
+
+impl Id for TestGameId {}
+
+struct SimpleIdGenerator(u32);
+
+impl IdGenerator for SimpleIdGenerator {
+    type Id = TestGameId;
+
+    fn generate_id(&mut self) -> TestGameId {
+        self.0 += 1;
+        TestGameId(self.0)
+    }
+}
+
+#[test]
+fn test_errors_round_trip() {
+    let error = SimulationError::GameError(GameError::InvalidMove {
+        player: NimPlayerId(2),
+        reason: "Cannot take 0 matches".to_string(),
+    });
+
+    let json = serde_json::to_string(&error).unwrap();
+    let back: SimulationError<NimPlayerId> = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(back.culprit(), Some(NimPlayerId(2)));
+    assert_eq!(back.to_string(), error.to_string());
+}
+
+#[test]
+fn test_move_results_and_matchmaker_output_round_trip() {
+    let result = MoveResult::GameOver(HashMap::from([(NimPlayerId(1), 1)]));
+    let json = serde_json::to_string(&result).unwrap();
+    assert_eq!(serde_json::from_str::<MoveResult<NimPlayerId>>(&json).unwrap(), result);
+
+    let outcome = Outcome::forfeit(NimPlayerId(1), -1, &[NimPlayerId(1), NimPlayerId(2)])
+        .with_termination(Termination::Other("resigned".to_string()));
+    let result = MoveResult::Finished(outcome);
+    let json = serde_json::to_string(&result).unwrap();
+    assert_eq!(serde_json::from_str::<MoveResult<NimPlayerId>>(&json).unwrap(), result);
+
+    let output = MatchMakerOutput::Continue(vec![vec![NimPlayerId(1), NimPlayerId(2)]]);
+    let json = serde_json::to_string(&output).unwrap();
+    match serde_json::from_str::<MatchMakerOutput<NimPlayerId>>(&json).unwrap() {
+        MatchMakerOutput::Continue(games) => assert_eq!(games, vec![vec![NimPlayerId(1), NimPlayerId(2)]]),
+        MatchMakerOutput::Done(_) => panic!("Deserialized the wrong variant"),
+    }
+}
+
+#[test]
+fn test_saved_record_replays() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let mut agents: IndexMap<NimPlayerId, NimPerfectAgent> = [
+        (NimPlayerId(1), NimPerfectAgent::new(&game)),
+        (NimPlayerId(2), NimPerfectAgent::new(&game)),
+    ]
+    .into();
+    let (_, record) = simulate_game_recorded(&game, &mut agents, &SimulationConfig::default());
+
+    let json = serde_json::to_string(&record).unwrap();
+    let loaded: GameRecordOf<NimGameLogic> = serde_json::from_str(&json).unwrap();
+
+    let mut replay = Replay::new(&game, &loaded);
+    replay.seek(loaded.turns.len()).unwrap();
+    assert_eq!(replay.last_result(), record.final_result.as_ref());
+}
+
+#[test]
+fn test_tournament_report_round_trips() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let players = [NimPlayerId(1), NimPlayerId(2), NimPlayerId(3)];
+    let factories: HashMap<NimPlayerId, PerfectFactory> =
+        players.iter().map(|&pid| (pid, PerfectFactory::new(game.max_takes + 1))).collect();
+
+    let report = host_tournament_with(
+        &game,
+        factories,
+        &mut RoundRobin::new(players, 2, 1),
+        &mut SimpleIdGenerator(0),
+        &TournamentConfig::default(),
+    );
+
+    let json = serde_json::to_string(&report).unwrap();
+    let back: TournamentReport<NimPlayerId, TestGameId> = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(back.result, report.result);
+    assert_eq!(back.win_draw_loss(), report.win_draw_loss());
+    assert_eq!(back.games.len(), 3);
+}
@@ -1,12 +1,21 @@
 // Tournament system tests
-// Note: The tournament system is still under development (see manager.rs comment)
-// These tests verify the basic traits and types are defined correctly
 
-use game_logic::tournament::{AgentFactory, IdGenerator};
-use game_logic::core::Id;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use game_logic::tournament::{host_tournament, AgentFactory, IdGenerator, MatchMaker, MatchMakerOutput, RoundRobin, Swiss};
+use game_logic::simulation::SimulationConfig;
+use game_logic::tournament::{host_tournament_with, GameEnd, Record, TournamentConfig};
+use game_logic::tournament::{host_tournament_async, host_tournament_with_observers, TournamentObserver};
+use game_logic::tournament::{Bracket, FailurePolicy, Glicko2, MatchStatus, Rated, RatingSystem, Section, TieBreaker};
+use game_logic::tournament::{Elo, GameReport, Scoring};
+use game_logic::core::{Agent, FinalScores, GameError, Id, Outcome};
+use game_logic::simulation::{block_on, AsyncAdapter, GameResult, SimulationError};
 
 mod common;
-use common::nim::PerfectFactory;
+use common::nim::{NimGameLogic, NimMove, NimPlayerId, NimState, PerfectFactory, RandomFactory};
 
 #[test]
 fn test_agent_factory_creates_agents() {
@@ -81,5 +90,1040 @@ fn test_id_generator_increments() {
     assert_eq!(id2.0, 1);
 }
 
-// TODO: Add full tournament tests when host_tournament implementation is complete
-// The current implementation has type system issues that need resolution
+fn perfect_factories(players: &[NimPlayerId], game: &NimGameLogic) -> HashMap<NimPlayerId, PerfectFactory> {
+    players
+        .iter()
+        .map(|&pid| (pid, PerfectFactory::new(game.max_takes + 1)))
+        .collect()
+}
+
+/// Reports a game the way the tournament host does, ranking games that ended normally by their scores.
+fn game_report(game_id: TestGameId, players: &[NimPlayerId], result: GameResult<NimPlayerId>) -> GameReport<NimPlayerId, TestGameId> {
+    let outcome = result.as_ref().ok().map(|scores| Outcome::from_scores(scores.clone(), players));
+    GameReport { game_id, players: players.to_vec(), result, outcome, turns: 0, duration: Duration::ZERO, logs: HashMap::new() }
+}
+
+#[test]
+fn test_round_robin_schedules_every_combination() {
+    let players = [NimPlayerId(1), NimPlayerId(2), NimPlayerId(3), NimPlayerId(4)];
+    let round_robin: RoundRobin<NimPlayerId, TestGameId> = RoundRobin::new(players, 2, 1);
+
+    let games = round_robin.initial_games();
+
+    // 4 choose 2
+    assert_eq!(games.len(), 6);
+    assert_eq!(round_robin.remaining_games(), 6);
+    for (i, a) in players.iter().enumerate() {
+        for b in &players[i + 1..] {
+            assert!(games.contains(&vec![*a, *b]), "Missing game {:?} vs {:?}", a, b);
+        }
+    }
+}
+
+#[test]
+fn test_round_robin_seat_rotation_and_repeats() {
+    let players = [NimPlayerId(1), NimPlayerId(2), NimPlayerId(3)];
+    let round_robin: RoundRobin<NimPlayerId, TestGameId> =
+        RoundRobin::new(players, 3, 2).with_seat_rotation();
+
+    let games = round_robin.initial_games();
+
+    // 1 combination, 3 rotations, played twice
+    assert_eq!(games.len(), 6);
+    for seat in 0..3 {
+        for pid in players {
+            let count = games.iter().filter(|game| game[seat] == pid).count();
+            assert_eq!(count, 2, "{:?} should sit in seat {} once per repeat", pid, seat);
+        }
+    }
+}
+
+#[test]
+fn test_round_robin_finishes_after_last_result() {
+    let players = [NimPlayerId(1), NimPlayerId(2), NimPlayerId(3)];
+    let mut round_robin: RoundRobin<NimPlayerId, TestGameId> = RoundRobin::new(players, 2, 1);
+
+    let first = round_robin.digest_result(&game_report(TestGameId(0), &[NimPlayerId(1), NimPlayerId(2)], Ok(HashMap::from([(NimPlayerId(1), 1)]))));
+    assert!(matches!(first, MatchMakerOutput::Continue(ref games) if games.is_empty()));
+    let second = round_robin.digest_result(&game_report(TestGameId(1), &[NimPlayerId(1), NimPlayerId(3)], Ok(HashMap::from([(NimPlayerId(1), 1)]))));
+    assert!(matches!(second, MatchMakerOutput::Continue(_)));
+
+    match round_robin.digest_result(&game_report(TestGameId(2), &[NimPlayerId(2), NimPlayerId(3)], Ok(HashMap::from([(NimPlayerId(3), 1)])))) {
+        MatchMakerOutput::Done(totals) => {
+            assert_eq!(totals[&NimPlayerId(1)], 2);
+            assert_eq!(totals[&NimPlayerId(2)], 0, "Players without points are still listed");
+            assert_eq!(totals[&NimPlayerId(3)], 1);
+        }
+        MatchMakerOutput::Continue(_) => panic!("Round robin should be done after every game reported"),
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_round_robin_rejects_oversized_tables() {
+    let _: RoundRobin<NimPlayerId, TestGameId> = RoundRobin::new([NimPlayerId(1), NimPlayerId(2)], 3, 1);
+}
+
+#[test]
+fn test_host_tournament_round_robin() {
+    // pile % (max_takes + 1) != 0, so whoever sits first always wins between perfect agents
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let players = [NimPlayerId(1), NimPlayerId(2), NimPlayerId(3)];
+
+    let mut round_robin = RoundRobin::new(players, 2, 1);
+    let result = host_tournament(
+        &game,
+        perfect_factories(&players, &game),
+        &mut round_robin,
+        &mut SimpleIdGenerator::new(),
+        Some(100),
+    );
+
+    // Games: (1, 2), (1, 3), (2, 3) -- the first seat wins each
+    assert_eq!(result[&NimPlayerId(1)], 2);
+    assert_eq!(result[&NimPlayerId(2)], 1);
+    assert_eq!(result[&NimPlayerId(3)], 0);
+}
+
+#[test]
+fn test_host_tournament_round_robin_with_seat_rotation() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let players = [NimPlayerId(1), NimPlayerId(2), NimPlayerId(3)];
+
+    let mut round_robin = RoundRobin::new(players, 2, 3).with_seat_rotation();
+    let result = host_tournament(
+        &game,
+        perfect_factories(&players, &game),
+        &mut round_robin,
+        &mut SimpleIdGenerator::new(),
+        Some(100),
+    );
+
+    // Every player sits first against each opponent once per repeat
+    for pid in players {
+        assert_eq!(result[&pid], 6, "Seat rotation should balance {:?}'s results", pid);
+    }
+}
+
+/// Reports a win for the first seat of every game in the current round.
+fn finish_swiss_round(swiss: &mut Swiss<NimPlayerId, TestGameId>, ids: &mut SimpleIdGenerator) -> MatchMakerOutput<NimPlayerId> {
+    let games = swiss.current_round().to_vec();
+    let mut output = MatchMakerOutput::Continue(Vec::new());
+    for (i, game) in games.iter().enumerate() {
+        output = swiss.digest_result(&game_report(ids.generate_id(), game, Ok(HashMap::from([(game[0], 1)]))));
+        if i + 1 < games.len() {
+            assert!(
+                matches!(output, MatchMakerOutput::Continue(ref next) if next.is_empty()),
+                "No games should be scheduled before the round is complete"
+            );
+        }
+    }
+    output
+}
+
+#[test]
+fn test_swiss_never_repeats_pairings() {
+    let players: Vec<NimPlayerId> = (1..=4).map(NimPlayerId).collect();
+    let mut swiss = Swiss::new(players.clone(), 3, 1);
+    let mut ids = SimpleIdGenerator::new();
+    let mut seen = Vec::new();
+
+    loop {
+        for game in swiss.current_round() {
+            assert_eq!(game.len(), 2);
+            assert!(
+                !seen.contains(&(game[0], game[1])) && !seen.contains(&(game[1], game[0])),
+                "Pairing {:?} was repeated",
+                game
+            );
+            seen.push((game[0], game[1]));
+        }
+        if let MatchMakerOutput::Done(_) = finish_swiss_round(&mut swiss, &mut ids) {
+            break;
+        }
+    }
+
+    // Three rounds of four players is a full round robin
+    assert_eq!(seen.len(), 6);
+    assert_eq!(swiss.rounds_started(), 3);
+}
+
+#[test]
+fn test_swiss_pairs_by_score_group() {
+    let players: Vec<NimPlayerId> = (1..=4).map(NimPlayerId).collect();
+    let mut swiss = Swiss::new(players, 2, 1);
+    let mut ids = SimpleIdGenerator::new();
+
+    // Round 1: 1 vs 2 and 3 vs 4, first seats win
+    assert_eq!(swiss.current_round(), &[vec![NimPlayerId(1), NimPlayerId(2)], vec![NimPlayerId(3), NimPlayerId(4)]]);
+
+    match finish_swiss_round(&mut swiss, &mut ids) {
+        MatchMakerOutput::Continue(next) => {
+            // Winners meet winners, losers meet losers
+            assert_eq!(next, vec![vec![NimPlayerId(1), NimPlayerId(3)], vec![NimPlayerId(2), NimPlayerId(4)]]);
+        }
+        MatchMakerOutput::Done(_) => panic!("Swiss should continue to round 2"),
+    }
+}
+
+#[test]
+fn test_swiss_byes_for_odd_player_counts() {
+    let players: Vec<NimPlayerId> = (1..=5).map(NimPlayerId).collect();
+    let mut swiss = Swiss::new(players.clone(), 4, 1);
+    let mut ids = SimpleIdGenerator::new();
+    let mut had_bye = Vec::new();
+
+    loop {
+        let seated: Vec<NimPlayerId> = swiss.current_round().iter().flatten().copied().collect();
+        assert_eq!(seated.len(), 4, "Exactly one player should sit out each round");
+        let bye = players.iter().copied().find(|pid| !seated.contains(pid)).unwrap();
+        assert!(!had_bye.contains(&bye), "{:?} received a second bye", bye);
+        had_bye.push(bye);
+
+        if let MatchMakerOutput::Done(totals) = finish_swiss_round(&mut swiss, &mut ids) {
+            let total: i32 = totals.values().sum();
+            // 2 wins and 1 bye point per round
+            assert_eq!(total, 4 * 3);
+            break;
+        }
+    }
+}
+
+#[test]
+fn test_swiss_ends_early_without_valid_pairing() {
+    let mut swiss = Swiss::new([NimPlayerId(1), NimPlayerId(2)], 5, 1);
+    let mut ids = SimpleIdGenerator::new();
+
+    let output = finish_swiss_round(&mut swiss, &mut ids);
+    assert!(matches!(output, MatchMakerOutput::Done(_)), "No rematch is possible, so the tournament ends");
+    assert_eq!(swiss.rounds_started(), 1);
+}
+
+#[test]
+fn test_swiss_pairs_large_fields_with_few_options_left() {
+    // Forty players and 39 rounds is a full round robin, so the last rounds leave each
+    // player with only a few opponents they haven't met
+    let players: Vec<NimPlayerId> = (1..=40).map(NimPlayerId).collect();
+    let mut swiss = Swiss::new(players, 39, 1);
+    let mut ids = SimpleIdGenerator::new();
+    let mut seen = HashSet::new();
+
+    loop {
+        assert_eq!(swiss.current_round().len(), 20, "Every player should be paired");
+        for game in swiss.current_round() {
+            assert!(!seen.contains(&(game[1], game[0])) && seen.insert((game[0], game[1])), "Pairing {:?} was repeated", game);
+        }
+        if let MatchMakerOutput::Done(_) = finish_swiss_round(&mut swiss, &mut ids) {
+            break;
+        }
+    }
+
+    assert!(swiss.rounds_started() > 30, "Only {} rounds were paired", swiss.rounds_started());
+}
+
+#[test]
+fn test_host_tournament_swiss() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let players: Vec<NimPlayerId> = (1..=6).map(NimPlayerId).collect();
+
+    let mut swiss = Swiss::new(players.clone(), 3, 1);
+    let result = host_tournament(
+        &game,
+        perfect_factories(&players, &game),
+        &mut swiss,
+        &mut SimpleIdGenerator::new(),
+        Some(100),
+    );
+
+    // Every game has exactly one winner
+    assert_eq!(result.values().sum::<i32>(), 3 * 3);
+    assert_eq!(result.len(), 6);
+}
+
+/// Plays out a bracket by hand, letting `winner` pick the winner of every game.
+fn run_bracket(
+    bracket: &mut Bracket<NimPlayerId, TestGameId>,
+    winner: impl Fn(&[NimPlayerId]) -> Option<NimPlayerId>,
+) -> HashMap<NimPlayerId, i32> {
+    let mut ids = SimpleIdGenerator::new();
+    let mut queue = bracket.initial_games();
+
+    while let Some(game) = queue.pop() {
+        let scores = winner(&game).map(|pid| HashMap::from([(pid, 1)])).unwrap_or_default();
+        match bracket.digest_result(&game_report(ids.generate_id(), &game, Ok(scores))) {
+            MatchMakerOutput::Continue(next) => queue.extend(next),
+            MatchMakerOutput::Done(result) => return result,
+        }
+    }
+    panic!("Bracket ran out of games without finishing");
+}
+
+fn lowest_id_wins(game: &[NimPlayerId]) -> Option<NimPlayerId> {
+    game.iter().copied().min_by_key(|pid| pid.0)
+}
+
+#[test]
+fn test_single_elimination_seeding_and_byes() {
+    let seeds: Vec<NimPlayerId> = (1..=6).map(NimPlayerId).collect();
+    let bracket: Bracket<NimPlayerId, TestGameId> = Bracket::single_elimination(seeds, TieBreaker::HigherSeed);
+
+    // 6 players fill an 8-slot bracket: seeds 1 and 2 get byes, so round 1 is 4v5 and 3v6
+    let mut first_round = bracket.initial_games();
+    first_round.sort_by_key(|game| game[0].0);
+    assert_eq!(first_round, vec![vec![NimPlayerId(3), NimPlayerId(6)], vec![NimPlayerId(4), NimPlayerId(5)]]);
+
+    let walkovers = bracket.matches().iter().filter(|m| m.status == MatchStatus::Walkover).count();
+    assert_eq!(walkovers, 2);
+}
+
+#[test]
+fn test_single_elimination_champion_and_tree() {
+    let seeds: Vec<NimPlayerId> = (1..=8).map(NimPlayerId).collect();
+    let mut bracket = Bracket::single_elimination(seeds, TieBreaker::HigherSeed);
+
+    let wins = run_bracket(&mut bracket, lowest_id_wins);
+
+    assert_eq!(bracket.champion(), Some(NimPlayerId(1)));
+    assert!(bracket.is_complete());
+    assert_eq!(bracket.matches().len(), 7);
+    assert_eq!(wins[&NimPlayerId(1)], 3);
+    assert_eq!(wins[&NimPlayerId(8)], 0);
+
+    let final_match = bracket.matches().last().unwrap();
+    assert_eq!(final_match.round, 3);
+    assert_eq!(final_match.players, [Some(NimPlayerId(1)), Some(NimPlayerId(2))]);
+    assert_eq!(final_match.games.len(), 1, "The final's game is kept for inspection");
+}
+
+#[test]
+fn test_single_elimination_tie_breakers() {
+    // Every game is a draw
+    let draw = |_: &[NimPlayerId]| None;
+
+    let mut by_seed = Bracket::single_elimination([NimPlayerId(5), NimPlayerId(9)], TieBreaker::HigherSeed);
+    run_bracket(&mut by_seed, draw);
+    assert_eq!(by_seed.champion(), Some(NimPlayerId(5)), "The first seed advances on a draw");
+
+    let mut by_replay = Bracket::single_elimination(
+        [NimPlayerId(5), NimPlayerId(9)],
+        TieBreaker::Replay { max_replays: 2 },
+    );
+    run_bracket(&mut by_replay, draw);
+    assert_eq!(by_replay.matches()[0].games.len(), 3, "Two replays after the first draw");
+    assert_eq!(by_replay.champion(), Some(NimPlayerId(5)));
+}
+
+#[test]
+fn test_double_elimination_requires_two_losses() {
+    let seeds: Vec<NimPlayerId> = (1..=4).map(NimPlayerId).collect();
+    let mut bracket = Bracket::double_elimination(seeds, TieBreaker::HigherSeed);
+
+    let wins = run_bracket(&mut bracket, lowest_id_wins);
+
+    assert_eq!(bracket.champion(), Some(NimPlayerId(1)));
+    // Player 2 loses the winners final, wins the losers final, then loses the grand final
+    let grand_final = bracket.matches().iter().find(|m| m.section == Section::GrandFinal).unwrap();
+    assert_eq!(grand_final.players, [Some(NimPlayerId(1)), Some(NimPlayerId(2))]);
+    let reset = bracket.matches().iter().find(|m| m.section == Section::GrandFinalReset).unwrap();
+    assert_eq!(reset.status, MatchStatus::Void, "No reset when the winners bracket champion wins");
+
+    let total_losses = |pid: NimPlayerId| bracket.matches().iter().filter(|m| m.loser == Some(pid)).count();
+    for pid in 2..=4 {
+        assert_eq!(total_losses(NimPlayerId(pid)), 2, "Player {} is out after two losses", pid);
+    }
+    assert_eq!(wins[&NimPlayerId(1)], 3);
+}
+
+#[test]
+fn test_double_elimination_grand_final_reset() {
+    let seeds: Vec<NimPlayerId> = (1..=8).map(NimPlayerId).collect();
+    let mut bracket = Bracket::double_elimination(seeds, TieBreaker::HigherSeed);
+
+    // Players 1 and 2 beat everyone else. They meet in the winners final (1 wins),
+    // the grand final (2 wins) and the reset (1 wins).
+    let meetings = std::cell::Cell::new(0);
+    let winner = |game: &[NimPlayerId]| {
+        if game.contains(&NimPlayerId(1)) && game.contains(&NimPlayerId(2)) {
+            meetings.set(meetings.get() + 1);
+            return Some(if meetings.get() == 2 { NimPlayerId(2) } else { NimPlayerId(1) });
+        }
+        lowest_id_wins(game)
+    };
+    run_bracket(&mut bracket, winner);
+
+    let reset = bracket.matches().iter().find(|m| m.section == Section::GrandFinalReset).unwrap();
+    assert_eq!(reset.status, MatchStatus::Finished, "The losers bracket champion won, so the final is replayed");
+    assert_eq!(meetings.get(), 3);
+    assert_eq!(bracket.champion(), Some(NimPlayerId(1)));
+}
+
+#[test]
+fn test_host_tournament_single_elimination() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let players: Vec<NimPlayerId> = (1..=5).map(NimPlayerId).collect();
+
+    let mut bracket = Bracket::single_elimination(players.clone(), TieBreaker::HigherSeed);
+    let result = host_tournament(
+        &game,
+        perfect_factories(&players, &game),
+        &mut bracket,
+        &mut SimpleIdGenerator::new(),
+        Some(100),
+    );
+
+    // Four games are played, and the bracket is still inspectable afterwards
+    assert_eq!(result.values().sum::<i32>(), 4);
+    assert!(bracket.is_complete());
+    assert!(bracket.champion().is_some());
+}
+
+#[test]
+fn test_host_tournament_with_ratings() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let players = [NimPlayerId(1), NimPlayerId(2), NimPlayerId(3)];
+
+    // Without seat rotation, lower IDs always sit first and always win
+    let mut rated = Rated::new(RoundRobin::new(players, 2, 5), Glicko2::default());
+    let result = host_tournament(
+        &game,
+        perfect_factories(&players, &game),
+        &mut rated,
+        &mut SimpleIdGenerator::new(),
+        Some(100),
+    );
+
+    assert_eq!(result[&NimPlayerId(1)], 10, "Rating doesn't change the matchmaker's result");
+    let ratings = rated.rating_system().ratings();
+    assert!(ratings[&NimPlayerId(1)].value > ratings[&NimPlayerId(2)].value);
+    assert!(ratings[&NimPlayerId(2)].value > ratings[&NimPlayerId(3)].value);
+    assert!(ratings[&NimPlayerId(1)].deviation < 350.0);
+}
+
+#[test]
+fn test_host_tournament_report() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let players = [NimPlayerId(1), NimPlayerId(2), NimPlayerId(3)];
+
+    let mut round_robin = RoundRobin::new(players, 2, 2);
+    let report = host_tournament_with(
+        &game,
+        perfect_factories(&players, &game),
+        &mut round_robin,
+        &mut SimpleIdGenerator::new(),
+        &TournamentConfig::default(),
+    );
+
+    assert_eq!(report.result[&NimPlayerId(1)], 4);
+    assert_eq!(report.games.len(), 6);
+    assert_eq!(report.failed_games().count(), 0);
+
+    let mut game_ids: Vec<u32> = report.games.iter().map(|game| game.game_id.0).collect();
+    game_ids.sort();
+    assert_eq!(game_ids, vec![0, 1, 2, 3, 4, 5], "Every generated game ID is reported");
+    for game in &report.games {
+        assert_eq!(game.end(), GameEnd::Normal);
+        assert_eq!(game.turns, 5);
+        assert_eq!(game.players.len(), 2);
+    }
+
+    let records = report.win_draw_loss();
+    assert_eq!(records[&NimPlayerId(1)], Record { wins: 4, draws: 0, losses: 0 });
+    assert_eq!(records[&NimPlayerId(2)], Record { wins: 2, draws: 0, losses: 2 });
+    assert_eq!(records[&NimPlayerId(3)], Record { wins: 0, draws: 0, losses: 4 });
+
+    let head_to_head = report.head_to_head();
+    assert_eq!(head_to_head[&(NimPlayerId(2), NimPlayerId(3))], Record { wins: 2, draws: 0, losses: 0 });
+    assert_eq!(head_to_head[&(NimPlayerId(3), NimPlayerId(2))], Record { wins: 0, draws: 0, losses: 2 });
+}
+
+#[test]
+fn test_host_tournament_report_turn_limit() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let players = [NimPlayerId(1), NimPlayerId(2)];
+
+    let config = TournamentConfig {
+        simulation: SimulationConfig {
+            max_turns: Some(2),
+            ..SimulationConfig::default()
+        },
+        ..TournamentConfig::default()
+    };
+    let report = host_tournament_with(
+        &game,
+        perfect_factories(&players, &game),
+        &mut RoundRobin::new(players, 2, 1),
+        &mut SimpleIdGenerator::new(),
+        &config,
+    );
+
+    assert_eq!(report.games[0].end(), GameEnd::TurnLimit);
+    assert_eq!(report.games[0].turns, 2);
+    assert!(report.win_draw_loss().is_empty(), "Unfinished games don't count as results");
+}
+
+/// An agent that always tries to take no matches, which Nim rejects.
+struct ZeroAgent;
+
+impl Agent for ZeroAgent {
+    type Game = NimGameLogic;
+
+    fn digest_state(&mut self, _new_state: NimState) {}
+
+    fn calculate_next_move(&mut self, _new_state: NimState) -> NimMove {
+        NimMove { amount: 0 }
+    }
+}
+
+/// An agent that panics whenever it has to move.
+struct PanickingAgent;
+
+impl Agent for PanickingAgent {
+    type Game = NimGameLogic;
+
+    fn digest_state(&mut self, _new_state: NimState) {}
+
+    fn calculate_next_move(&mut self, _new_state: NimState) -> NimMove {
+        panic!("bot crashed")
+    }
+}
+
+/// Creates perfect agents, agents that always make invalid moves, or misbehaving agents.
+enum MixedFactory {
+    Perfect(PerfectFactory),
+    Zero,
+    Panicking,
+    PanicOnCreate,
+}
+
+impl AgentFactory for MixedFactory {
+    type Agent = Box<dyn Agent<Game = NimGameLogic> + Send>;
+
+    fn create_agent(&self) -> Self::Agent {
+        match self {
+            MixedFactory::Perfect(factory) => factory.create_agent(),
+            MixedFactory::Zero => Box::new(ZeroAgent),
+            MixedFactory::Panicking => Box::new(PanickingAgent),
+            MixedFactory::PanicOnCreate => panic!("factory crashed"),
+        }
+    }
+}
+
+fn mixed_factories(players: &[NimPlayerId], cheater: NimPlayerId, game: &NimGameLogic) -> HashMap<NimPlayerId, MixedFactory> {
+    players
+        .iter()
+        .map(|&pid| {
+            let factory = if pid == cheater {
+                MixedFactory::Zero
+            } else {
+                MixedFactory::Perfect(PerfectFactory::new(game.max_takes + 1))
+            };
+            (pid, factory)
+        })
+        .collect()
+}
+
+fn invalid_move_by(player: NimPlayerId) -> SimulationError<NimPlayerId> {
+    SimulationError::GameError(GameError::InvalidMove {
+        player,
+        reason: "test".to_string(),
+    })
+}
+
+#[test]
+fn test_failed_games_reach_the_matchmaker_with_culprit() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let players = [NimPlayerId(1), NimPlayerId(2)];
+
+    // Player 2 sits first and immediately makes an invalid move
+    let report = host_tournament_with(
+        &game,
+        mixed_factories(&players, NimPlayerId(2), &game),
+        &mut RoundRobin::new([NimPlayerId(2), NimPlayerId(1)], 2, 1),
+        &mut SimpleIdGenerator::new(),
+        &TournamentConfig::default(),
+    );
+
+    assert_eq!(report.games[0].end(), GameEnd::Error);
+    let error = report.games[0].result.as_ref().unwrap_err();
+    assert_eq!(error.culprit(), Some(NimPlayerId(2)));
+    assert_eq!(report.result[&NimPlayerId(1)], 0, "Ignored failures score nothing");
+}
+
+#[test]
+fn test_round_robin_forfeit_policy() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let players = [NimPlayerId(1), NimPlayerId(2), NimPlayerId(3)];
+
+    // Player 3 forfeits whenever it has to move; it always sits second, and
+    // perfect play from pile 10 means the second seat gets to move
+    let mut round_robin = RoundRobin::new(players, 2, 1).with_failure_policy(FailurePolicy::Forfeit { penalty: -5 });
+    let result = host_tournament(
+        &game,
+        mixed_factories(&players, NimPlayerId(3), &game),
+        &mut round_robin,
+        &mut SimpleIdGenerator::new(),
+        Some(100),
+    );
+
+    assert_eq!(result[&NimPlayerId(1)], 1);
+    assert_eq!(result[&NimPlayerId(2)], 0);
+    assert_eq!(result[&NimPlayerId(3)], -10);
+}
+
+#[test]
+fn test_round_robin_replay_policy() {
+    let mut round_robin: RoundRobin<NimPlayerId, TestGameId> =
+        RoundRobin::new([NimPlayerId(1), NimPlayerId(2)], 2, 1).with_failure_policy(FailurePolicy::Replay { max_replays: 1 });
+    let matchup = [NimPlayerId(1), NimPlayerId(2)];
+
+    match round_robin.digest_result(&game_report(TestGameId(0), &matchup, Err(SimulationError::MaxTurnsExceeded(10)))) {
+        MatchMakerOutput::Continue(games) => assert_eq!(games, vec![matchup.to_vec()], "The failed game is replayed"),
+        MatchMakerOutput::Done(_) => panic!("A replayed game is still outstanding"),
+    }
+
+    // Replays are used up, so the second failure is ignored and the tournament ends
+    let output = round_robin.digest_result(&game_report(TestGameId(1), &matchup, Err(SimulationError::MaxTurnsExceeded(10))));
+    assert!(matches!(output, MatchMakerOutput::Done(_)));
+}
+
+#[test]
+fn test_bracket_culprit_loses_the_match() {
+    let mut bracket: Bracket<NimPlayerId, TestGameId> =
+        Bracket::single_elimination([NimPlayerId(1), NimPlayerId(2)], TieBreaker::HigherSeed);
+
+    let output = bracket.digest_result(&game_report(TestGameId(0), &[NimPlayerId(1), NimPlayerId(2)], Err(invalid_move_by(NimPlayerId(1)))));
+
+    assert!(matches!(output, MatchMakerOutput::Done(_)));
+    assert_eq!(bracket.champion(), Some(NimPlayerId(2)), "The top seed is disqualified for its invalid move");
+}
+
+#[test]
+fn test_round_robin_points_for_draws() {
+    let players = [NimPlayerId(1), NimPlayerId(2), NimPlayerId(3)];
+    let mut round_robin: RoundRobin<NimPlayerId, TestGameId> =
+        RoundRobin::new(players, 2, 1).with_scoring(Scoring::Points { win: 3, draw: 1, loss: 0 });
+
+    // A draw scores nothing, but the outcome ranks both players first
+    let mut draw = game_report(TestGameId(0), &[NimPlayerId(1), NimPlayerId(2)], Ok(HashMap::new()));
+    draw.outcome = Some(Outcome::draw(&[NimPlayerId(1), NimPlayerId(2)]));
+    round_robin.digest_result(&draw);
+    round_robin.digest_result(&game_report(TestGameId(1), &[NimPlayerId(1), NimPlayerId(3)], Ok(HashMap::from([(NimPlayerId(1), 1)]))));
+
+    match round_robin.digest_result(&game_report(TestGameId(2), &[NimPlayerId(2), NimPlayerId(3)], Err(SimulationError::MaxTurnsExceeded(10)))) {
+        MatchMakerOutput::Done(totals) => {
+            assert_eq!(totals[&NimPlayerId(1)], 4, "A draw and a win");
+            assert_eq!(totals[&NimPlayerId(2)], 1, "A draw, and a failed game that isn't credited");
+            assert_eq!(totals[&NimPlayerId(3)], 0, "A loss");
+        }
+        MatchMakerOutput::Continue(_) => panic!("Every game has reported"),
+    }
+}
+
+#[test]
+fn test_bracket_follows_outcome_rankings() {
+    let mut bracket: Bracket<NimPlayerId, TestGameId> =
+        Bracket::single_elimination([NimPlayerId(1), NimPlayerId(2)], TieBreaker::HigherSeed);
+
+    // Both scored 0, but the outcome ranks the lower seed first
+    let mut report = game_report(TestGameId(0), &[NimPlayerId(1), NimPlayerId(2)], Ok(HashMap::new()));
+    report.outcome = Some(Outcome::win(NimPlayerId(2), &[NimPlayerId(1), NimPlayerId(2)]).with_scores(HashMap::new()));
+    bracket.digest_result(&report);
+
+    assert_eq!(bracket.champion(), Some(NimPlayerId(2)));
+}
+
+#[test]
+fn test_report_tells_forfeits_apart() {
+    let players = [NimPlayerId(1), NimPlayerId(2)];
+    let mut report = game_report(TestGameId(0), &players, Ok(HashMap::from([(NimPlayerId(1), -1)])));
+    assert_eq!(report.end(), GameEnd::Normal);
+
+    report.outcome = Some(Outcome::forfeit(NimPlayerId(1), -1, &players));
+    assert_eq!(report.end(), GameEnd::Forfeit);
+}
+
+#[test]
+fn test_rated_forfeits_without_penalty_are_losses() {
+    let players = [NimPlayerId(1), NimPlayerId(2)];
+    let mut rated = Rated::new(RoundRobin::<NimPlayerId, TestGameId>::new(players, 2, 1), Elo::default());
+
+    // Both score 0, as with a forfeit penalty of 0, but the outcome ranks the forfeiter last
+    let mut report = game_report(TestGameId(0), &players, Ok(HashMap::new()));
+    report.outcome = Some(Outcome::forfeit(NimPlayerId(1), 0, &players));
+    rated.digest_result(&report);
+
+    let elo = rated.rating_system();
+    assert!(elo.rating(NimPlayerId(2)).value > elo.rating(NimPlayerId(1)).value, "The forfeit counts as a loss, not a draw");
+}
+
+#[test]
+fn test_agent_panics_only_end_their_game() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let players = [NimPlayerId(1), NimPlayerId(2), NimPlayerId(3)];
+    let mut factories = mixed_factories(&players, NimPlayerId(3), &game);
+    factories.insert(NimPlayerId(3), MixedFactory::Panicking);
+
+    let report = host_tournament_with(
+        &game,
+        factories,
+        &mut RoundRobin::new(players, 2, 1).with_failure_policy(FailurePolicy::Forfeit { penalty: -5 }),
+        &mut SimpleIdGenerator::new(),
+        &TournamentConfig::default(),
+    );
+
+    assert_eq!(report.games.len(), 3, "Every game is played despite the panics");
+    for game in &report.games {
+        if game.players.contains(&NimPlayerId(3)) {
+            assert_eq!(game.end(), GameEnd::Panicked);
+            match game.result.as_ref().unwrap_err() {
+                SimulationError::AgentPanicked { player, message } => {
+                    assert_eq!(*player, NimPlayerId(3));
+                    assert_eq!(message, "bot crashed");
+                }
+                error => panic!("Unexpected error: {:?}", error),
+            }
+        } else {
+            assert_eq!(game.end(), GameEnd::Normal);
+        }
+    }
+    assert_eq!(report.result[&NimPlayerId(3)], -10);
+}
+
+#[test]
+fn test_factory_panics_are_reported_without_playing() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let players = [NimPlayerId(1), NimPlayerId(2)];
+    let mut factories = mixed_factories(&players, NimPlayerId(2), &game);
+    factories.insert(NimPlayerId(2), MixedFactory::PanicOnCreate);
+
+    let report = host_tournament_with(
+        &game,
+        factories,
+        &mut RoundRobin::new(players, 2, 2),
+        &mut SimpleIdGenerator::new(),
+        &TournamentConfig::default(),
+    );
+
+    assert_eq!(report.games.len(), 2);
+    for game in &report.games {
+        assert_eq!(game.end(), GameEnd::Panicked);
+        assert_eq!(game.turns, 0);
+        assert_eq!(game.result.as_ref().unwrap_err().culprit(), Some(NimPlayerId(2)));
+    }
+}
+
+/// A perfect agent that records how many agents are thinking at the same time.
+struct CountingAgent {
+    inner: Box<dyn Agent<Game = NimGameLogic> + Send>,
+    thinking: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+}
+
+impl Agent for CountingAgent {
+    type Game = NimGameLogic;
+
+    fn digest_state(&mut self, new_state: NimState) {
+        self.inner.digest_state(new_state);
+    }
+
+    fn calculate_next_move(&mut self, new_state: NimState) -> NimMove {
+        let now = self.thinking.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(now, Ordering::SeqCst);
+        std::thread::sleep(Duration::from_millis(2));
+        self.thinking.fetch_sub(1, Ordering::SeqCst);
+        self.inner.calculate_next_move(new_state)
+    }
+}
+
+struct CountingFactory {
+    inner: PerfectFactory,
+    thinking: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+}
+
+impl AgentFactory for CountingFactory {
+    type Agent = CountingAgent;
+
+    fn create_agent(&self) -> Self::Agent {
+        CountingAgent {
+            inner: self.inner.create_agent(),
+            thinking: self.thinking.clone(),
+            peak: self.peak.clone(),
+        }
+    }
+}
+
+#[test]
+fn test_host_tournament_respects_concurrency_limit() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let players: Vec<NimPlayerId> = (1..=6).map(NimPlayerId).collect();
+    let thinking = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let factories = players
+        .iter()
+        .map(|&pid| {
+            let factory = CountingFactory {
+                inner: PerfectFactory::new(game.max_takes + 1),
+                thinking: thinking.clone(),
+                peak: peak.clone(),
+            };
+            (pid, factory)
+        })
+        .collect();
+
+    let config = TournamentConfig {
+        max_concurrent_games: Some(2),
+        ..TournamentConfig::default()
+    };
+    let report = host_tournament_with(
+        &game,
+        factories,
+        &mut RoundRobin::new(players, 2, 1),
+        &mut SimpleIdGenerator::new(),
+        &config,
+    );
+
+    // Nim is sequential, so each running game has at most one agent thinking
+    assert_eq!(report.games.len(), 15, "Queued games still all get played");
+    assert!(peak.load(Ordering::SeqCst) <= 2);
+}
+
+#[test]
+fn test_host_tournament_results_independent_of_pool_size() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let players: Vec<NimPlayerId> = (1..=8).map(NimPlayerId).collect();
+
+    let run = |workers| {
+        let config = TournamentConfig {
+            max_concurrent_games: Some(workers),
+            ..TournamentConfig::default()
+        };
+        let mut swiss = Swiss::new(players.clone(), 3, 1);
+        let report = host_tournament_with(
+            &game,
+            perfect_factories(&players, &game),
+            &mut swiss,
+            &mut SimpleIdGenerator::new(),
+            &config,
+        );
+        let games: Vec<(u32, Vec<NimPlayerId>)> =
+            report.games.iter().map(|game| (game.game_id.0, game.players.clone())).collect();
+        (report.result, games)
+    };
+
+    let sequential = run(1);
+    for workers in [2, 3, 16] {
+        assert_eq!(run(workers), sequential, "Results changed with {} workers", workers);
+    }
+}
+
+/// The ID, seating, scores and length of every game of a tournament.
+type GameLog = Vec<(TestGameId, Vec<NimPlayerId>, HashMap<NimPlayerId, i32>, usize)>;
+
+/// Runs a seeded round robin between random agents and returns the result and a log of every game.
+fn seeded_random_tournament(seed: u64, workers: usize) -> (HashMap<NimPlayerId, i32>, GameLog) {
+    seeded_random_tournament_hosted(seed, workers, false)
+}
+
+/// Makes the agents of a sync factory async.
+struct AsyncFactory<F>(F);
+
+impl<F: AgentFactory> AgentFactory for AsyncFactory<F> {
+    type Agent = AsyncAdapter<F::Agent>;
+
+    fn create_agent(&self) -> Self::Agent {
+        AsyncAdapter(self.0.create_agent())
+    }
+
+    fn create_agent_seeded(&self, seed: u64) -> Self::Agent {
+        AsyncAdapter(self.0.create_agent_seeded(seed))
+    }
+}
+
+fn seeded_random_tournament_hosted(seed: u64, workers: usize, hosted_async: bool) -> (HashMap<NimPlayerId, i32>, GameLog) {
+    let game = NimGameLogic {
+        initial_pile_size: 21,
+        max_takes: 4,
+    };
+    let players = [NimPlayerId(1), NimPlayerId(2), NimPlayerId(3), NimPlayerId(4)];
+    let factories: HashMap<NimPlayerId, RandomFactory> = players.iter().map(|&pid| (pid, RandomFactory::new(game.max_takes))).collect();
+
+    let config = TournamentConfig {
+        max_concurrent_games: Some(workers),
+        seed: Some(seed),
+        ..TournamentConfig::default()
+    };
+    let mut matchmaker = RoundRobin::new(players, 2, 5).with_seat_rotation();
+    let report = if hosted_async {
+        let factories: HashMap<NimPlayerId, _> = factories.into_iter().map(|(pid, factory)| (pid, AsyncFactory(factory))).collect();
+        block_on(host_tournament_async(&game, factories, &mut matchmaker, &mut SimpleIdGenerator::new(), &config, &[]))
+    } else {
+        host_tournament_with(&game, factories, &mut matchmaker, &mut SimpleIdGenerator::new(), &config)
+    };
+
+    let log = report
+        .games
+        .into_iter()
+        .map(|game| (game.game_id, game.players, game.result.expect("Random agents make valid moves"), game.turns))
+        .collect();
+    (report.result, log)
+}
+
+#[test]
+fn test_seeded_tournaments_are_reproducible() {
+    let (result, log) = seeded_random_tournament(42, 4);
+
+    assert_eq!(seeded_random_tournament(42, 4), (result.clone(), log.clone()));
+    assert_eq!(seeded_random_tournament(42, 1), (result, log.clone()), "The pool size doesn't matter");
+    assert_ne!(seeded_random_tournament(7, 4).1, log, "Another seed plays other games");
+}
+
+#[test]
+fn test_async_tournaments_play_the_same_games() {
+    let expected = seeded_random_tournament(42, 4);
+
+    assert_eq!(seeded_random_tournament_hosted(42, 4, true), expected);
+    assert_eq!(seeded_random_tournament_hosted(42, 1, true), expected, "The number of concurrent games doesn't matter");
+}
+
+/// Collects the turn and game over events of every game of a tournament.
+#[derive(Default)]
+struct GameEvents {
+    turns: Mutex<HashMap<TestGameId, usize>>,
+    finished: Mutex<Vec<TestGameId>>,
+}
+
+impl TournamentObserver<NimGameLogic, TestGameId> for GameEvents {
+    fn on_turn_start(&self, game_id: TestGameId, turn: usize, _state: &NimState, _active_players: &HashSet<NimPlayerId>) {
+        let mut turns = self.turns.lock().unwrap();
+        let last = turns.entry(game_id).or_insert(0);
+        assert_eq!(*last + 1, turn, "Turns of a game arrive in order");
+        *last = turn;
+    }
+
+    fn on_game_over(&self, game_id: TestGameId, _scores: &FinalScores<NimPlayerId>) {
+        self.finished.lock().unwrap().push(game_id);
+    }
+}
+
+#[test]
+fn test_tournament_observers_get_game_ids() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let players = [NimPlayerId(1), NimPlayerId(2), NimPlayerId(3), NimPlayerId(4)];
+
+    let events = GameEvents::default();
+    let report = host_tournament_with_observers(
+        &game,
+        perfect_factories(&players, &game),
+        &mut RoundRobin::new(players, 2, 1),
+        &mut SimpleIdGenerator::new(),
+        &TournamentConfig::default(),
+        &[&events],
+    );
+
+    let mut finished = events.finished.into_inner().unwrap();
+    finished.sort_by_key(|id| id.0);
+    let mut played: Vec<TestGameId> = report.games.iter().map(|game| game.game_id).collect();
+    played.sort_by_key(|id| id.0);
+    assert_eq!(finished, played);
+
+    let turns = events.turns.into_inner().unwrap();
+    for game in &report.games {
+        assert_eq!(turns[&game.game_id], game.turns);
+    }
+}
+
+/// An observer that panics during the second turn of one game.
+struct PanickingObserver(TestGameId);
+
+impl TournamentObserver<NimGameLogic, TestGameId> for PanickingObserver {
+    fn on_turn_start(&self, game_id: TestGameId, turn: usize, _state: &NimState, _active_players: &HashSet<NimPlayerId>) {
+        assert!(game_id != self.0 || turn != 2, "observer failure");
+    }
+}
+
+#[test]
+fn test_observer_panics_only_fail_their_game() {
+    let game = NimGameLogic {
+        initial_pile_size: 10,
+        max_takes: 3,
+    };
+    let players = [NimPlayerId(1), NimPlayerId(2), NimPlayerId(3), NimPlayerId(4)];
+    let observer = PanickingObserver(TestGameId(1));
+    let config = TournamentConfig {
+        max_concurrent_games: Some(2),
+        ..TournamentConfig::default()
+    };
+
+    let hosted = host_tournament_with_observers(
+        &game,
+        perfect_factories(&players, &game),
+        &mut RoundRobin::new(players, 2, 1),
+        &mut SimpleIdGenerator::new(),
+        &config,
+        &[&observer],
+    );
+    let factories: HashMap<NimPlayerId, _> = perfect_factories(&players, &game).into_iter().map(|(pid, factory)| (pid, AsyncFactory(factory))).collect();
+    let hosted_async = block_on(host_tournament_async(
+        &game,
+        factories,
+        &mut RoundRobin::new(players, 2, 1),
+        &mut SimpleIdGenerator::new(),
+        &config,
+        &[&observer],
+    ));
+
+    for report in [hosted, hosted_async] {
+        assert_eq!(report.games.len(), 6, "The other games are still played");
+        let failed: Vec<_> = report.failed_games().collect();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].game_id, TestGameId(1));
+        assert_eq!(failed[0].end(), GameEnd::Panicked);
+        match &failed[0].result {
+            Err(SimulationError::ObserverPanicked(message)) => assert_eq!(message, "observer failure"),
+            result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+}